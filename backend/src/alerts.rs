@@ -0,0 +1,227 @@
+/*!
+ * Configurable Threshold Alerts
+ *
+ * Evaluates the `[[alerts]]` rules against the PDM state on every channel
+ * monitoring cycle. A rule whose condition holds for its `for_ms` is raised
+ * as an active alert and reported as an `alert` event, which reaches the
+ * event stream and webhooks; it clears once the reading is back past the
+ * threshold by the rule's hysteresis. Alerts only notify: tripping and
+ * shutdowns stay with the safety limits.
+ */
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::{AlertCondition, AlertMetric, AlertRuleConfig, AlertSeverity};
+use crate::events::{EventKind, EventLog};
+use crate::models::PdmState;
+
+/// An alert that is currently raised
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveAlert {
+    pub name: String,
+    pub severity: AlertSeverity,
+    pub channel: Option<u8>,
+    pub message: String,
+    /// Reading that raised the alert
+    pub value: f32,
+    pub threshold: f32,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    config: AlertRuleConfig,
+    /// When the condition started holding, while waiting out `for_ms`
+    pending_since: Option<Instant>,
+    active: Option<ActiveAlert>,
+}
+
+impl Rule {
+    /// The rule's reading from the state (None if its channel is missing)
+    fn read(&self, state: &PdmState) -> Option<f32> {
+        let channel = || self.config.channel.and_then(|ch| state.channels.get(&ch));
+        match self.config.metric {
+            AlertMetric::InputVoltage => Some(state.input_voltage),
+            AlertMetric::InputCurrent => Some(state.input_current),
+            AlertMetric::TotalCurrent => Some(state.total_current),
+            AlertMetric::Temperature => Some(state.temperature),
+            AlertMetric::InputRipple => Some(state.input_ripple_pp),
+            AlertMetric::ChannelCurrent => channel().map(|channel| channel.current),
+            AlertMetric::ChannelTemperature => channel().and_then(|channel| channel.temperature),
+        }
+    }
+
+    /// Whether `value` is past the threshold, moved back by `margin`
+    fn breached(&self, value: f32, margin: f32) -> bool {
+        match self.config.condition {
+            AlertCondition::Above => value > self.config.threshold - margin,
+            AlertCondition::Below => value < self.config.threshold + margin,
+        }
+    }
+
+    fn message(&self, value: f32) -> String {
+        let config = &self.config;
+        let template = config.message.clone().unwrap_or_else(|| {
+            let side = match config.condition {
+                AlertCondition::Above => "above",
+                AlertCondition::Below => "below",
+            };
+            format!("{{name}}: {:?} {{value}} {} {{threshold}}", config.metric, side)
+        });
+        template
+            .replace("{name}", &config.name)
+            .replace("{value}", &format!("{:.2}", value))
+            .replace("{threshold}", &format!("{:.2}", config.threshold))
+            .replace("{channel}", &config.channel.map_or_else(|| "-".to_string(), |ch| ch.to_string()))
+    }
+}
+
+/// Evaluates the configured alert rules
+#[derive(Debug)]
+pub struct AlertEngine {
+    rules: Vec<Rule>,
+}
+
+impl AlertEngine {
+    /// Check the alert rules
+    pub fn new(rules: &[AlertRuleConfig], channel_count: u8) -> Result<Self> {
+        let mut names = HashSet::new();
+        for rule in rules {
+            if rule.name.trim().is_empty() || !names.insert(rule.name.as_str()) {
+                bail!("Alert name {:?} must be non-empty and unique", rule.name);
+            }
+            match rule.channel {
+                None if rule.metric.per_channel() => {
+                    bail!("Alert '{}' needs a channel for {:?}", rule.name, rule.metric);
+                }
+                Some(ch) if ch == 0 || ch > channel_count => {
+                    bail!("Channel {} in alert '{}' is outside the configured range 1-{}", ch, rule.name, channel_count);
+                }
+                _ => {}
+            }
+            if !rule.threshold.is_finite() {
+                bail!("Alert '{}' threshold must be a number", rule.name);
+            }
+            if !(rule.hysteresis.is_finite() && rule.hysteresis >= 0.0) {
+                bail!("Alert '{}' hysteresis must not be negative", rule.name);
+            }
+        }
+
+        let rules = rules.iter()
+            .map(|config| Rule { config: config.clone(), pending_since: None, active: None })
+            .collect();
+        Ok(Self { rules })
+    }
+
+    /// Raise and clear alerts for the current state
+    pub fn evaluate(&mut self, state: &PdmState, now: Instant, events: &EventLog) {
+        for rule in &mut self.rules {
+            let Some(value) = rule.read(state) else {
+                rule.pending_since = None;
+                continue;
+            };
+
+            if rule.active.is_some() {
+                if !rule.breached(value, rule.config.hysteresis) {
+                    let alert = rule.active.take().expect("checked above");
+                    rule.pending_since = None;
+                    info!("Alert '{}' cleared at {:.2}", alert.name, value);
+                    events.emit(EventKind::AlertCleared, alert.channel,
+                                format!("{} cleared: {}", alert.name, rule.message(value)));
+                }
+                continue;
+            }
+
+            if !rule.breached(value, 0.0) {
+                rule.pending_since = None;
+                continue;
+            }
+            let since = *rule.pending_since.get_or_insert(now);
+            if now.saturating_duration_since(since) < Duration::from_millis(rule.config.for_ms) {
+                continue;
+            }
+
+            let alert = ActiveAlert {
+                name: rule.config.name.clone(),
+                severity: rule.config.severity,
+                channel: rule.config.channel,
+                message: rule.message(value),
+                value,
+                threshold: rule.config.threshold,
+                since: Utc::now(),
+            };
+            warn!("{:?} alert '{}': {}", alert.severity, alert.name, alert.message);
+            events.emit(EventKind::Alert, alert.channel, format!("[{:?}] {}", alert.severity, alert.message));
+            rule.active = Some(alert);
+        }
+    }
+
+    /// Alerts currently raised, most severe first
+    pub fn active(&self) -> Vec<ActiveAlert> {
+        let mut active: Vec<ActiveAlert> = self.rules.iter().filter_map(|rule| rule.active.clone()).collect();
+        active.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.since.cmp(&b.since)));
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_alert_rule_debounce_and_hysteresis() {
+        use crate::alerts::AlertEngine;
+        use crate::config::{AlertCondition, AlertMetric, AlertRuleConfig, AlertSeverity};
+        use crate::events::{EventKind, EventLog};
+        use std::time::{Duration, Instant};
+        
+        let rule = AlertRuleConfig {
+            name: "low_voltage".to_string(),
+            metric: AlertMetric::InputVoltage,
+            channel: None,
+            condition: AlertCondition::Below,
+            threshold: 12.0,
+            for_ms: 5000,
+            hysteresis: 0.5,
+            severity: AlertSeverity::Critical,
+            message: Some("Battery at {value}V".to_string()),
+        };
+        assert!(AlertEngine::new(&[AlertRuleConfig { metric: AlertMetric::ChannelCurrent, ..rule.clone() }], 8).is_err());
+        
+        let mut engine = AlertEngine::new(&[rule], 8).unwrap();
+        let events = EventLog::new();
+        let mut state = PdmState::new();
+        let start = Instant::now();
+        
+        // A dip shorter than for_ms is ignored
+        state.input_voltage = 11.5;
+        engine.evaluate(&state, start, &events);
+        state.input_voltage = 12.5;
+        engine.evaluate(&state, start + Duration::from_secs(1), &events);
+        state.input_voltage = 11.5;
+        engine.evaluate(&state, start + Duration::from_secs(2), &events);
+        engine.evaluate(&state, start + Duration::from_secs(6), &events);
+        assert!(engine.active().is_empty());
+        
+        engine.evaluate(&state, start + Duration::from_secs(7), &events);
+        let active = engine.active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].message, "Battery at 11.50V");
+        assert_eq!(events.recent(10).last().unwrap().kind, EventKind::Alert);
+        
+        // Inside the hysteresis band the alert stays raised
+        state.input_voltage = 12.3;
+        engine.evaluate(&state, start + Duration::from_secs(8), &events);
+        assert_eq!(engine.active().len(), 1);
+        
+        state.input_voltage = 12.6;
+        engine.evaluate(&state, start + Duration::from_secs(9), &events);
+        assert!(engine.active().is_empty());
+        assert_eq!(events.recent(10).last().unwrap().kind, EventKind::AlertCleared);
+    }
+}
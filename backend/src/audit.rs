@@ -0,0 +1,103 @@
+/*!
+ * Audit Log for PDM Backend
+ *
+ * Every state-changing API call is appended to a newline-delimited JSON
+ * file so control actions can be traced after the fact:
+ * - What was requested (endpoint, channel, request body)
+ * - Who requested it (identity, source IP)
+ * - What happened (HTTP status)
+ */
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::config::AuditConfig;
+
+/// A single audited API call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// HTTP method and path (e.g. "POST /api/channel/1/toggle")
+    pub endpoint: String,
+    /// Channel addressed by the request, if any
+    pub channel: Option<u8>,
+    /// Requested action (the JSON request body, if any)
+    pub action: Option<Value>,
+    /// Authenticated identity (None when authentication is not enabled)
+    pub identity: Option<String>,
+    /// Client address
+    pub source_ip: Option<String>,
+    /// HTTP status code returned
+    pub status: u16,
+    /// Whether the request succeeded
+    pub success: bool,
+}
+
+/// Append-only audit log backed by a file
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    // Serializes appends so entries never interleave
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Create an audit log from configuration
+    pub fn new(config: &AuditConfig) -> Self {
+        Self {
+            path: config.enabled.then(|| PathBuf::from(&config.log_path)),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Whether audit logging is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Append an entry to the log file
+    pub async fn record(&self, entry: &AuditEntry) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Read the most recent `limit` entries, oldest first
+    pub async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let lines: Vec<&str> = contents.lines().filter(|line| !line.trim().is_empty()).collect();
+        let start = lines.len().saturating_sub(limit);
+
+        // Skip lines that fail to parse rather than failing the whole request
+        Ok(lines[start..]
+            .iter()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
@@ -0,0 +1,57 @@
+/*!
+ * Battery State of Charge
+ *
+ * Estimates the charge left in the battery feeding the PDM, for off-grid
+ * installations. Between readings the estimate follows the PDM's input
+ * current (coulomb counting). Whenever the draw is small enough for the
+ * terminal voltage to approach the resting voltage, the estimate is pulled a
+ * little toward the configured voltage-to-SoC curve: that corrects counting
+ * drift and picks up charging, which the PDM cannot measure. The first
+ * estimate comes from the curve alone.
+ */
+
+use crate::config::{BatteryConfig, SocPoint};
+
+/// State of charge (%) the curve gives for a resting voltage
+///
+/// Linear between the points and clamped to the ends of the curve.
+pub fn soc_from_voltage(curve: &[SocPoint], voltage: f32) -> f32 {
+    let (Some(first), Some(last)) = (curve.first(), curve.last()) else { return 0.0 };
+    if voltage <= first.voltage {
+        return first.soc;
+    }
+    curve.windows(2)
+        .find(|pair| voltage <= pair[1].voltage)
+        .map_or(last.soc, |pair| {
+            let (low, high) = (pair[0], pair[1]);
+            low.soc + (high.soc - low.soc) * (voltage - low.voltage) / (high.voltage - low.voltage)
+        })
+}
+
+/// Running state-of-charge estimate
+#[derive(Debug, Default)]
+pub struct SocEstimator {
+    /// Estimate (%), kept in f64 so the tiny per-cycle steps add up
+    soc: Option<f64>,
+}
+
+impl SocEstimator {
+    /// Advance the estimate by one status cycle of `seconds`, drawing `current` (A) at `voltage`
+    pub fn update(&mut self, config: &BatteryConfig, voltage: f32, current: f32, seconds: f64) -> f32 {
+        let from_voltage = f64::from(soc_from_voltage(&config.soc_curve, voltage));
+        let soc = match self.soc {
+            None => from_voltage,
+            Some(soc) => {
+                let drawn_ah = f64::from(current) * seconds / 3600.0;
+                let mut soc = soc - drawn_ah / f64::from(config.capacity_ah) * 100.0;
+                if current.abs() < config.rest_current {
+                    soc += (from_voltage - soc) * f64::from(config.voltage_correction);
+                }
+                soc
+            }
+        }
+        .clamp(0.0, 100.0);
+        self.soc = Some(soc);
+        soc as f32
+    }
+}
@@ -0,0 +1,250 @@
+/*!
+ * CAN Telemetry Output
+ *
+ * Broadcasts PDM telemetry on the vehicle CAN bus so existing dashboards can
+ * read the PDM as a native node. Frames are described in config the same way
+ * a DBC file describes them (identifier, start bit, length, byte order,
+ * scale/offset) and are encoded from the live state at a fixed rate.
+ */
+
+use anyhow::{bail, Context, Result};
+use socketcan::{CanFrame, CanSocket, EmbeddedFrame, ExtendedId, Id, Socket, StandardId};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use tracing::{debug, info, warn};
+
+use crate::config::{CanByteOrder, CanMessageConfig, CanMetric, CanOutputConfig, CanSignalConfig};
+use crate::models::{ChannelStatus, PdmState};
+
+/// Transmits the configured telemetry frames
+pub struct CanBroadcaster {
+    config: CanOutputConfig,
+    interface: String,
+}
+
+impl CanBroadcaster {
+    /// Create a broadcaster, validating the signal mapping up front
+    pub fn new(config: CanOutputConfig, interface: Option<String>) -> Result<Self> {
+        validate(&config)?;
+        let Some(interface) = interface else {
+            bail!("CAN output is enabled but hardware.can_interface is not set");
+        };
+
+        Ok(Self { config, interface })
+    }
+
+    /// Start the transmit loop
+    pub async fn start(&self, pdm_state: Arc<RwLock<PdmState>>) -> Result<()> {
+        let socket = CanSocket::open(&self.interface)
+            .with_context(|| format!("Failed to open CAN interface {}", self.interface))?;
+        // Never stall the runtime on a full transmit queue; the frame is resent next cycle anyway
+        socket.set_nonblocking(true)?;
+
+        info!("Broadcasting {} CAN telemetry frames on {} every {}ms",
+              self.config.messages.len(), self.interface, self.config.interval_ms);
+
+        let mut tx_interval = interval(Duration::from_millis(self.config.interval_ms));
+        let mut failing = false;
+
+        loop {
+            tx_interval.tick().await;
+
+            let frames: Vec<CanFrame> = {
+                let state = pdm_state.read().await;
+                self.config.messages.iter()
+                    .filter_map(|message| build_frame(message, &state))
+                    .collect()
+            };
+
+            for frame in &frames {
+                match socket.write_frame(frame) {
+                    Ok(()) => failing = false,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        debug!("CAN transmit queue full, frame dropped");
+                    }
+                    Err(e) => {
+                        // Log once per outage rather than every frame
+                        if !failing {
+                            warn!("CAN telemetry transmit failed: {}", e);
+                        }
+                        failing = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check that every message and signal fits its frame
+pub fn validate(config: &CanOutputConfig) -> Result<()> {
+    if config.enabled && config.interval_ms == 0 {
+        bail!("can_output.interval_ms must be greater than 0");
+    }
+
+    for message in &config.messages {
+        let max_id = if message.extended { ExtendedId::MAX.as_raw() } else { StandardId::MAX.as_raw() as u32 };
+        if message.id > max_id {
+            bail!("CAN id {:#X} does not fit a {} identifier", message.id,
+                  if message.extended { "29-bit" } else { "11-bit" });
+        }
+        if message.dlc > 8 {
+            bail!("CAN id {:#X}: dlc {} is larger than 8 bytes", message.id, message.dlc);
+        }
+
+        for signal in &message.signals {
+            if signal.length == 0 || signal.length > 64 {
+                bail!("CAN id {:#X}: {:?} length must be 1-64 bits", message.id, signal.metric);
+            }
+            if signal.scale == 0.0 {
+                bail!("CAN id {:#X}: {:?} scale must not be zero", message.id, signal.metric);
+            }
+            if signal.metric.is_per_channel() && signal.channel.is_none() {
+                bail!("CAN id {:#X}: {:?} needs a channel", message.id, signal.metric);
+            }
+            let fits = signal_bit_positions(signal)
+                .is_some_and(|positions| positions.iter().all(|&pos| pos < message.dlc as u16 * 8));
+            if !fits {
+                bail!("CAN id {:#X}: {:?} at start bit {} does not fit in {} bytes",
+                      message.id, signal.metric, signal.start_bit, message.dlc);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encode a message's payload from the current state
+pub fn encode_message(message: &CanMessageConfig, state: &PdmState) -> Vec<u8> {
+    let mut data = vec![0u8; message.dlc as usize];
+
+    for signal in &message.signals {
+        let raw = raw_value(signal, metric_value(signal, state));
+        let Some(positions) = signal_bit_positions(signal) else { continue };
+
+        for (bit, pos) in positions.into_iter().enumerate() {
+            let Some(byte) = data.get_mut(pos as usize / 8) else { continue };
+            if (raw >> bit) & 1 == 1 {
+                *byte |= 1 << (pos % 8);
+            }
+        }
+    }
+
+    data
+}
+
+/// Build the frame for a message
+fn build_frame(message: &CanMessageConfig, state: &PdmState) -> Option<CanFrame> {
+    let id: Id = if message.extended {
+        ExtendedId::new(message.id)?.into()
+    } else {
+        StandardId::new(message.id as u16)?.into()
+    };
+    CanFrame::new(id, &encode_message(message, state))
+}
+
+/// Physical value of a signal's metric
+fn metric_value(signal: &CanSignalConfig, state: &PdmState) -> f32 {
+    let channel = signal.channel.and_then(|ch| state.channels.get(&ch));
+
+    match signal.metric {
+        CanMetric::InputVoltage => state.input_voltage,
+        CanMetric::TotalCurrent => state.total_current,
+        CanMetric::TotalPower => state.total_power(),
+        CanMetric::Temperature => state.temperature,
+        CanMetric::SystemStatus => state.system_status.severity() as f32,
+        CanMetric::ChannelCurrent => channel.map_or(0.0, |c| c.current),
+        CanMetric::ChannelVoltage => channel.map_or(0.0, |c| c.voltage),
+        CanMetric::ChannelStatus => match channel.map(|c| &c.status) {
+            Some(ChannelStatus::On) => 1.0,
+            Some(ChannelStatus::Fault) => 2.0,
+            Some(ChannelStatus::Off) | None => 0.0,
+        },
+    }
+}
+
+/// Scale a physical value to its raw bits, saturating at the signal's range
+fn raw_value(signal: &CanSignalConfig, value: f32) -> u64 {
+    let length = signal.length as i32;
+    let (min, max) = if signal.signed {
+        (-(2f64.powi(length - 1)), 2f64.powi(length - 1) - 1.0)
+    } else {
+        (0.0, 2f64.powi(length) - 1.0)
+    };
+
+    let raw = ((value as f64 - signal.offset as f64) / signal.scale as f64).round();
+    let raw = if raw.is_nan() { 0.0 } else { raw.clamp(min, max) };
+    let mask = if signal.length >= 64 { u64::MAX } else { (1u64 << signal.length) - 1 };
+
+    (raw as i64 as u64) & mask
+}
+
+/// Payload bit positions of a signal, least significant bit first
+///
+/// Positions use DBC numbering (byte * 8 + bit). Motorola signals start at
+/// their most significant bit and run "sawtooth" through the bytes.
+fn signal_bit_positions(signal: &CanSignalConfig) -> Option<Vec<u16>> {
+    let length = signal.length as i32;
+    let start = signal.start_bit as i32;
+
+    let positions: Vec<i32> = match signal.byte_order {
+        CanByteOrder::LittleEndian => (start..start + length).collect(),
+        CanByteOrder::BigEndian => {
+            let mut msb_first = Vec::with_capacity(length as usize);
+            let mut pos = start;
+            for _ in 0..length {
+                msb_first.push(pos);
+                pos = if pos % 8 == 0 { pos + 15 } else { pos - 1 };
+            }
+            msb_first.into_iter().rev().collect()
+        }
+    };
+
+    positions.into_iter().map(|pos| u16::try_from(pos).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_can_signal_encoding() {
+        use crate::can_output::{encode_message, validate};
+        use crate::config::{CanByteOrder, CanMessageConfig, CanMetric, CanOutputConfig, CanSignalConfig};
+        
+        let signal = |metric, start_bit, byte_order, scale| CanSignalConfig {
+            metric,
+            channel: None,
+            start_bit,
+            length: 16,
+            byte_order,
+            signed: false,
+            scale,
+            offset: 0.0,
+        };
+        let message = CanMessageConfig {
+            id: 0x650,
+            extended: false,
+            dlc: 8,
+            signals: vec![
+                signal(CanMetric::InputVoltage, 0, CanByteOrder::LittleEndian, 0.01),
+                // Motorola: MSB at bit 23 (byte 2), LSB in byte 3
+                signal(CanMetric::TotalCurrent, 23, CanByteOrder::BigEndian, 0.1),
+            ],
+        };
+        
+        let mut state = PdmState::new();
+        state.input_voltage = 13.8;  // 1380 = 0x0564
+        state.total_current = 25.6;  // 256 = 0x0100
+        assert_eq!(encode_message(&message, &state), vec![0x64, 0x05, 0x01, 0x00, 0, 0, 0, 0]);
+        
+        // Values beyond the signal range saturate instead of wrapping
+        state.input_voltage = 1000.0;
+        assert_eq!(&encode_message(&message, &state)[..2], &[0xFF, 0xFF]);
+        
+        // A signal running past the frame is rejected
+        let mut config = CanOutputConfig { messages: vec![message], ..Default::default() };
+        config.messages[0].dlc = 3;
+        assert!(validate(&config).is_err());
+    }
+}
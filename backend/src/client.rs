@@ -0,0 +1,202 @@
+/*!
+ * Typed API Client
+ *
+ * `PdmClient` wraps the HTTP API for Rust tools that drive or monitor a
+ * PDM backend. It sends and receives the same types the server uses (see
+ * `crate::models`), so clients and server cannot drift apart. Errors from
+ * the API carry the server's `error` message. Responses from a server that
+ * envelopes them (`response_envelope`) are unwrapped.
+ */
+
+use anyhow::{anyhow, Result};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::api::unwrap_envelope;
+use crate::history::HistorySample;
+pub use crate::models::{
+    ChannelAction, ChannelControlRequest, EmergencyShutdownRequest, ShutdownReport, SystemStatusResponse,
+};
+
+/// Client for one PDM backend
+#[derive(Debug, Clone)]
+pub struct PdmClient {
+    client: reqwest::Client,
+    /// Server URL including the API base path, without a trailing '/'
+    base_url: String,
+}
+
+impl PdmClient {
+    /// Client for a backend at `url` (e.g. "http://localhost:3030") using the default "/api" base path
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_base_path(url, "/api")
+    }
+
+    /// Client for a backend serving its API under a custom `api_base_path`
+    pub fn with_base_path(url: &str, base_path: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        Ok(Self::with_client(client, url, base_path))
+    }
+
+    /// Client reusing an existing `reqwest::Client` (timeouts, proxies, ...)
+    pub fn with_client(client: reqwest::Client, url: &str, base_path: &str) -> Self {
+        let base_path = base_path.trim_matches('/');
+        let base_url = if base_path.is_empty() {
+            url.trim_end_matches('/').to_string()
+        } else {
+            format!("{}/{}", url.trim_end_matches('/'), base_path)
+        };
+        Self { client, base_url }
+    }
+
+    /// Current PDM status (`GET /status`)
+    pub async fn status(&self) -> Result<SystemStatusResponse> {
+        send(self.client.get(self.url("/status"))).await
+    }
+
+    /// Apply a control action to a channel (`POST /channel/{id}/control`)
+    pub async fn control(&self, channel: u8, action: ChannelAction) -> Result<Value> {
+        self.control_request(&ChannelControlRequest { channel, action, swap_off: None, expected_seq: None }).await
+    }
+
+    /// Send a full control request, e.g. with `swap_off` or `expected_seq` set
+    pub async fn control_request(&self, request: &ChannelControlRequest) -> Result<Value> {
+        let url = self.url(&format!("/channel/{}/control", request.channel));
+        send(self.client.post(url).json(request)).await
+    }
+
+    /// Toggle a channel on/off (`POST /channel/{id}/toggle`)
+    pub async fn toggle(&self, channel: u8) -> Result<Value> {
+        send(self.client.post(self.url(&format!("/channel/{}/toggle", channel)))).await
+    }
+
+    /// Switch every channel off (`POST /emergency-shutdown`)
+    ///
+    /// If the server requires a confirmation token (428), one is fetched and
+    /// the request sent again: calling this method is the confirmation.
+    /// A shutdown the hardware could not confirm is still a report, not an
+    /// error: check `confirmed_all_off` and `unconfirmed_channels`.
+    pub async fn emergency_shutdown(&self, reason: &str) -> Result<ShutdownReport> {
+        let mut request = EmergencyShutdownRequest { reason: reason.to_string(), token: None };
+        let mut response = self.client.post(self.url("/emergency-shutdown")).json(&request).send().await?;
+        if response.status() == StatusCode::PRECONDITION_REQUIRED {
+            request.token = Some(self.shutdown_token().await?);
+            response = self.client.post(self.url("/emergency-shutdown")).json(&request).send().await?;
+        }
+        if response.status() == StatusCode::BAD_GATEWAY {
+            return Ok(serde_json::from_value(unwrap_envelope(response.json().await?))?);
+        }
+        parse(response).await
+    }
+
+    /// A one-time emergency shutdown confirmation token (`GET /emergency-shutdown/token`)
+    pub async fn shutdown_token(&self) -> Result<String> {
+        let body: Value = send(self.client.get(self.url("/emergency-shutdown/token"))).await?;
+        body.get("token")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("No token in the /emergency-shutdown/token response"))
+    }
+
+    /// Recent aggregated history, newest last (`GET /history`)
+    pub async fn history(&self, limit: Option<usize>) -> Result<Vec<HistorySample>> {
+        let mut request = self.client.get(self.url("/history"));
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+        send(request).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    parse(request.send().await?).await
+}
+
+/// Decode a successful response, or turn an error response into its message
+async fn parse<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(serde_json::from_value(unwrap_envelope(response.json().await?))?);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<Value>(&body)
+        .ok()
+        .map(unwrap_envelope)
+        .and_then(|body| body.get("error").and_then(Value::as_str).map(str::to_string))
+        .unwrap_or(body);
+    Err(anyhow!("{} ({})", message, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::ChannelStatus;
+    use crate::test_support::test_app;
+    
+    #[tokio::test]
+    async fn test_pdm_client_round_trip() {
+        use crate::client::{ChannelAction, PdmClient};
+        
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        let (app, pdm_state, _) = test_app(config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+            axum::serve(listener, service).await.unwrap();
+        });
+        
+        let client = PdmClient::new(&format!("http://{}/", addr)).unwrap();
+        client.control(1, ChannelAction::TurnOn).await.unwrap();
+        client.toggle(2).await.unwrap();
+        let status = client.status().await.unwrap();
+        assert_eq!(status.pdm_state.channels[&1].status, ChannelStatus::On);
+        assert_eq!(status.pdm_state.channels[&2].status, ChannelStatus::On);
+        
+        // API errors surface the server's message
+        let error = client.control(1, ChannelAction::SetCurrentLimit(-1.0)).await.unwrap_err();
+        assert!(error.to_string().contains("400"), "{}", error);
+        
+        let report = client.emergency_shutdown("client test").await.unwrap();
+        assert!(report.confirmed_all_off);
+        assert!(client.history(Some(10)).await.unwrap().len() <= 10);
+        assert_eq!(pdm_state.read().await.channels[&1].status, ChannelStatus::Off);
+    }
+    
+    #[tokio::test]
+    async fn test_pdm_client_unwraps_enveloped_responses() {
+        use crate::client::{ChannelAction, PdmClient};
+        
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        config.response_envelope = true;
+        // The client confirms its own shutdowns
+        config.safety.emergency_confirmation.enabled = true;
+        let (app, _, _) = test_app(config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+            axum::serve(listener, service).await.unwrap();
+        });
+        
+        let client = PdmClient::new(&format!("http://{}", addr)).unwrap();
+        client.control(1, ChannelAction::TurnOn).await.unwrap();
+        let status = client.status().await.unwrap();
+        assert_eq!(status.pdm_state.channels[&1].status, ChannelStatus::On);
+        let error = client.control(1, ChannelAction::SetCurrentLimit(-1.0)).await.unwrap_err();
+        assert!(!error.to_string().contains("api_version"), "{}", error);
+        assert!(client.emergency_shutdown("client test").await.unwrap().confirmed_all_off);
+        assert!(client.history(None).await.is_ok());
+    }
+}
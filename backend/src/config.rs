@@ -1,4 +1,4 @@
-/**
+/*!
  * Configuration Management for PDM Backend
  * 
  * This module handles loading and managing configuration settings:
@@ -8,103 +8,2113 @@
  * - Logging configuration
  */
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use tracing::warn;
+
+use crate::models::ChannelFault;
+use crate::signing;
+
+/// Configuration file, relative to the working directory
+pub const CONFIG_FILE: &str = "pdm_config.toml";
+
+/// Replaces credentials in `Config::redacted`
+pub const REDACTED: &str = "[redacted]";
+
+/// Longest PDM temperature read interval (ms), so overtemperature is still caught promptly
+pub const MAX_TEMPERATURE_INTERVAL_MS: u64 = 10_000;
+
+/// Field names (or name suffixes) that hold credentials
+const SECRET_FIELDS: [&str; 5] = ["secret", "token", "password", "passphrase", "api_key"];
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    /// Server configuration
-    pub server_address: String,
-    pub api_version: String,
-    
-    /// Hardware configuration
-    pub hardware: HardwareConfig,
-    
-    /// Safety configuration
-    pub safety: SafetyConfig,
-    
-    /// Logging configuration
-    pub logging: LoggingConfig,
+pub struct Config {
+    /// Server configuration
+    pub server_address: String,
+    pub api_version: String,
+    /// Path prefix all API routes are mounted under
+    #[serde(default = "default_api_base_path")]
+    pub api_base_path: String,
+    /// Wrap responses in a versioned envelope even without an explicit Accept header
+    #[serde(default)]
+    pub response_envelope: bool,
+    /// How long a serialized `GET /status` response is reused (ms, 0 = no caching)
+    #[serde(default = "default_status_cache_ttl_ms")]
+    pub status_cache_ttl_ms: u64,
+    /// Requests handled at once before the rest get 503 (0 = unlimited; streams don't count)
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Refuse to load a config with keys the backend doesn't know, instead of warning
+    #[serde(default)]
+    pub strict_config: bool,
+    /// API endpoints left out of the router
+    #[serde(default)]
+    pub features: FeaturesConfig,
+    /// Decimals readings are rounded to in API output
+    #[serde(default)]
+    pub precision: PrecisionConfig,
+    
+    /// Identity of this PDM unit
+    #[serde(default)]
+    pub unit: UnitConfig,
+    
+    /// Vehicle electrical system
+    #[serde(default)]
+    pub system: SystemConfig,
+    
+    /// Hardware configuration
+    pub hardware: HardwareConfig,
+    
+    /// Safety configuration
+    pub safety: SafetyConfig,
+    
+    /// Logging configuration
+    pub logging: LoggingConfig,
+    
+    /// Multi-PDM aggregator configuration
+    #[serde(default)]
+    pub aggregator: AggregatorConfig,
+    
+    /// Audit log configuration
+    #[serde(default)]
+    pub audit: AuditConfig,
+    
+    /// Lifetime counters kept across restarts
+    #[serde(default)]
+    pub lifetime: LifetimeConfig,
+    
+    /// History buffer configuration
+    #[serde(default)]
+    pub history: HistoryConfig,
+    
+    /// Rotating on-disk telemetry log
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    
+    /// Status LEDs / buzzer on GPIO outputs
+    #[serde(default)]
+    pub indicators: IndicatorConfig,
+    
+    /// Current-limit learning defaults
+    #[serde(default)]
+    pub learn: LearnConfig,
+    
+    /// Battery energy budget
+    #[serde(default)]
+    pub energy: EnergyConfig,
+    
+    /// Battery state-of-charge estimate and SoC load shedding
+    #[serde(default)]
+    pub battery: BatteryConfig,
+    
+    /// Bench-test bypass of channel overcurrent protection
+    #[serde(default)]
+    pub test_mode: TestModeConfig,
+    
+    /// Simulator behaviour
+    #[serde(default)]
+    pub simulation: SimulationConfig,
+    
+    /// Client heartbeat / dead-man's switch
+    #[serde(default)]
+    pub heartbeat: HeartbeatConfig,
+    
+    /// Ignition (key) input
+    #[serde(default)]
+    pub ignition: IgnitionConfig,
+    
+    /// External safety interlock (E-stop / safety loop) input
+    #[serde(default)]
+    pub interlock: InterlockConfig,
+    
+    /// Live state stream (snapshot + delta) settings
+    #[serde(default)]
+    pub stream: StreamConfig,
+    
+    /// State-change webhooks
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    
+    /// CAN telemetry broadcast (DBC-style signal mapping)
+    #[serde(default)]
+    pub can_output: CanOutputConfig,
+    
+    /// Graceful power-off sequencing
+    #[serde(default)]
+    pub sequence: SequenceConfig,
+    
+    /// Hardware validation test vectors
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    
+    /// Groups of channels that always share the same on/off state
+    #[serde(default)]
+    pub mirror_groups: Vec<Vec<u8>>,
+    
+    /// Channels wired in parallel to drive one high-current load
+    #[serde(default)]
+    pub parallel_groups: Vec<ParallelGroupConfig>,
+    
+    /// Time-of-day rules that switch channels (e.g. quiet hours)
+    #[serde(default)]
+    pub schedules: Vec<ScheduleConfig>,
+    
+    /// Areas that can be emergency-shut down on their own
+    #[serde(default)]
+    pub zones: Vec<ZoneConfig>,
+    
+    /// Threshold rules that raise alerts (see `alerts`)
+    #[serde(default)]
+    pub alerts: Vec<AlertRuleConfig>,
+    
+    /// Channel loadout (channels not listed keep their defaults)
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+}
+
+/// Vehicle electrical system settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemConfig {
+    pub ground_polarity: GroundPolarity,
+}
+
+/// Which battery terminal is bonded to the chassis
+///
+/// On a positive-ground vehicle the PDM measures the supply and load
+/// currents with the opposite sign. Readings are converted on ingest, so the
+/// state, the API and every threshold see the supply as a positive voltage
+/// and load current as positive, whichever way the vehicle is wired.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroundPolarity {
+    /// Battery negative on the chassis (modern vehicles)
+    #[default]
+    Negative,
+    /// Battery positive on the chassis (some classic vehicles)
+    Positive,
+}
+
+impl GroundPolarity {
+    /// Convert a voltage or current read from the hardware to the negative-ground convention
+    pub fn normalize(self, raw: f32) -> f32 {
+        match self {
+            GroundPolarity::Negative => raw,
+            GroundPolarity::Positive => -raw,
+        }
+    }
+}
+
+/// Channels paralleled onto one load, reported as a single virtual channel
+///
+/// Members switch together like a mirror group. While all of them are on,
+/// their currents should be about equal; a member that drifts away from
+/// the group mean points at a failing FET or a bad connection and trips
+/// the group with `CurrentImbalance`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelGroupConfig {
+    pub name: String,
+    pub channels: Vec<u8>,
+    /// Largest allowed deviation of a member's current from the group mean,
+    /// as a fraction of the mean (0.25 = 25%)
+    #[serde(default = "default_imbalance_tolerance")]
+    pub imbalance_tolerance: f32,
+    /// Combined current below which imbalance is not checked (A)
+    #[serde(default = "default_imbalance_min_current")]
+    pub min_current: f32,
+}
+
+fn default_imbalance_tolerance() -> f32 {
+    0.25
+}
+
+fn default_imbalance_min_current() -> f32 {
+    2.0
+}
+
+/// Channel loadout entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// Channel number (1..=hardware.channel_count)
+    pub ch: u8,
+    /// Human-readable channel name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Display category for grouping channels in a UI (e.g. "Lighting"); no effect on control
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Current limit (A), defaults to safety.default_channel_current_limit
+    #[serde(default)]
+    pub current_limit: Option<f32>,
+    /// Highest current limit that may be set at runtime (A), e.g. the wiring rating
+    #[serde(default)]
+    pub max_current_limit: Option<f32>,
+    /// false models a fuse: an overcurrent trip must be cleared with replace-fuse
+    #[serde(default)]
+    pub resettable: Option<bool>,
+    /// Critical channels are skipped by a selective reset-all
+    #[serde(default)]
+    pub critical: bool,
+    /// Expected power draw used for power budgeting (W)
+    #[serde(default)]
+    pub power_budget_watts: Option<f32>,
+    /// Overcurrent is ignored for this long after switch-on to ride out inrush (ms)
+    #[serde(default)]
+    pub inrush_ignore_ms: Option<u64>,
+    /// Inductive load: ramp the output down over this long on turn-off instead of hard-switching (ms)
+    #[serde(default)]
+    pub ramp_down_ms: Option<u64>,
+    /// Shortest time the channel stays ON before it may be switched off (ms)
+    #[serde(default)]
+    pub min_on_ms: Option<u64>,
+    /// Shortest time the channel stays OFF before it may be switched on again, e.g. compressor restart protection (ms)
+    #[serde(default)]
+    pub min_off_ms: Option<u64>,
+    /// PWM-capable channels can hold current at the limit instead of tripping
+    #[serde(default)]
+    pub overcurrent_mode: OvercurrentMode,
+    /// Reduce the current limit as the channel heats up
+    #[serde(default)]
+    pub derating: Option<DeratingConfig>,
+    /// Smooth the reported `display_current` with this weight per new reading (0-1); protection uses the raw current
+    #[serde(default)]
+    pub display_smoothing: Option<f32>,
+    /// Current of a healthy load while ON; readings outside it are flagged, not tripped
+    #[serde(default)]
+    pub expected_current: Option<CurrentRange>,
+    /// Least current the load draws while ON (A); less for `open_load_debounce_ms` is an OpenLoad fault
+    #[serde(default)]
+    pub min_current: Option<f32>,
+    /// How the simulated load's current follows the input voltage
+    #[serde(default)]
+    pub load_model: LoadModel,
+    /// Switched on by `POST /api/sequence/power-on-all`
+    #[serde(default)]
+    pub startup: bool,
+    /// Channels that must be powered on before this one
+    #[serde(default)]
+    pub depends_on: Vec<u8>,
+    /// Conditions that must all hold before the API switches this channel on
+    #[serde(default)]
+    pub preconditions: Vec<Precondition>,
+}
+
+/// A condition checked before a channel may be switched on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check")]
+pub enum Precondition {
+    /// The system status is Normal (no warnings, faults or emergency)
+    SystemNormal,
+    /// The input voltage is within `min`-`max` (V)
+    InputVoltage { min: f32, max: f32 },
+    /// The ignition input is on
+    IgnitionOn,
+    /// Another channel is already ON
+    ChannelOn { channel: u8 },
+    /// The PDM temperature is below `max` (°C)
+    TemperatureBelow { max: f32 },
+}
+
+/// What a channel does when its current exceeds the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OvercurrentMode {
+    /// Switch off with an Overcurrent fault
+    #[default]
+    Trip,
+    /// Reduce the PWM duty to hold the current at the limit; a load that
+    /// would need less than the minimum duty (a short) still trips
+    Limit,
+}
+
+/// Electrical behaviour of a simulated load as the supply voltage changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoadModel {
+    /// Draws the same current at any voltage (e.g. a regulated LED driver)
+    #[default]
+    ConstantCurrent,
+    /// Current rises with voltage (heaters, incandescent lamps)
+    ConstantResistance,
+    /// Current falls as voltage rises (switch-mode supplies, motor controllers)
+    ConstantPower,
+}
+
+impl LoadModel {
+    /// Factor applied to the current drawn at `nominal_voltage` when the supply is at `voltage`
+    pub fn current_scale(self, voltage: f32, nominal_voltage: f32) -> f32 {
+        match self {
+            LoadModel::ConstantCurrent => 1.0,
+            LoadModel::ConstantResistance => voltage.max(0.0) / nominal_voltage,
+            // A real supply drops out near zero volts rather than drawing unbounded current
+            LoadModel::ConstantPower => nominal_voltage / voltage.max(nominal_voltage * 0.25),
+        }
+    }
+}
+
+/// Band of current a channel's load normally draws (A)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurrentRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Temperature derating of a channel's current limit
+///
+/// The full limit applies up to `start_temperature`; above it the limit falls
+/// linearly to `min_fraction` of the limit at `safety.max_channel_temperature`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeratingConfig {
+    /// Temperature where derating begins (°C)
+    pub start_temperature: f32,
+    /// Fraction of the limit left at the maximum channel temperature
+    pub min_fraction: f32,
+}
+
+impl Default for DeratingConfig {
+    fn default() -> Self {
+        Self {
+            start_temperature: 50.0,
+            min_fraction: 0.5,
+        }
+    }
+}
+
+fn default_api_base_path() -> String {
+    "/api".to_string()
+}
+
+/// Hardware communication settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareConfig {
+    /// USB/Serial port for PDM communication
+    pub serial_port: Option<String>,
+    pub serial_baud_rate: u32,
+    
+    /// CAN bus settings
+    pub can_interface: Option<String>,
+    pub can_bitrate: u32,
+    
+    /// Update intervals: system status (input voltage, total current) and
+    /// channel readings
+    pub status_update_interval_ms: u64,
+    pub monitoring_interval_ms: u64,
+    /// PDM temperature read interval (ms); temperature changes slowly, so this
+    /// can be far longer than the others. None = with the system status
+    #[serde(default)]
+    pub temperature_interval_ms: Option<u64>,
+    
+    /// Hardware simulation mode (for development)
+    pub simulation_mode: bool,
+    
+    /// Number of output channels on the PDM
+    #[serde(default = "default_channel_count")]
+    pub channel_count: u8,
+    
+    /// What to do when a configured current limit exceeds the hardware rating
+    #[serde(default)]
+    pub rating_check: CheckAction,
+    
+    /// Oldest hardware firmware the backend supports (e.g. "1.2.0")
+    #[serde(default)]
+    pub min_firmware_version: Option<String>,
+    
+    /// What to do when the hardware firmware is older than `min_firmware_version`
+    #[serde(default)]
+    pub firmware_check: CheckAction,
+    
+    /// Slow channel monitoring down while readings are steady
+    #[serde(default)]
+    pub adaptive_rate: AdaptiveRateConfig,
+    
+    /// Wait after opening the link before talking to the board, which may still be booting (ms)
+    #[serde(default = "default_startup_delay_ms")]
+    pub startup_delay_ms: u64,
+    
+    /// Pings sent at startup before giving up and starting degraded
+    #[serde(default = "default_handshake_attempts")]
+    pub handshake_attempts: u32,
+    
+    /// Pause between startup pings (ms)
+    #[serde(default = "default_handshake_retry_ms")]
+    pub handshake_retry_ms: u64,
+    
+    /// Timeout for each attempt at a periodic status read (ms)
+    #[serde(default = "default_read_timeout_ms")]
+    pub read_timeout_ms: u64,
+    
+    /// Further attempts after a status read fails or times out
+    #[serde(default = "default_read_retries")]
+    pub read_retries: u32,
+    
+    /// What a status read that failed every attempt does
+    #[serde(default)]
+    pub read_failure_action: ReadFailureAction,
+    
+    /// What real mode does when neither the serial port nor the CAN interface is present
+    #[serde(default)]
+    pub missing_transport: MissingTransportAction,
+    
+    /// Longest gap between two updates that energy and ON-time accumulate over (ms);
+    /// longer gaps (a stalled loop) count as this long
+    #[serde(default = "default_max_time_step_ms")]
+    pub max_time_step_ms: u64,
+    
+    /// I²C current sensors read for channel voltage/current in real mode
+    #[serde(default)]
+    pub current_sensors: CurrentSensorsConfig,
+}
+
+/// INA219/INA226 current sensors on an I²C bus (DIY builds)
+///
+/// When enabled, real mode takes channel voltages and currents from these
+/// sensors instead of the serial PDM's status frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrentSensorsConfig {
+    pub enabled: bool,
+    /// I²C bus device
+    pub bus: String,
+    pub sensors: Vec<CurrentSensorConfig>,
+}
+
+impl Default for CurrentSensorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bus: "/dev/i2c-1".to_string(),
+            sensors: Vec::new(),
+        }
+    }
+}
+
+/// One current sensor and the channel it measures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurrentSensorConfig {
+    pub channel: u8,
+    /// 7-bit I²C address (0x40-0x4F for both models)
+    pub address: u16,
+    #[serde(default)]
+    pub model: SensorModel,
+    /// Shunt resistance (Ω)
+    #[serde(default = "default_shunt_ohms")]
+    pub shunt_ohms: f32,
+}
+
+fn default_shunt_ohms() -> f32 {
+    0.1
+}
+
+/// Current sensor chip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SensorModel {
+    #[default]
+    Ina219,
+    Ina226,
+}
+
+/// Startup handling of real mode without a usable hardware link
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MissingTransportAction {
+    /// Refuse to start
+    #[default]
+    Refuse,
+    /// Run the simulator instead, reporting the fallback
+    Simulate,
+}
+
+/// Handling of a status read that failed every attempt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadFailureAction {
+    /// Keep the last readings but flag them as stale until a read succeeds
+    #[default]
+    MarkStale,
+    /// Keep the last readings as if nothing happened
+    KeepLast,
+    /// Treat the hardware as lost: the system goes to Fault and the link to disconnected
+    Fault,
+}
+
+fn default_startup_delay_ms() -> u64 {
+    500
+}
+
+fn default_handshake_attempts() -> u32 {
+    5
+}
+
+fn default_handshake_retry_ms() -> u64 {
+    200
+}
+
+fn default_read_timeout_ms() -> u64 {
+    250
+}
+
+fn default_read_retries() -> u32 {
+    2
+}
+
+fn default_max_time_step_ms() -> u64 {
+    5000
+}
+
+/// Adaptive channel monitoring rate
+///
+/// While every reading stays within its deadband of the last reference
+/// sample, the monitoring interval doubles every `settle_samples` samples
+/// from `monitoring_interval_ms` up to `idle_interval_ms`. Any change,
+/// fault or control action drops straight back to the fast rate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveRateConfig {
+    pub enabled: bool,
+    /// Slowest monitoring interval when idle
+    pub idle_interval_ms: u64,
+    /// Stable samples needed before each slowdown step
+    pub settle_samples: u32,
+    /// Channel current change that counts as activity (A)
+    pub current_deadband: f32,
+    /// Input/channel voltage change that counts as activity (V)
+    pub voltage_deadband: f32,
+    /// Channel temperature change that counts as activity (°C)
+    pub temperature_deadband: f32,
+}
+
+impl Default for AdaptiveRateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_interval_ms: 500, // 2Hz
+            settle_samples: 20,
+            current_deadband: 0.1,
+            voltage_deadband: 0.2,
+            temperature_deadband: 1.0,
+        }
+    }
+}
+
+fn default_channel_count() -> u8 {
+    8
+}
+
+/// Identifies this PDM in fleet views, events and logs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitConfig {
+    /// Unique unit id (defaults to the hostname)
+    pub id: Option<String>,
+    /// Display name (defaults to the id)
+    pub name: Option<String>,
+}
+
+impl UnitConfig {
+    /// Fill in the defaults
+    pub fn resolve(&self) -> UnitIdentity {
+        let id = self.id.clone().unwrap_or_else(hostname);
+        UnitIdentity {
+            name: self.name.clone().unwrap_or_else(|| id.clone()),
+            id,
+        }
+    }
+}
+
+/// Resolved unit identity reported in responses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnitIdentity {
+    pub id: String,
+    pub name: String,
+}
+
+/// Name of the host the backend runs on
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "pdm".to_string())
+}
+
+fn default_status_cache_ttl_ms() -> u64 {
+    50
+}
+
+fn default_max_concurrent_requests() -> usize {
+    32
+}
+
+/// Decimal places of readings in serialized output (7 or more = unrounded)
+///
+/// Values are kept at full precision internally.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrecisionConfig {
+    /// Decimals of voltages (V)
+    pub voltage: u8,
+    /// Decimals of currents (A)
+    pub current: u8,
+    /// Decimals of temperatures (°C)
+    pub temperature: u8,
+    /// Decimals of power (W)
+    pub power: u8,
+}
+
+impl Default for PrecisionConfig {
+    fn default() -> Self {
+        Self {
+            voltage: 2,
+            current: 2,
+            temperature: 1,
+            power: 1,
+        }
+    }
+}
+
+/// Which groups of API endpoints the router serves
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeaturesConfig {
+    /// Features whose endpoints are not served
+    pub disabled: Vec<ApiFeature>,
+    /// Features off by default (`ApiFeature::OFF_BY_DEFAULT`) that are served
+    pub opt_in: Vec<ApiFeature>,
+    /// How requests to a disabled endpoint are answered
+    pub disabled_response: DisabledResponse,
+}
+
+impl FeaturesConfig {
+    pub fn is_enabled(&self, feature: ApiFeature) -> bool {
+        if self.disabled.contains(&feature) {
+            return false;
+        }
+        !ApiFeature::OFF_BY_DEFAULT.contains(&feature) || self.opt_in.contains(&feature)
+    }
+    
+    /// Enabled features, in `ApiFeature::ALL` order
+    pub fn enabled(&self) -> Vec<ApiFeature> {
+        ApiFeature::ALL.into_iter().filter(|&feature| self.is_enabled(feature)).collect()
+    }
+    
+    /// Features not served, whether listed in `disabled` or off by default
+    pub fn disabled_features(&self) -> Vec<ApiFeature> {
+        ApiFeature::ALL.into_iter().filter(|&feature| !self.is_enabled(feature)).collect()
+    }
+}
+
+/// A group of related API endpoints that can be switched off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiFeature {
+    /// `/health`
+    Health,
+    /// `/status`, `/status/stream`
+    Status,
+    /// `/config`
+    Config,
+    /// `/config/reload`
+    ConfigReload,
+    /// `/config/full`
+    ConfigExport,
+    /// `/setup/status`
+    Setup,
+    /// `/channel/:id/control`, `/channel/:id/toggle`
+    ChannelControl,
+    /// `/channel/:id/clear-fault`, `/channel/:id/replace-fuse`
+    FaultClearing,
+    /// `/channel/:id/transitions`
+    Transitions,
+    /// `/channel/:id/learn/*`
+    Learning,
+    /// `/channel/:id/override`
+    Override,
+    /// `/emergency-shutdown`, `/emergency-shutdown/zone/:name`
+    EmergencyShutdown,
+    /// `/reset-all`
+    ResetAll,
+    /// `/sequence/*`
+    Sequencing,
+    /// `/master/*`
+    MasterSwitch,
+    /// `/heartbeat`
+    Heartbeat,
+    /// `/fleet/status`
+    Fleet,
+    /// `/audit`
+    Audit,
+    /// `/hardware/commission`
+    Commissioning,
+    /// `/validate`
+    Validation,
+    /// `/sim/*`
+    Simulation,
+    /// `/history`
+    History,
+    /// `/telemetry/files`
+    Telemetry,
+    /// `/events`, `/events/stream`
+    Events,
+    /// `/logs/stream`
+    Logs,
+    /// `/diagnostics/*`
+    Diagnostics,
+    /// `/energy/reset`
+    Energy,
+    /// `/channel/:id/test-mode`
+    TestMode,
+    /// `/profile/export`, `/profile/import`
+    Profile,
+    /// `/alerts`
+    Alerts,
+    /// `/stats/lifetime`
+    Lifetime,
+}
+
+impl ApiFeature {
+    /// Features that expose more than a deployed unit should by default,
+    /// served only when listed in `features.opt_in`
+    pub const OFF_BY_DEFAULT: [ApiFeature; 2] = [ApiFeature::ConfigExport, ApiFeature::Logs];
+    
+    pub const ALL: [ApiFeature; 31] = [
+        ApiFeature::Health,
+        ApiFeature::Status,
+        ApiFeature::Config,
+        ApiFeature::ConfigReload,
+        ApiFeature::ConfigExport,
+        ApiFeature::Setup,
+        ApiFeature::ChannelControl,
+        ApiFeature::FaultClearing,
+        ApiFeature::Transitions,
+        ApiFeature::Learning,
+        ApiFeature::Override,
+        ApiFeature::EmergencyShutdown,
+        ApiFeature::ResetAll,
+        ApiFeature::Sequencing,
+        ApiFeature::MasterSwitch,
+        ApiFeature::Heartbeat,
+        ApiFeature::Fleet,
+        ApiFeature::Audit,
+        ApiFeature::Commissioning,
+        ApiFeature::Validation,
+        ApiFeature::Simulation,
+        ApiFeature::History,
+        ApiFeature::Telemetry,
+        ApiFeature::Events,
+        ApiFeature::Logs,
+        ApiFeature::Diagnostics,
+        ApiFeature::Energy,
+        ApiFeature::TestMode,
+        ApiFeature::Profile,
+        ApiFeature::Alerts,
+        ApiFeature::Lifetime,
+    ];
+}
+
+/// Response for an endpoint whose feature is disabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisabledResponse {
+    /// Behave as if the route didn't exist
+    #[default]
+    NotFound,
+    /// 403 naming the disabled feature
+    Forbidden,
+}
+
+/// Response to a failed startup check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckAction {
+    /// Log a warning and keep running
+    #[default]
+    Warn,
+    /// Refuse to start
+    Refuse,
+}
+
+/// Safety limits and thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyConfig {
+    /// Maximum input voltage before fault (V)
+    pub max_input_voltage: f32,
+    /// Minimum input voltage before fault (V)
+    pub min_input_voltage: f32,
+    
+    /// Maximum total current before emergency shutdown (A)
+    pub max_total_current: f32,
+    
+    /// Maximum PDM temperature before fault (°C)
+    pub max_temperature: f32,
+    /// Maximum per-channel (FET) temperature before the channel trips (°C)
+    #[serde(default = "default_max_channel_temperature")]
+    pub max_channel_temperature: f32,
+    /// Reverse (negative) channel current that trips the channel (A)
+    #[serde(default = "default_max_reverse_current")]
+    pub max_reverse_current: f32,
+    /// How long a channel stays below its `min_current` before OpenLoad trips it (ms)
+    #[serde(default = "default_open_load_debounce_ms")]
+    pub open_load_debounce_ms: u64,
+    
+    /// Default current limit per channel (A)
+    pub default_channel_current_limit: f32,
+    
+    /// Fraction of a channel's current limit that raises a soft warning (0.0-1.0)
+    #[serde(default = "default_current_warning_fraction")]
+    pub current_warning_fraction: f32,
+    
+    /// Peak-to-peak input voltage ripple that raises a warning (V)
+    #[serde(default = "default_max_input_ripple")]
+    pub max_input_ripple: f32,
+    /// Number of input voltage samples in the ripple window
+    #[serde(default = "default_ripple_window_samples")]
+    pub ripple_window_samples: usize,
+    
+    /// Consecutive status cycles a lower system status must hold before it is
+    /// reported (escalations apply at once; 0 or 1 = no debounce)
+    #[serde(default = "default_status_downgrade_cycles")]
+    pub status_downgrade_cycles: u32,
+    
+    /// Projected total power above which switching a channel on is refused or warned (W)
+    #[serde(default)]
+    pub max_total_power: Option<f32>,
+    /// Most channels the supply can feed at once, regardless of current
+    #[serde(default)]
+    pub max_active_channels: Option<usize>,
+    /// What to do when switching a channel on would exceed `max_total_power`
+    #[serde(default = "default_power_budget_check")]
+    pub power_budget_check: CheckAction,
+    
+    /// Current anomaly detection (early-warning diagnostics)
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
+    
+    /// Alerting on channels switched on and off too often
+    #[serde(default)]
+    pub switch_rate: SwitchRateConfig,
+    
+    /// Switching low-priority channels off while the PDM runs hot
+    #[serde(default)]
+    pub thermal_shedding: ThermalSheddingConfig,
+    
+    /// Emergency shutdown timeout (seconds)
+    pub emergency_shutdown_timeout: u64,
+    
+    /// How each channel fault type is handled
+    #[serde(default)]
+    pub fault_handling: FaultHandlingConfig,
+    
+    /// Ramp inductive channels down during an emergency shutdown too (delays it by the longest ramp)
+    #[serde(default)]
+    pub ramp_down_on_emergency: bool,
+    
+    /// Two-step emergency shutdown through the API
+    #[serde(default)]
+    pub emergency_confirmation: EmergencyConfirmationConfig,
+}
+
+fn default_max_channel_temperature() -> f32 {
+    125.0
+}
+
+fn default_current_warning_fraction() -> f32 {
+    0.8
+}
+
+fn default_max_reverse_current() -> f32 {
+    0.5
+}
+
+fn default_open_load_debounce_ms() -> u64 {
+    1000
+}
+
+fn default_max_input_ripple() -> f32 {
+    1.0
+}
+
+fn default_power_budget_check() -> CheckAction {
+    CheckAction::Refuse
+}
+
+fn default_ripple_window_samples() -> usize {
+    20 // 2s at the default 10Hz status rate
+}
+
+fn default_status_downgrade_cycles() -> u32 {
+    5 // 0.5s at the default 10Hz status rate
+}
+
+/// Channel current anomaly detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    /// Flag channels whose current deviates from their moving average
+    pub enabled: bool,
+    /// Deviation from the average that counts as an anomaly (0.5 = 50%)
+    pub deviation_fraction: f32,
+    /// Number of monitoring samples the moving average spans
+    pub window_samples: u32,
+    /// Ignore channels whose average current is below this (A)
+    pub min_current: f32,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            deviation_fraction: 0.5,
+            window_samples: 40, // ~2s at the default 20Hz monitoring rate
+            min_current: 0.2,
+        }
+    }
+}
+
+/// Channel switching rate alerting
+///
+/// A channel switched more than `max_switches` times within `window_ms`
+/// points at a control bug or flapping automation wearing out the output.
+/// It is flagged and reported, never switched off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwitchRateConfig {
+    pub enabled: bool,
+    /// ON/OFF changes allowed within the window
+    pub max_switches: usize,
+    /// Sliding window the switches are counted over (ms)
+    pub window_ms: u64,
+}
+
+impl Default for SwitchRateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_switches: 10,
+            window_ms: 60_000,
+        }
+    }
+}
+
+/// Thermal load shedding settings
+///
+/// Above `shed_temperature` the listed channels are switched off one per
+/// `step_ms`, lowest priority first. They come back in reverse order, again
+/// one per step, only once the temperature is down to `recovery_temperature`
+/// and each has been off for `min_shed_ms`. The gap between the two
+/// temperatures and the minimum off time keep a restored load from heating
+/// the PDM straight back over the threshold and shedding again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThermalSheddingConfig {
+    pub enabled: bool,
+    /// Channels that may be shed, lowest priority first (critical channels are never shed)
+    pub channels: Vec<u8>,
+    /// PDM temperature above which channels are shed (°C)
+    pub shed_temperature: f32,
+    /// PDM temperature at or below which shed channels are restored (°C)
+    pub recovery_temperature: f32,
+    /// Shortest time a shed channel stays off (ms)
+    pub min_shed_ms: u64,
+    /// Time between shedding or restoring successive channels (ms)
+    pub step_ms: u64,
+}
+
+impl Default for ThermalSheddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channels: Vec::new(),
+            shed_temperature: 68.0,     // The Warning level at the default 85°C max_temperature
+            recovery_temperature: 58.0,
+            min_shed_ms: 120_000,
+            step_ms: 10_000,
+        }
+    }
+}
+
+/// Confirmation tokens for the emergency shutdown endpoints
+///
+/// When enabled, a shutdown request must carry a token fetched from
+/// `GET /emergency-shutdown/token` shortly before, so a UI needs a deliberate
+/// second step. Off by default so scripts keep working unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmergencyConfirmationConfig {
+    pub enabled: bool,
+    /// How long a token stays valid after it is issued (ms)
+    pub token_ttl_ms: u64,
+}
+
+impl Default for EmergencyConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            token_ttl_ms: 10_000,
+        }
+    }
+}
+
+/// Action taken when a channel fault is detected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action")]
+pub enum FaultAction {
+    /// Turn the channel off and keep it faulted until an operator clears it
+    Latch,
+    /// Turn the channel off, then re-enable it after `delay_ms`, up to `attempts` times
+    AutoRetry { attempts: u32, delay_ms: u64 },
+    /// Turn the channel off and restore its prior state if the supply is back in
+    /// range within `window_ms`; a fault still present after the window latches.
+    /// Only supply faults (under/overvoltage) can clear while the channel is off,
+    /// so other fault types simply latch
+    RecoverIfCleared { window_ms: u64 },
+    /// Log the fault but leave the channel running
+    Ignore,
+}
+
+/// Per-fault-type handling table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultHandlingConfig {
+    pub overcurrent: FaultAction,
+    pub overvoltage: FaultAction,
+    pub undervoltage: FaultAction,
+    pub short_circuit: FaultAction,
+    pub open_load: FaultAction,
+    pub overtemperature: FaultAction,
+    pub reverse_current: FaultAction,
+    pub current_imbalance: FaultAction,
+    /// Restore channels faulted by an input (supply) under/overvoltage to their
+    /// pre-fault state once the input is back in range, whatever their action;
+    /// faults of the channel's own making stay as configured
+    pub recover_system_faults: bool,
+}
+
+impl FaultHandlingConfig {
+    /// Look up the configured action for a fault type
+    pub fn action_for(&self, fault: ChannelFault) -> &FaultAction {
+        match fault {
+            ChannelFault::Overcurrent => &self.overcurrent,
+            ChannelFault::Overvoltage => &self.overvoltage,
+            ChannelFault::Undervoltage => &self.undervoltage,
+            ChannelFault::ShortCircuit => &self.short_circuit,
+            ChannelFault::OpenLoad => &self.open_load,
+            ChannelFault::Overtemperature => &self.overtemperature,
+            ChannelFault::ReverseCurrent => &self.reverse_current,
+            ChannelFault::CurrentImbalance => &self.current_imbalance,
+            // Raised by the shutdown path, never by detection; always needs an operator
+            ChannelFault::ShutdownUnconfirmed => &FaultAction::Latch,
+        }
+    }
+}
+
+impl Default for FaultHandlingConfig {
+    fn default() -> Self {
+        Self {
+            overcurrent: FaultAction::Latch,
+            overvoltage: FaultAction::Latch,
+            undervoltage: FaultAction::Latch,
+            short_circuit: FaultAction::Latch,
+            // Open load is usually a blown bulb, so retry a few times before latching
+            open_load: FaultAction::AutoRetry { attempts: 3, delay_ms: 5000 },
+            overtemperature: FaultAction::Latch,
+            reverse_current: FaultAction::Latch,
+            current_imbalance: FaultAction::Latch,
+            recover_system_faults: false,
+        }
+    }
+}
+
+/// Logging configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Log level (trace, debug, info, warn, error)
+    pub level: String,
+    /// Log to file
+    pub log_to_file: bool,
+    /// Log file path
+    pub log_file_path: Option<String>,
+}
+
+/// Aggregator mode settings (combined view of several PDM units)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatorConfig {
+    /// Poll downstream PDM backends and serve a combined fleet view
+    pub enabled: bool,
+    /// Base URLs of downstream PDM backends (e.g. "http://10.0.0.21:3030")
+    pub units: Vec<String>,
+    /// Route prefix the downstream units serve their API under (their `api_base_path`)
+    #[serde(default = "default_api_base_path")]
+    pub api_base_path: String,
+    /// How often each unit's status is polled
+    pub poll_interval_ms: u64,
+    /// Timeout for a single status request (ms)
+    pub request_timeout_ms: u64,
+}
+
+impl Default for AggregatorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            units: Vec::new(),
+            api_base_path: default_api_base_path(),
+            poll_interval_ms: 1000,
+            request_timeout_ms: 500,
+        }
+    }
+}
+
+/// Audit log of state-changing API calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Record control actions to the audit log
+    pub enabled: bool,
+    /// Audit log file path (newline-delimited JSON)
+    pub log_path: String,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            log_path: "pdm_audit.log".to_string(),
+        }
+    }
+}
+
+/// Lifetime counters (runtime, starts, energy, channel ON-time)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeConfig {
+    /// Persist the counters; when false they only cover the current run
+    pub enabled: bool,
+    /// Counter file (JSON)
+    pub path: String,
+    /// How often the counters are written (ms); at most this much is lost in a crash
+    pub flush_interval_ms: u64,
+}
+
+impl Default for LifetimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: "pdm_lifetime.json".to_string(),
+            flush_interval_ms: 60_000,
+        }
+    }
+}
+
+/// Simulation mode parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    /// Simulated input voltage before noise and ripple, at which the load currents are defined (V)
+    pub nominal_voltage: f32,
+    /// Amplitude of the sinusoidal ripple added to the input voltage (V)
+    pub ripple_amplitude: f32,
+    /// Frequency of the simulated ripple (Hz)
+    pub ripple_frequency_hz: f32,
+    /// Delay before a simulated channel command completes (ms)
+    pub control_latency_ms: u64,
+    /// Probability (0.0-1.0) that a simulated channel command fails
+    pub control_failure_probability: f32,
+    /// On-resistance of the simulated output FETs (ohm)
+    pub fet_resistance_ohms: f32,
+    /// Temperature rise per watt dissipated in a FET (°C/W)
+    pub thermal_resistance_c_per_w: f32,
+    /// Fraction of the gap to the steady-state temperature closed per monitoring tick
+    pub thermal_smoothing: f32,
+    /// Supply current the PDM draws for itself (A)
+    pub quiescent_current: f32,
+    /// Fraction of the switched current that reaches the outputs (0.0-1.0)
+    pub efficiency: f32,
+    /// Channels failing on their own over time
+    pub failures: SimFailureConfig,
+    /// Cycle channels on their own from startup, for unattended displays (see `demo`)
+    pub demo_mode: bool,
+    /// Pattern run by the demo unless `POST /api/sim/demo/start` names another
+    pub demo_pattern: DemoPattern,
+    /// Time between demo steps (ms)
+    pub demo_step_ms: u64,
+}
+
+/// How the simulator demo cycles the channels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DemoPattern {
+    /// One channel on at a time, moving along the channels
+    #[default]
+    Chase,
+    /// A random set of channels each step
+    Random,
+    /// Channels added one per step until all are on, then removed again
+    LoadTest,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            nominal_voltage: 13.8,
+            ripple_amplitude: 0.0,
+            ripple_frequency_hz: 1.5,
+            control_latency_ms: 0,
+            control_failure_probability: 0.0,
+            fet_resistance_ohms: 0.01,
+            thermal_resistance_c_per_w: 20.0,
+            thermal_smoothing: 0.05,
+            quiescent_current: 0.15,
+            efficiency: 0.97,
+            failures: SimFailureConfig::default(),
+            demo_mode: false,
+            demo_pattern: DemoPattern::default(),
+            demo_step_ms: 1000,
+        }
+    }
+}
+
+/// Simulated channel failures over time (see `sim_failures`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimFailureConfig {
+    pub enabled: bool,
+    /// Mean ON-time before a channel fails (s)
+    pub mtbf_secs: f64,
+    /// Seed for reproducible runs (random when omitted)
+    pub seed: Option<u64>,
+    /// Channels that can fail (empty = all)
+    pub channels: Vec<u8>,
+    /// Faults a failure develops, picked by weight
+    pub modes: Vec<FailureModeConfig>,
+}
+
+impl Default for SimFailureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mtbf_secs: 3600.0,
+            seed: None,
+            channels: Vec::new(),
+            modes: vec![FailureModeConfig { fault: ChannelFault::Overcurrent, weight: 1.0 }],
+        }
+    }
+}
+
+/// One simulated failure mode and its relative likelihood
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureModeConfig {
+    pub fault: ChannelFault,
+    pub weight: f32,
+}
+
+/// Dead-man's switch for remotely controlled setups
+///
+/// Once a client has sent its first heartbeat, missing the timeout triggers
+/// an emergency shutdown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeartbeatConfig {
+    pub enabled: bool,
+    /// Maximum time between heartbeats (ms)
+    pub timeout_ms: u64,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_ms: 5000,
+        }
+    }
+}
+
+/// Ignition/key input
+///
+/// Read from a GPIO in real mode and settable through the simulator API in
+/// simulation mode. When the ignition goes off, non-critical channels can be
+/// switched off one after another once a delay has passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IgnitionConfig {
+    pub enabled: bool,
+    /// Sysfs GPIO number of the ignition input (real mode)
+    pub gpio_pin: Option<u32>,
+    /// Whether the GPIO reads low while the ignition is on
+    pub active_low: bool,
+    /// Switch non-critical channels off when the ignition goes off
+    pub sequence_off: bool,
+    /// Time the ignition must stay off before channels are switched off (ms)
+    pub off_delay_ms: u64,
 }
 
-/// Hardware communication settings
+impl Default for IgnitionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gpio_pin: None,
+            active_low: false,
+            sequence_off: true,
+            off_delay_ms: 5000,
+        }
+    }
+}
+
+/// External safety interlock input
+///
+/// A hardware E-stop or safety loop that must be closed for any output to be
+/// on. Read from a GPIO in real mode and settable through the simulator API
+/// in simulation mode. While it is open every channel is held off and channel
+/// control is refused; an input that can't be read counts as open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterlockConfig {
+    pub enabled: bool,
+    /// Sysfs GPIO number of the interlock input (real mode)
+    pub gpio_pin: Option<u32>,
+    /// Whether the GPIO reads low while the interlock is closed
+    pub active_low: bool,
+}
+
+/// Order used by the graceful power-off sequence
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HardwareConfig {
-    /// USB/Serial port for PDM communication
-    pub serial_port: Option<String>,
-    pub serial_baud_rate: u32,
-    
-    /// CAN bus settings
-    pub can_interface: Option<String>,
-    pub can_bitrate: u32,
-    
-    /// Update intervals
-    pub status_update_interval_ms: u64,
-    pub monitoring_interval_ms: u64,
-    
-    /// Hardware simulation mode (for development)
-    pub simulation_mode: bool,
+#[serde(default)]
+pub struct SequenceConfig {
+    /// Channels switched off first, in this order; the rest follow from the
+    /// highest channel number down
+    pub power_off_order: Vec<u8>,
+    /// Pause between steps (ms)
+    pub step_delay_ms: u64,
 }
 
-/// Safety limits and thresholds
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            power_off_order: Vec::new(),
+            step_delay_ms: 200,
+        }
+    }
+}
+
+/// The channel loadout on its own, for sharing and switching between vehicles
+///
+/// Covers what a vehicle's wiring decides (channel names, limits, startup
+/// states, dependencies, groups and power-off priority) and nothing about
+/// the unit itself, so a profile can be moved between units.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SafetyConfig {
-    /// Maximum input voltage before fault (V)
-    pub max_input_voltage: f32,
-    /// Minimum input voltage before fault (V)
-    pub min_input_voltage: f32,
-    
-    /// Maximum total current before emergency shutdown (A)
-    pub max_total_current: f32,
-    
-    /// Maximum PDM temperature before fault (°C)
-    pub max_temperature: f32,
-    
-    /// Default current limit per channel (A)
-    pub default_channel_current_limit: f32,
-    
-    /// Emergency shutdown timeout (seconds)
-    pub emergency_shutdown_timeout: u64,
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Label for the loadout (e.g. the vehicle)
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+    #[serde(default)]
+    pub mirror_groups: Vec<Vec<u8>>,
+    #[serde(default)]
+    pub parallel_groups: Vec<ParallelGroupConfig>,
+    #[serde(default)]
+    pub zones: Vec<ZoneConfig>,
+    #[serde(default)]
+    pub sequence: SequenceConfig,
 }
 
-/// Logging configuration
+/// Channels de-energized together by a zone emergency shutdown
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LoggingConfig {
-    /// Log level (trace, debug, info, warn, error)
-    pub level: String,
-    /// Log to file
-    pub log_to_file: bool,
-    /// Log file path
-    pub log_file_path: Option<String>,
+pub struct ZoneConfig {
+    /// Used in `POST /api/emergency-shutdown/zone/:name`
+    pub name: String,
+    pub channels: Vec<u8>,
+}
+
+/// A daily time window during which channels are switched
+///
+/// Times are local "HH:MM"; a window whose end is before its start runs
+/// over midnight. When the window closes, channels the schedule switched
+/// are put back unless they were changed in the meantime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    pub name: String,
+    pub channels: Vec<u8>,
+    pub start: String,
+    pub end: String,
+    /// State the channels are switched to inside the window
+    #[serde(default)]
+    pub action: ScheduleAction,
+}
+
+/// What a schedule does to its channels inside the window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScheduleAction {
+    #[default]
+    Off,
+    On,
+}
+
+/// A threshold rule, e.g. input voltage below 12V for 5s raises a Warning
+///
+/// Alerts only notify; the safety limits still do the protecting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRuleConfig {
+    /// Identifies the alert in `/api/alerts` and events
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Channel read by the `ChannelCurrent` and `ChannelTemperature` metrics
+    #[serde(default)]
+    pub channel: Option<u8>,
+    pub condition: AlertCondition,
+    pub threshold: f32,
+    /// How long the condition must hold before the alert is raised (ms)
+    #[serde(default)]
+    pub for_ms: u64,
+    /// How far the value must come back past the threshold to clear the alert
+    #[serde(default)]
+    pub hysteresis: f32,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    /// Text of the alert; `{name}`, `{value}`, `{threshold}` and `{channel}` are filled in
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Reading an alert rule watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    InputVoltage,
+    InputCurrent,
+    TotalCurrent,
+    /// PDM internal temperature
+    Temperature,
+    /// Peak-to-peak input voltage ripple
+    InputRipple,
+    ChannelCurrent,
+    ChannelTemperature,
+}
+
+impl AlertMetric {
+    /// Whether the metric is read from the rule's channel
+    pub fn per_channel(self) -> bool {
+        matches!(self, AlertMetric::ChannelCurrent | AlertMetric::ChannelTemperature)
+    }
+}
+
+/// Side of the threshold that raises the alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertCondition {
+    Above,
+    Below,
+}
+
+/// How urgent an alert is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+/// Hardware validation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Directory holding stored test vectors (`<name>.json`)
+    pub vector_dir: String,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            vector_dir: "test_vectors".to_string(),
+        }
+    }
+}
+
+/// Live state stream settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamConfig {
+    /// How often the state is checked for changes (ms)
+    pub interval_ms: u64,
+    /// How often a full snapshot is re-sent (ms)
+    pub keyframe_interval_ms: u64,
+    /// Voltage change that counts as a change (V)
+    pub voltage_deadband: f32,
+    /// Current change that counts as a change (A)
+    pub current_deadband: f32,
+    /// Temperature change that counts as a change (°C)
+    pub temperature_deadband: f32,
+    /// Longest history replay a client may request on connect (s)
+    pub max_replay_secs: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 100,
+            keyframe_interval_ms: 10_000,
+            voltage_deadband: 0.05,
+            current_deadband: 0.05,
+            temperature_deadband: 0.5,
+            max_replay_secs: 300,
+        }
+    }
+}
+
+/// Outgoing webhook for alerting integrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    /// Endpoint that receives the JSON event payloads
+    pub url: Option<String>,
+    /// Which events are sent
+    pub events: Vec<WebhookEvent>,
+    /// Shared secret for the HMAC-SHA256 `X-PDM-Signature` header
+    pub secret: Option<String>,
+    /// Retries after the first failed delivery
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry (ms)
+    pub initial_backoff_ms: u64,
+    /// Timeout for each delivery attempt (ms)
+    pub request_timeout_ms: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            events: vec![WebhookEvent::Fault, WebhookEvent::EmergencyShutdown, WebhookEvent::StatusChange, WebhookEvent::Alert],
+            secret: None,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            request_timeout_ms: 2000,
+        }
+    }
+}
+
+/// Event categories a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A channel fault trip
+    Fault,
+    /// Any emergency shutdown (including a lost heartbeat)
+    EmergencyShutdown,
+    /// System status severity change
+    StatusChange,
+    /// A configured alert raised or cleared
+    Alert,
+}
+
+/// Periodic CAN broadcast of telemetry for existing vehicle dashboards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanOutputConfig {
+    /// Broadcast telemetry on `hardware.can_interface`
+    pub enabled: bool,
+    /// Transmit period for all messages (ms)
+    pub interval_ms: u64,
+    /// Frames to transmit
+    pub messages: Vec<CanMessageConfig>,
+}
+
+impl Default for CanOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 100, // 10Hz
+            messages: Vec::new(),
+        }
+    }
+}
+
+/// One CAN frame, equivalent to a DBC `BO_` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanMessageConfig {
+    /// CAN identifier
+    pub id: u32,
+    /// Use a 29-bit extended identifier
+    #[serde(default)]
+    pub extended: bool,
+    /// Data length in bytes (0-8)
+    #[serde(default = "default_can_dlc")]
+    pub dlc: u8,
+    /// Signals packed into the frame
+    #[serde(default)]
+    pub signals: Vec<CanSignalConfig>,
+}
+
+fn default_can_dlc() -> u8 {
+    8
+}
+
+/// One signal within a frame, equivalent to a DBC `SG_` entry
+///
+/// The raw value is `(physical - offset) / scale`, as in DBC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanSignalConfig {
+    /// Telemetry value carried by the signal
+    pub metric: CanMetric,
+    /// Channel for per-channel metrics
+    #[serde(default)]
+    pub channel: Option<u8>,
+    /// Start bit using DBC numbering (LSB for Intel, MSB for Motorola)
+    pub start_bit: u16,
+    /// Signal length in bits (1-64)
+    pub length: u8,
+    #[serde(default)]
+    pub byte_order: CanByteOrder,
+    #[serde(default)]
+    pub signed: bool,
+    #[serde(default = "default_can_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub offset: f32,
+}
+
+fn default_can_scale() -> f32 {
+    1.0
+}
+
+/// Telemetry values that can be mapped to CAN signals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanMetric {
+    InputVoltage,
+    TotalCurrent,
+    TotalPower,
+    Temperature,
+    /// 0 = Normal, 1 = Warning, 2 = Fault, 3 = Emergency
+    SystemStatus,
+    ChannelCurrent,
+    ChannelVoltage,
+    /// 0 = OFF, 1 = ON, 2 = FAULT
+    ChannelStatus,
+}
+
+impl CanMetric {
+    /// Whether the metric needs a `channel`
+    pub fn is_per_channel(self) -> bool {
+        matches!(self, Self::ChannelCurrent | Self::ChannelVoltage | Self::ChannelStatus)
+    }
+}
+
+/// DBC signal byte order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CanByteOrder {
+    /// Intel, DBC `@1`
+    #[default]
+    LittleEndian,
+    /// Motorola, DBC `@0`
+    BigEndian,
+}
+
+/// In-memory telemetry history settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Maximum number of stored points (0 disables history)
+    pub capacity: usize,
+    /// Monitoring samples within this interval are aggregated into one point (ms)
+    pub sample_interval_ms: u64,
+    /// History fitted to predict when a rising current reaches its limit (ms)
+    pub trend_window_ms: u64,
+    /// Points needed in the window before a prediction is made
+    pub trend_min_points: usize,
+    /// Predictions further out than this are reported as no trend (ms)
+    pub trend_horizon_ms: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 3600,           // One hour...
+            sample_interval_ms: 1000, // ...at one point per second
+            trend_window_ms: 60_000,
+            trend_min_points: 10,
+            trend_horizon_ms: 600_000,
+        }
+    }
+}
+
+/// On-disk telemetry log settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Record telemetry to disk
+    pub enabled: bool,
+    /// Directory holding the log files
+    pub directory: String,
+    /// Sample period (ms)
+    pub interval_ms: u64,
+    /// Start a new file every this many seconds
+    pub rotation_secs: u64,
+    /// Number of files kept; the oldest are deleted
+    pub retain_files: usize,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "telemetry".to_string(),
+            interval_ms: 1000,
+            rotation_secs: 3600, // One file per hour...
+            retain_files: 48,    // ...for two days
+        }
+    }
+}
+
+/// Total energy budget for battery/off-grid setups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfig {
+    /// Energy that may be drawn before non-critical channels are switched off (Wh, None = no budget)
+    pub max_total_energy_wh: Option<f32>,
+    /// Fraction of the budget at which a low-energy warning is raised
+    pub warn_fraction: f32,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            max_total_energy_wh: None,
+            warn_fraction: 0.9,
+        }
+    }
+}
+
+/// Battery state-of-charge estimation for battery-powered installations
+///
+/// The defaults describe a 100Ah 12V flooded lead-acid battery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    pub enabled: bool,
+    /// Usable capacity (Ah)
+    pub capacity_ah: f32,
+    /// Resting voltage to state of charge, in ascending voltage
+    pub soc_curve: Vec<SocPoint>,
+    /// Input current below which the battery counts as resting, so its
+    /// voltage is trusted to correct the estimate (A)
+    pub rest_current: f32,
+    /// Share of the gap to the voltage estimate closed per status cycle while resting (0-1)
+    pub voltage_correction: f32,
+    /// Channels switched off as the charge falls
+    pub shedding: Vec<SocShedStage>,
+    /// Charge above a stage's threshold before its channels are restored (%)
+    pub recovery_margin: f32,
+}
+
+/// A point on the voltage-to-SoC curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SocPoint {
+    pub voltage: f32,
+    pub soc: f32,
+}
+
+/// Channels shed once the state of charge drops below `below_soc`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SocShedStage {
+    /// State of charge the stage starts at (%)
+    pub below_soc: f32,
+    /// Channels switched off, in order (critical channels are never shed)
+    pub channels: Vec<u8>,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        let point = |voltage, soc| SocPoint { voltage, soc };
+        Self {
+            enabled: false,
+            capacity_ah: 100.0,
+            soc_curve: vec![
+                point(11.6, 0.0),
+                point(11.9, 20.0),
+                point(12.1, 40.0),
+                point(12.3, 60.0),
+                point(12.5, 80.0),
+                point(12.7, 100.0),
+            ],
+            rest_current: 1.0,
+            voltage_correction: 0.01,
+            shedding: Vec::new(),
+            recovery_margin: 5.0,
+        }
+    }
+}
+
+/// Defaults for learning a channel's current limit from its running peak
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LearnConfig {
+    /// Suggested limit = observed peak × margin
+    pub margin: f32,
+    /// Observation window; sampling stops once it has elapsed (ms)
+    pub window_ms: u64,
+}
+
+impl Default for LearnConfig {
+    fn default() -> Self {
+        Self {
+            margin: 1.25,
+            window_ms: 300_000, // Five minutes of normal running
+        }
+    }
+}
+
+/// Channel test mode: overcurrent tripping suspended for bench testing
+///
+/// Off unless explicitly enabled; every use goes through the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TestModeConfig {
+    /// Allow `POST /api/channel/:id/test-mode`
+    pub enabled: bool,
+    /// Longest test mode that may be requested; it always expires (ms)
+    pub max_duration_ms: u64,
+}
+
+impl Default for TestModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_duration_ms: 60_000,
+        }
+    }
+}
+
+/// Physical fault indicators driven from GPIO outputs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndicatorConfig {
+    /// Drive the outputs (simulated when `hardware.simulation_mode` is set)
+    pub enabled: bool,
+    pub outputs: Vec<IndicatorOutputConfig>,
+}
+
+/// One GPIO output and the condition that switches it on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorOutputConfig {
+    pub name: String,
+    /// Linux GPIO number (sysfs)
+    pub pin: u64,
+    pub condition: IndicatorCondition,
+    /// The output is driven low when the indicator is on
+    #[serde(default)]
+    pub active_low: bool,
+}
+
+/// State that lights an indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndicatorCondition {
+    /// System status Warning or worse
+    SystemWarning,
+    /// System status Fault or worse
+    SystemFault,
+    /// Emergency shutdown in effect
+    Emergency,
+    /// Any channel faulted
+    AnyChannelFault,
+    /// Any channel tripped on overcurrent
+    AnyOvercurrent,
+    /// The given channel faulted
+    ChannelFault(u8),
 }
 
 impl Config {
     /// Load configuration from file or create default
+    ///
+    /// When a signing key is installed the file must match its detached
+    /// signature (see `signing`).
     pub fn load() -> Result<Self> {
-        const CONFIG_FILE: &str = "pdm_config.toml";
+        let path = std::path::Path::new(CONFIG_FILE);
         
-        if std::path::Path::new(CONFIG_FILE).exists() {
+        if path.exists() {
             // Load from file
             let config_str = fs::read_to_string(CONFIG_FILE)?;
-            let config: Config = toml::from_str(&config_str)?;
-            Ok(config)
+            signing::check_config(path, &config_str)?;
+            Self::parse(&config_str)
         } else {
-            // Create default configuration
+            // Create default configuration; an unsigned default doesn't bypass a required signature
             let config = Self::default();
             config.save()?;
+            signing::check_config(path, &fs::read_to_string(CONFIG_FILE)?)?;
             Ok(config)
         }
     }
     
+    /// Parse a config file, reporting keys the backend doesn't know
+    ///
+    /// Serde would otherwise drop a misspelt key (`simulaton_mode`) silently
+    /// and run with the default. Unknown keys are logged with their path, or
+    /// refused when `strict_config` is set.
+    pub fn parse(config_str: &str) -> Result<Self> {
+        let raw: toml::Value = toml::from_str(config_str)?;
+        let config: Config = raw.clone().try_into()?;
+        let unknown = unknown_keys(&raw, &toml::Value::try_from(&config)?, "");
+        if !unknown.is_empty() {
+            if config.strict_config {
+                bail!("Unknown config keys (strict_config is set): {}", unknown.join(", "));
+            }
+            for key in &unknown {
+                warn!("Unknown config key '{}' ignored - check the spelling", key);
+            }
+        }
+        Ok(config)
+    }
+    
+    /// Operating summary logged at startup, one line per aspect
+    pub fn startup_summary(&self) -> Vec<String> {
+        let hardware = &self.hardware;
+        let safety = &self.safety;
+        let unit = self.unit.resolve();
+        
+        let transport = if hardware.simulation_mode {
+            "simulator".to_string()
+        } else {
+            let mut links = Vec::new();
+            links.push(format!("serial {} @ {} baud",
+                               hardware.serial_port.as_deref().unwrap_or("auto-detect"), hardware.serial_baud_rate));
+            if let Some(interface) = &hardware.can_interface {
+                links.push(format!("CAN {} @ {}kbps", interface, hardware.can_bitrate / 1000));
+            }
+            links.join(", ")
+        };
+        
+        let features = [
+            ("audit", self.audit.enabled),
+            ("webhooks", self.webhooks.enabled),
+            ("can_output", self.can_output.enabled),
+            ("telemetry", self.telemetry.enabled),
+            ("indicators", self.indicators.enabled),
+            ("aggregator", self.aggregator.enabled),
+            ("heartbeat", self.heartbeat.enabled),
+            ("ignition", self.ignition.enabled),
+            ("interlock", self.interlock.enabled),
+            ("adaptive_rate", hardware.adaptive_rate.enabled),
+            ("response_envelope", self.response_envelope),
+        ];
+        let list = |enabled: bool| {
+            let names: Vec<&str> = features.iter().filter(|(_, on)| *on == enabled).map(|(name, _)| *name).collect();
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        };
+        
+        vec![
+            format!("Unit:      {} ({})", unit.id, unit.name),
+            format!("Mode:      {}", if hardware.simulation_mode { "SIMULATION" } else { "REAL HARDWARE" }),
+            format!("Transport: {}", transport),
+            format!("Ground:    {}", match self.system.ground_polarity {
+                GroundPolarity::Negative => "negative",
+                GroundPolarity::Positive => "positive (voltage and current signs flipped on ingest)",
+            }),
+            format!("Channels:  {} ({} customized, {} mirror groups, {} parallel groups)",
+                    hardware.channel_count, self.channels.len(), self.mirror_groups.len(), self.parallel_groups.len()),
+            format!("Safety:    input {:.1}-{:.1}V, total {:.0}A, PDM {:.0}°C, channel {:.0}°C, default limit {:.1}A, reverse {:.1}A",
+                    safety.min_input_voltage, safety.max_input_voltage, safety.max_total_current,
+                    safety.max_temperature, safety.max_channel_temperature,
+                    safety.default_channel_current_limit, safety.max_reverse_current),
+            format!("Features:  {} (disabled: {})", list(true), list(false)),
+            format!("API:       http://{}{}", self.server_address, self.api_base_path),
+        ]
+    }
+    
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
-        const CONFIG_FILE: &str = "pdm_config.toml";
         let config_str = toml::to_string_pretty(self)?;
         fs::write(CONFIG_FILE, config_str)?;
         Ok(())
     }
+    
+    /// The loadout part of this config as a profile
+    pub fn profile(&self, name: Option<String>) -> Profile {
+        Profile {
+            name,
+            channels: self.channels.clone(),
+            mirror_groups: self.mirror_groups.clone(),
+            parallel_groups: self.parallel_groups.clone(),
+            zones: self.zones.clone(),
+            sequence: self.sequence.clone(),
+        }
+    }
+    
+    /// This config with its loadout replaced by `profile`
+    pub fn with_profile(&self, profile: Profile) -> Config {
+        Config {
+            channels: profile.channels,
+            mirror_groups: profile.mirror_groups,
+            parallel_groups: profile.parallel_groups,
+            zones: profile.zones,
+            sequence: profile.sequence,
+            ..self.clone()
+        }
+    }
+    
+    /// The complete configuration as JSON with credentials replaced by `REDACTED`
+    ///
+    /// Secret fields are redacted whether or not they are set. URLs keep only
+    /// their scheme, host and port: a username/password, and the path and
+    /// query (where webhook services put their tokens), are stripped.
+    pub fn redacted(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).expect("config serializes to JSON");
+        redact(&mut value);
+        value
+    }
+}
+
+fn redact(value: &mut serde_json::Value) {
+    use serde_json::Value;
+    
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                let name = name.to_ascii_lowercase();
+                if SECRET_FIELDS.iter().any(|secret| name.ends_with(secret)) {
+                    if !field.is_null() {
+                        *field = Value::String(REDACTED.to_string());
+                    }
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        Value::String(text) => {
+            if let Some(mut url) = reqwest::Url::parse(text).ok().filter(|url| url.has_host()) {
+                let sensitive = !url.username().is_empty() || url.password().is_some()
+                    || url.path() != "/" || url.query().is_some() || url.fragment().is_some();
+                if sensitive {
+                    // Only fails for URLs that can't carry credentials in the first place
+                    let _ = url.set_username("");
+                    let _ = url.set_password(None);
+                    url.set_path("");
+                    url.set_query(None);
+                    url.set_fragment(None);
+                    *text = url.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Paths of the keys in `raw` that don't appear in `known`
+///
+/// `known` is the parsed config serialized back, so it holds every key the
+/// schema accepted. Arrays are compared element by element.
+pub fn unknown_keys(raw: &toml::Value, known: &toml::Value, path: &str) -> Vec<String> {
+    let join = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match (raw, known) {
+        (toml::Value::Table(raw), toml::Value::Table(known)) => raw.iter()
+            .flat_map(|(key, value)| match known.get(key) {
+                Some(known) => unknown_keys(value, known, &join(key)),
+                None => vec![join(key)],
+            })
+            .collect(),
+        (toml::Value::Array(raw), toml::Value::Array(known)) => raw.iter().zip(known).enumerate()
+            .flat_map(|(i, (value, known))| unknown_keys(value, known, &format!("{}[{}]", path, i)))
+            .collect(),
+        _ => Vec::new(),
+    }
 }
 
 impl Default for Config {
@@ -112,6 +2122,15 @@ impl Default for Config {
         Self {
             server_address: "127.0.0.1:3030".to_string(),
             api_version: "1.0.0".to_string(),
+            api_base_path: default_api_base_path(),
+            response_envelope: false,
+            status_cache_ttl_ms: default_status_cache_ttl_ms(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            strict_config: false,
+            features: FeaturesConfig::default(),
+            precision: PrecisionConfig::default(),
+            unit: UnitConfig::default(),
+            system: SystemConfig::default(),
             
             hardware: HardwareConfig {
                 serial_port: None, // Auto-detect
@@ -120,7 +2139,22 @@ impl Default for Config {
                 can_bitrate: 500000, // 500kbps
                 status_update_interval_ms: 100, // 10Hz
                 monitoring_interval_ms: 50,     // 20Hz
+                temperature_interval_ms: None,
                 simulation_mode: true, // Start in simulation mode
+                channel_count: default_channel_count(),
+                rating_check: CheckAction::Warn,
+                min_firmware_version: None,
+                firmware_check: CheckAction::Warn,
+                adaptive_rate: AdaptiveRateConfig::default(),
+                startup_delay_ms: default_startup_delay_ms(),
+                handshake_attempts: default_handshake_attempts(),
+                handshake_retry_ms: default_handshake_retry_ms(),
+                read_timeout_ms: default_read_timeout_ms(),
+                read_retries: default_read_retries(),
+                read_failure_action: ReadFailureAction::default(),
+                missing_transport: MissingTransportAction::default(),
+                current_sensors: CurrentSensorsConfig::default(),
+                max_time_step_ms: default_max_time_step_ms(),
             },
             
             safety: SafetyConfig {
@@ -128,8 +2162,24 @@ impl Default for Config {
                 min_input_voltage: 10.0,
                 max_total_current: 100.0,
                 max_temperature: 85.0,
+                max_channel_temperature: default_max_channel_temperature(),
+                max_reverse_current: default_max_reverse_current(),
+                open_load_debounce_ms: default_open_load_debounce_ms(),
                 default_channel_current_limit: 15.0,
+                current_warning_fraction: default_current_warning_fraction(),
+                max_input_ripple: default_max_input_ripple(),
+                ripple_window_samples: default_ripple_window_samples(),
+                status_downgrade_cycles: default_status_downgrade_cycles(),
+                max_total_power: None,
+                max_active_channels: None,
+                power_budget_check: default_power_budget_check(),
+                anomaly: AnomalyConfig::default(),
+                switch_rate: SwitchRateConfig::default(),
+                thermal_shedding: ThermalSheddingConfig::default(),
                 emergency_shutdown_timeout: 5,
+                fault_handling: FaultHandlingConfig::default(),
+                ramp_down_on_emergency: false,
+                emergency_confirmation: EmergencyConfirmationConfig::default(),
             },
             
             logging: LoggingConfig {
@@ -137,9 +2187,109 @@ impl Default for Config {
                 log_to_file: true,
                 log_file_path: Some("pdm_backend.log".to_string()),
             },
+            
+            aggregator: AggregatorConfig::default(),
+            audit: AuditConfig::default(),
+            lifetime: LifetimeConfig::default(),
+            history: HistoryConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            indicators: IndicatorConfig::default(),
+            learn: LearnConfig::default(),
+            energy: EnergyConfig::default(),
+            battery: BatteryConfig::default(),
+            test_mode: TestModeConfig::default(),
+            simulation: SimulationConfig::default(),
+            stream: StreamConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            ignition: IgnitionConfig::default(),
+            interlock: InterlockConfig::default(),
+            webhooks: WebhookConfig::default(),
+            can_output: CanOutputConfig::default(),
+            sequence: SequenceConfig::default(),
+            validation: ValidationConfig::default(),
+            mirror_groups: Vec::new(),
+            parallel_groups: Vec::new(),
+            schedules: Vec::new(),
+            zones: Vec::new(),
+            alerts: Vec::new(),
+            channels: Vec::new(),
         }
     }
 }
 
 // Add toml dependency to Cargo.toml
 use toml;
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_pdm_state_rejects_duplicate_channel() {
+        use crate::config::ChannelConfig;
+        
+        let config = Config {
+            channels: vec![
+                ChannelConfig { ch: 3, name: Some("WATER PUMP".to_string()), ..Default::default() },
+                ChannelConfig { ch: 3, name: Some("HORN".to_string()), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        
+        let error = PdmState::from_config(&config).unwrap_err().to_string();
+        assert!(error.contains("Channel 3 is defined more than once"));
+    }
+    
+    #[test]
+    fn test_pdm_state_rejects_out_of_range_channel() {
+        use crate::config::ChannelConfig;
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig { ch: 9, ..Default::default() }],
+            ..Default::default()
+        };
+        assert!(PdmState::from_config(&config).unwrap_err().to_string().contains("outside the configured range 1-8"));
+        
+        config.channels = vec![ChannelConfig { ch: 0, ..Default::default() }];
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[test]
+    fn test_startup_summary() {
+        let mut config = Config::default();
+        config.telemetry.enabled = true;
+        let summary = config.startup_summary();
+        assert!(summary.contains(&"Mode:      SIMULATION".to_string()));
+        assert!(summary.contains(&"Transport: simulator".to_string()));
+        let features = summary.iter().find(|line| line.starts_with("Features:")).unwrap();
+        assert!(features.starts_with("Features:  audit, telemetry (disabled: webhooks, "));
+        assert!(summary.contains(&"API:       http://127.0.0.1:3030/api".to_string()));
+        
+        config.hardware.simulation_mode = false;
+        let summary = config.startup_summary();
+        assert!(summary.contains(&"Mode:      REAL HARDWARE".to_string()));
+        assert!(summary.contains(&"Transport: serial auto-detect @ 115200 baud, CAN can0 @ 500kbps".to_string()));
+    }
+    
+    #[test]
+    fn test_unknown_config_keys() {
+        use crate::config::{unknown_keys, Config};
+        
+        let config_str = toml::to_string(&Config::default()).unwrap()
+            .replace("api_version =", "simulaton_mode = true\napi_version =")
+            .replace("[safety]", "[safety]\nmax_curent = 30.0");
+        let raw: toml::Value = toml::from_str(&config_str).unwrap();
+        let config = Config::parse(&config_str).unwrap();
+        let known = toml::Value::try_from(&config).unwrap();
+        assert_eq!(unknown_keys(&raw, &known, ""), vec!["safety.max_curent", "simulaton_mode"]);
+        
+        // A clean config has nothing left over
+        let clean: toml::Value = toml::from_str(&toml::to_string(&Config::default()).unwrap()).unwrap();
+        assert!(unknown_keys(&clean, &known, "").is_empty());
+        
+        let strict = config_str.replace("strict_config = false", "strict_config = true");
+        let error = Config::parse(&strict).unwrap_err().to_string();
+        assert!(error.contains("simulaton_mode") && error.contains("safety.max_curent"));
+    }
+}
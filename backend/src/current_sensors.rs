@@ -0,0 +1,123 @@
+/*!
+ * I²C Current Sensors
+ *
+ * Per-channel voltage and current from INA219/INA226 shunt monitors on a
+ * Linux I²C bus, for homemade PDMs without a serial controller reporting
+ * channel status. Each configured sensor is mapped to one channel. Current is
+ * worked out from the shunt voltage and the configured shunt resistance, so
+ * the chips' calibration registers are left at their power-on defaults.
+ */
+
+use anyhow::{Context, Result};
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+
+use crate::config::{CurrentSensorConfig, CurrentSensorsConfig, SensorModel};
+
+/// Shunt voltage register (both models)
+const REG_SHUNT_VOLTAGE: u8 = 0x01;
+/// Bus voltage register (both models)
+const REG_BUS_VOLTAGE: u8 = 0x02;
+
+/// One sensor's reading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorReading {
+    /// Bus (load-side) voltage (V)
+    pub voltage: f32,
+    /// Current through the shunt (A, negative when flowing back)
+    pub current: f32,
+}
+
+/// Convert the raw shunt and bus voltage registers into a reading
+pub fn convert(model: SensorModel, shunt_raw: u16, bus_raw: u16, shunt_ohms: f32) -> SensorReading {
+    // The shunt register is two's complement; the bus register is unsigned
+    let shunt_volts = f32::from(shunt_raw as i16) * match model {
+        SensorModel::Ina219 => 10e-6,
+        SensorModel::Ina226 => 2.5e-6,
+    };
+    let voltage = match model {
+        // Bits 15-3 hold the voltage in 4mV steps; the low bits are flags
+        SensorModel::Ina219 => f32::from(bus_raw >> 3) * 0.004,
+        SensorModel::Ina226 => f32::from(bus_raw) * 0.00125,
+    };
+    SensorReading { voltage, current: shunt_volts / shunt_ohms }
+}
+
+/// The configured sensors, opened on their bus
+pub struct CurrentSensors {
+    sensors: Vec<(CurrentSensorConfig, LinuxI2CDevice)>,
+}
+
+impl CurrentSensors {
+    /// Open every configured sensor on the bus
+    pub fn open(config: &CurrentSensorsConfig) -> Result<Self> {
+        let sensors = config.sensors.iter()
+            .map(|sensor| {
+                let device = LinuxI2CDevice::new(&config.bus, sensor.address)
+                    .with_context(|| format!("Cannot open {:?} at 0x{:02x} on {} for channel {}",
+                                             sensor.model, sensor.address, config.bus, sensor.channel))?;
+                Ok((sensor.clone(), device))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { sensors })
+    }
+
+    /// Read every sensor, keyed by channel
+    ///
+    /// Blocking bus I/O; a sensor that can't be read is reported in its slot
+    /// without holding up the others.
+    pub fn read(&mut self) -> Vec<(u8, Result<SensorReading>)> {
+        self.sensors.iter_mut()
+            .map(|(sensor, device)| {
+                let reading = read_registers(device).map(|(shunt_raw, bus_raw)| {
+                    convert(sensor.model, shunt_raw, bus_raw, sensor.shunt_ohms)
+                });
+                let reading = reading.with_context(|| {
+                    format!("{:?} at 0x{:02x} (channel {})", sensor.model, sensor.address, sensor.channel)
+                });
+                (sensor.channel, reading)
+            })
+            .collect()
+    }
+}
+
+fn read_registers(device: &mut LinuxI2CDevice) -> Result<(u16, u16)> {
+    // The registers are big-endian; SMBus words arrive low byte first
+    let shunt_raw = device.smbus_read_word_data(REG_SHUNT_VOLTAGE)?.swap_bytes();
+    let bus_raw = device.smbus_read_word_data(REG_BUS_VOLTAGE)?.swap_bytes();
+    Ok((shunt_raw, bus_raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_ina_sensor_conversion_and_config() {
+        use crate::config::{CurrentSensorConfig, SensorModel};
+        use crate::current_sensors::convert;
+        
+        // INA219: 10µV shunt LSB, bus voltage in bits 15-3 at 4mV
+        let reading = convert(SensorModel::Ina219, 1000, 3000 << 3 | 0b010, 0.01);
+        assert!((reading.voltage - 12.0).abs() < 1e-4);
+        assert!((reading.current - 1.0).abs() < 1e-4);
+        let backfeed = convert(SensorModel::Ina219, (-500i16) as u16, 0, 0.01);
+        assert!((backfeed.current + 0.5).abs() < 1e-4);
+        
+        // INA226: 2.5µV shunt LSB, 1.25mV bus LSB
+        let reading = convert(SensorModel::Ina226, 4000, 11040, 0.002);
+        assert!((reading.voltage - 13.8).abs() < 1e-4);
+        assert!((reading.current - 5.0).abs() < 1e-3);
+        
+        let sensor = |channel, address| CurrentSensorConfig { channel, address, model: SensorModel::Ina226, shunt_ohms: 0.002 };
+        let mut config = Config::default();
+        config.hardware.current_sensors.enabled = true;
+        config.hardware.current_sensors.sensors = vec![sensor(1, 0x40), sensor(2, 0x41)];
+        assert!(PdmState::from_config(&config).is_ok());
+        config.hardware.current_sensors.sensors[1].address = 0x40;
+        assert!(PdmState::from_config(&config).is_err());
+        config.hardware.current_sensors.sensors[1] = sensor(9, 0x41);
+        assert!(PdmState::from_config(&config).is_err());
+    }
+}
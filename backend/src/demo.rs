@@ -0,0 +1,274 @@
+/*!
+ * Simulator Demo Mode
+ *
+ * Cycles the simulated channels on and off by itself, for trade-show
+ * displays and long unattended demos. Each step picks the channels that
+ * should be ON for the running pattern and switches the difference. The
+ * demo only drives ordinary channels (not critical, overridden, grouped,
+ * faulted or with a blown fuse) and only switches one on while the master
+ * switch, emergency latch, energy budget, power budget, active-channel limit
+ * and minimum OFF time allow it. Stopping the demo switches off what it
+ * left on.
+ */
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{Notify, RwLock};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::config::{DemoPattern, SafetyConfig};
+use crate::hardware::HardwareManager;
+use crate::models::{ChannelStatus, PdmState, TransitionTrigger};
+
+/// Demo state reported by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct DemoStatus {
+    pub running: bool,
+    pub pattern: Option<DemoPattern>,
+    pub step_ms: Option<u64>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+struct Run {
+    pattern: DemoPattern,
+    step_ms: u64,
+    since: DateTime<Utc>,
+    stop: Arc<Notify>,
+}
+
+/// The demo run, if one is going
+#[derive(Default)]
+pub struct Demo {
+    run: Mutex<Option<Run>>,
+}
+
+impl Demo {
+    /// Whether a demo is running
+    pub fn is_running(&self) -> bool {
+        self.run.lock().unwrap().is_some()
+    }
+
+    pub fn status(&self) -> DemoStatus {
+        let run = self.run.lock().unwrap();
+        DemoStatus {
+            running: run.is_some(),
+            pattern: run.as_ref().map(|run| run.pattern),
+            step_ms: run.as_ref().map(|run| run.step_ms),
+            since: run.as_ref().map(|run| run.since),
+        }
+    }
+
+    /// Signal the running demo to stop; returns whether one was running
+    pub fn stop(&self) -> bool {
+        match self.run.lock().unwrap().take() {
+            Some(run) => {
+                run.stop.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Start cycling channels in the background (simulation mode only)
+pub fn start(
+    hardware_manager: &Arc<HardwareManager>,
+    pdm_state: &Arc<RwLock<PdmState>>,
+    pattern: DemoPattern,
+    step_ms: u64,
+) -> Result<()> {
+    if !hardware_manager.is_simulation() {
+        bail!("Demo mode is only available in simulation mode");
+    }
+    if step_ms == 0 {
+        bail!("Demo step must be at least 1ms");
+    }
+
+    let stop = Arc::new(Notify::new());
+    {
+        let mut run = hardware_manager.demo().run.lock().unwrap();
+        if run.is_some() {
+            bail!("A demo is already running");
+        }
+        *run = Some(Run { pattern, step_ms, since: Utc::now(), stop: Arc::clone(&stop) });
+    }
+
+    info!("[SIM] Demo started: {:?} every {}ms", pattern, step_ms);
+    let hardware_manager = Arc::clone(hardware_manager);
+    let pdm_state = Arc::clone(pdm_state);
+    tokio::spawn(async move {
+        let mut lit = BTreeSet::new();
+        let mut step = 0u64;
+        loop {
+            let wanted = {
+                let state = pdm_state.read().await;
+                wanted_on(pattern, step, &drivable(&state))
+            };
+            apply(&hardware_manager, &pdm_state, &wanted, &mut lit).await;
+            step += 1;
+
+            tokio::select! {
+                _ = sleep(Duration::from_millis(step_ms)) => {}
+                _ = stop.notified() => break,
+            }
+        }
+        apply(&hardware_manager, &pdm_state, &BTreeSet::new(), &mut lit).await;
+        info!("[SIM] Demo stopped");
+    });
+    Ok(())
+}
+
+/// Channels the demo may switch
+fn drivable(state: &PdmState) -> Vec<u8> {
+    let mut channels: Vec<u8> = state.channels.values()
+        .filter(|channel| {
+            !channel.critical
+                && !channel.overridden
+                && !channel.fuse_blown
+                && channel.mirrored_with.is_empty()
+                && channel.status != ChannelStatus::Fault
+        })
+        .map(|channel| channel.ch)
+        .collect();
+    channels.sort_unstable();
+    channels
+}
+
+/// Channels a pattern has ON at a step
+pub fn wanted_on(pattern: DemoPattern, step: u64, channels: &[u8]) -> BTreeSet<u8> {
+    let count = channels.len() as u64;
+    if count == 0 {
+        return BTreeSet::new();
+    }
+    match pattern {
+        // One channel lit, moving along the row
+        DemoPattern::Chase => BTreeSet::from([channels[(step % count) as usize]]),
+        DemoPattern::Random => channels.iter().copied().filter(|_| rand::random::<bool>()).collect(),
+        // Add one channel per step until all are on, then drop them again
+        DemoPattern::LoadTest => {
+            let phase = step % (2 * count);
+            let lit = phase.min(2 * count - phase) as usize;
+            channels[..lit].iter().copied().collect()
+        }
+    }
+}
+
+/// Switch the channels the demo drives to match `wanted`
+async fn apply(
+    hardware_manager: &HardwareManager,
+    pdm_state: &Arc<RwLock<PdmState>>,
+    wanted: &BTreeSet<u8>,
+    lit: &mut BTreeSet<u8>,
+) {
+    let (to_off, to_on): (Vec<u8>, Vec<u8>) = {
+        let state = pdm_state.read().await;
+        let on = |ch: &u8| state.channels.get(ch).is_some_and(|channel| channel.status == ChannelStatus::On);
+        let to_off = lit.iter().copied().filter(|ch| !wanted.contains(ch) && on(ch)).collect();
+        let to_on = wanted.iter().copied().filter(|ch| !on(ch)).collect();
+        (to_off, to_on)
+    };
+    lit.retain(|ch| wanted.contains(ch));
+
+    hardware_manager.ramp_down(pdm_state, &to_off).await;
+    for ch in to_off {
+        switch(hardware_manager, pdm_state, ch, false).await;
+    }
+
+    let safety = hardware_manager.config().safety.clone();
+    for ch in to_on {
+        if !may_switch_on(&*pdm_state.read().await, ch, &safety) {
+            continue;
+        }
+        if switch(hardware_manager, pdm_state, ch, true).await {
+            lit.insert(ch);
+        }
+    }
+}
+
+/// Whether the demo may switch a channel on now: it must be idle, past its
+/// dwell time and allowed by the shared switch-on gate
+fn may_switch_on(state: &PdmState, ch: u8, safety: &SafetyConfig) -> bool {
+    let Some(channel) = state.channels.get(&ch) else { return false };
+    if channel.status != ChannelStatus::Off || channel.dwell_remaining(true, Instant::now()).is_some() {
+        return false;
+    }
+    state.check_switch_on(&[ch], safety).is_ok()
+}
+
+/// Command one channel and mirror it into the state; returns whether it switched
+async fn switch(hardware_manager: &HardwareManager, pdm_state: &Arc<RwLock<PdmState>>, ch: u8, on: bool) -> bool {
+    if let Err(e) = hardware_manager.control_channel(ch, on).await {
+        warn!("[SIM] Demo failed to switch channel {} {}: {}", ch, if on { "ON" } else { "OFF" }, e);
+        return false;
+    }
+
+    let mut state = pdm_state.write().await;
+    let Some(channel) = state.channels.get_mut(&ch) else { return false };
+    let status = if on { ChannelStatus::On } else { ChannelStatus::Off };
+    channel.set_status(status, TransitionTrigger::Demo);
+    channel.ramp_duty = None;
+    channel.switched_on_at = on.then(Instant::now);
+    channel.last_update = Utc::now();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[test]
+    fn test_demo_patterns() {
+        use crate::config::DemoPattern;
+        use crate::demo::wanted_on;
+        use std::collections::BTreeSet;
+        
+        let channels = [2, 4, 6];
+        let chase: Vec<BTreeSet<u8>> = (0..4).map(|step| wanted_on(DemoPattern::Chase, step, &channels)).collect();
+        assert_eq!(chase, vec![BTreeSet::from([2]), BTreeSet::from([4]), BTreeSet::from([6]), BTreeSet::from([2])]);
+        
+        let load: Vec<usize> = (0..7).map(|step| wanted_on(DemoPattern::LoadTest, step, &channels).len()).collect();
+        assert_eq!(load, vec![0, 1, 2, 3, 2, 1, 0]);
+        assert!(wanted_on(DemoPattern::Random, 0, &channels).is_subset(&BTreeSet::from(channels)));
+        assert!(wanted_on(DemoPattern::Chase, 0, &[]).is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_demo_respects_limits_and_stops_cleanly() {
+        use crate::config::{ChannelConfig, DemoPattern};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig { ch: 1, critical: true, ..Default::default() }],
+            ..Default::default()
+        };
+        config.safety.max_active_channels = Some(3);
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        pdm_state.write().await.channels.get_mut(&2).unwrap().fuse_blown = true;
+        let hardware_manager = Arc::new(crate::hardware::HardwareManager::new(config).unwrap());
+        
+        crate::demo::start(&hardware_manager, &pdm_state, DemoPattern::LoadTest, 5).unwrap();
+        assert!(crate::demo::start(&hardware_manager, &pdm_state, DemoPattern::Chase, 5).is_err());
+        let mut peak = 0;
+        for _ in 0..40 {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&1].status, ChannelStatus::Off);
+            assert_eq!(state.channels[&2].status, ChannelStatus::Off);
+            peak = peak.max(state.active_channel_count());
+        }
+        assert_eq!(peak, 3);
+        
+        assert!(hardware_manager.demo().stop());
+        assert!(!hardware_manager.demo().is_running());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pdm_state.read().await.active_channel_count(), 0);
+    }
+}
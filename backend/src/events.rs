@@ -0,0 +1,128 @@
+/*!
+ * System Event Log
+ *
+ * Notable system events (hardware connection changes, safety actions, etc.)
+ * are kept in a bounded in-memory log and broadcast to live subscribers
+ * such as the SSE event stream.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Number of events kept in memory
+const EVENT_LOG_CAPACITY: usize = 500;
+/// Number of events buffered per live subscriber before it starts lagging
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Event types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    HardwareDisconnected,
+    HardwareReconnected,
+    HeartbeatLost,
+    /// A channel tripped on a fault
+    ChannelFault,
+    EmergencyShutdown,
+    /// The system status moved to a different severity
+    SystemStatusChanged,
+    /// The energy budget is running low or used up
+    EnergyBudget,
+    /// Something was done that wears the hardware, e.g. restarting a load inside its minimum off time
+    MaintenanceConcern,
+    /// A configured alert rule was raised
+    Alert,
+    /// A raised alert is back within its threshold
+    AlertCleared,
+    /// A channel was shed or restored by thermal load shedding
+    LoadShedding,
+    /// The safety interlock opened, holding every channel off
+    InterlockOpened,
+    /// The safety interlock closed again
+    InterlockClosed,
+    /// A channel is switching more often than `safety.switch_rate` allows
+    SwitchRate,
+}
+
+/// A single system event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub kind: EventKind,
+    /// Channel the event relates to, if any
+    pub channel: Option<u8>,
+    pub message: String,
+    /// Id of the unit that raised the event
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+/// Bounded event log with live broadcast
+pub struct EventLog {
+    recent: Mutex<VecDeque<Event>>,
+    sender: broadcast::Sender<Event>,
+    unit: Option<String>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    /// Create an empty event log
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            recent: Mutex::new(VecDeque::with_capacity(EVENT_LOG_CAPACITY)),
+            sender,
+            unit: None,
+        }
+    }
+
+    /// Create an empty event log that tags every event with a unit id
+    pub fn with_unit(unit: impl Into<String>) -> Self {
+        Self {
+            unit: Some(unit.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Record an event and broadcast it to subscribers
+    pub fn emit(&self, kind: EventKind, channel: Option<u8>, message: impl Into<String>) {
+        let event = Event {
+            timestamp: Utc::now(),
+            kind,
+            channel,
+            message: message.into(),
+            unit: self.unit.clone(),
+        };
+
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == EVENT_LOG_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+
+        // No subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// The most recent `limit` events, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<Event> {
+        let recent = self.recent.lock().unwrap();
+        let start = recent.len().saturating_sub(limit);
+        recent.iter().skip(start).cloned().collect()
+    }
+
+    /// Subscribe to live events
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,209 @@
+/*!
+ * Multi-PDM Aggregation
+ *
+ * In aggregator mode the backend polls the status endpoint of a list of
+ * downstream PDM backends and merges them into a single fleet view.
+ * Units that cannot be reached are reported as offline instead of failing
+ * the whole response.
+ */
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tokio::time::{interval, Duration};
+use tracing::{debug, info, warn};
+
+use crate::api::{normalize_base_path, unwrap_envelope};
+use crate::config::AggregatorConfig;
+use crate::models::{FleetStatusResponse, FleetUnitStatus, SystemStatus, SystemStatusResponse};
+
+/// Polls downstream PDM units and keeps their latest status
+pub struct FleetMonitor {
+    config: AggregatorConfig,
+    client: reqwest::Client,
+    units: RwLock<Vec<FleetUnitStatus>>,
+}
+
+impl FleetMonitor {
+    /// Create a new fleet monitor for the configured units
+    pub fn new(config: AggregatorConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()?;
+
+        let units = config.units.iter()
+            .map(|url| FleetUnitStatus {
+                url: url.trim_end_matches('/').to_string(),
+                online: false,
+                status: None,
+                error: None,
+                last_seen: None,
+            })
+            .collect();
+
+        Ok(Self {
+            config,
+            client,
+            units: RwLock::new(units),
+        })
+    }
+
+    /// Whether aggregator mode is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Start the polling loop
+    pub async fn start_polling(&self) -> Result<()> {
+        info!("Starting fleet polling for {} units", self.config.units.len());
+
+        let mut poll_interval = interval(Duration::from_millis(self.config.poll_interval_ms));
+
+        loop {
+            poll_interval.tick().await;
+            self.poll_once().await;
+        }
+    }
+
+    /// Poll every unit once, concurrently, and record the results
+    pub async fn poll_once(&self) {
+        let base_path = normalize_base_path(&self.config.api_base_path);
+        let urls: Vec<String> = self.units.read().await
+            .iter()
+            .map(|unit| format!("{}{}", unit.url, base_path))
+            .collect();
+
+        let mut polls = JoinSet::new();
+        for (index, url) in urls.into_iter().enumerate() {
+            let client = self.client.clone();
+            polls.spawn(async move { (index, fetch_status(&client, &url).await) });
+        }
+
+        while let Some(joined) = polls.join_next().await {
+            let Ok((index, result)) = joined else { continue };
+            let mut units = self.units.write().await;
+            let unit = &mut units[index];
+
+            match result {
+                Ok(status) => {
+                    if !unit.online {
+                        info!("Fleet unit {} is online", unit.url);
+                    }
+                    debug!("Fleet unit {} polled", unit.url);
+                    unit.online = true;
+                    unit.status = Some(status);
+                    unit.error = None;
+                    unit.last_seen = Some(Utc::now());
+                }
+                Err(e) => {
+                    if unit.online || unit.error.is_none() {
+                        warn!("Fleet unit {} is offline: {}", unit.url, e);
+                    }
+                    // Keep the last known status for display, but mark the unit offline
+                    unit.online = false;
+                    unit.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Build the combined fleet view
+    pub async fn fleet_status(&self) -> FleetStatusResponse {
+        let units = self.units.read().await.clone();
+
+        let online: Vec<&SystemStatusResponse> = units.iter()
+            .filter(|unit| unit.online)
+            .filter_map(|unit| unit.status.as_ref())
+            .collect();
+
+        let total_current = online.iter().map(|s| s.pdm_state.total_current).sum();
+        let total_power = online.iter().map(|s| s.pdm_state.total_power()).sum();
+
+        // Worst status among online units; an unreachable unit is at least a warning
+        let mut system_status = online.iter()
+            .map(|s| s.pdm_state.system_status.clone())
+            .max_by_key(|status| status.severity())
+            .unwrap_or(SystemStatus::Normal);
+        if online.len() < units.len() && system_status.severity() < SystemStatus::Warning.severity() {
+            system_status = SystemStatus::Warning;
+        }
+
+        FleetStatusResponse {
+            total_units: units.len(),
+            online_units: online.len(),
+            total_current,
+            total_power,
+            system_status,
+            units,
+        }
+    }
+}
+
+/// Fetch the status of a single downstream unit from its API root `api_url`
+///
+/// Units that envelope their responses by default are unwrapped.
+async fn fetch_status(client: &reqwest::Client, api_url: &str) -> Result<SystemStatusResponse> {
+    let body = client.get(format!("{}/status", api_url))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+    Ok(serde_json::from_value(unwrap_envelope(body))?)
+}
+
+#[cfg(test)]
+mod tests {
+    #[tokio::test]
+    async fn test_fleet_marks_unreachable_unit_offline() {
+        let config = crate::config::AggregatorConfig {
+            enabled: true,
+            units: vec!["http://127.0.0.1:1".to_string()],
+            request_timeout_ms: 200,
+            ..Default::default()
+        };
+        let fleet = crate::fleet::FleetMonitor::new(config).unwrap();
+        
+        fleet.poll_once().await;
+        let status = fleet.fleet_status().await;
+        
+        // Unreachable unit is reported, not dropped
+        assert_eq!(status.total_units, 1);
+        assert_eq!(status.online_units, 0);
+        assert!(!status.units[0].online);
+        assert!(status.units[0].error.is_some());
+        assert!(matches!(status.system_status, crate::models::SystemStatus::Warning));
+    }
+    
+    #[tokio::test]
+    async fn test_fleet_polls_enveloped_unit_under_base_path() {
+        use crate::config::Config;
+        use crate::test_support::test_app;
+        
+        let mut unit_config = Config::default();
+        unit_config.audit.enabled = false;
+        unit_config.api_base_path = "/pdm/v1".to_string();
+        unit_config.response_envelope = true;
+        let (app, _, _) = test_app(unit_config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+            axum::serve(listener, service).await.unwrap();
+        });
+        
+        let config = crate::config::AggregatorConfig {
+            enabled: true,
+            units: vec![format!("http://{}", addr)],
+            api_base_path: "/pdm/v1".to_string(),
+            ..Default::default()
+        };
+        let fleet = crate::fleet::FleetMonitor::new(config).unwrap();
+        fleet.poll_once().await;
+        let status = fleet.fleet_status().await;
+        assert_eq!(status.online_units, 1, "{:?}", status.units[0].error);
+        assert_eq!(status.units[0].status.as_ref().unwrap().pdm_state.channels.len(), 8);
+    }
+}
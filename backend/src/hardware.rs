@@ -1,23 +1,280 @@
 use anyhow::{Result, anyhow};
 use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::sync::Notify;
+use tokio::time::{interval, interval_at, Duration};
 use tracing::{info, warn, error, debug};
-use std::sync::Arc;
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use crate::config::Config;
-use crate::models::{PdmState, HardwareMessage, HardwareResponse, ChannelStatus, SystemStatus};
+use crate::alerts::{ActiveAlert, AlertEngine};
+use crate::battery::SocEstimator;
+use crate::config::{
+    AdaptiveRateConfig, CheckAction, Config, FaultAction, HardwareConfig, LoadModel, MissingTransportAction,
+    OvercurrentMode, ReadFailureAction, SafetyConfig, UnitIdentity,
+};
+use crate::current_sensors::CurrentSensors;
+use crate::demo::Demo;
+use crate::events::{EventKind, EventLog};
+use crate::history::HistoryBuffer;
+use crate::indicators::{IndicatorBank, IndicatorReport};
+use crate::lifetime::LifetimeCounters;
+use crate::models::{
+    current_imbalance, ripple_stats, time_step, PdmState, Channel, ChannelCommissionResult, ChannelFault, ChannelStatus,
+    CommissionReport, FaultSource, HardwareMessage, HardwareResponse, ReadKind, ShutdownReport, SimReading, SystemStatus,
+    TransitionTrigger,
+};
+use crate::sim_failures::FailureInjector;
 use memcache;
 
+/// Interval between PWM duty steps during a turn-off ramp
+const RAMP_STEP_MS: u64 = 20;
+
+/// Lowest duty (%) a current-limiting channel is held at before it trips as a short
+const MIN_LIMIT_DUTY: u8 = 10;
+
+/// Firmware version reported by the simulator
+const SIMULATED_FIRMWARE_VERSION: &str = "1.0.0";
+
+/// Link state between the backend and the PDM hardware
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// The board has not answered a handshake yet; readings are not trusted
+    Unknown,
+    Connected,
+    Disconnected,
+}
+
+/// Effective channel monitoring rate
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitoringRate {
+    pub adaptive: bool,
+    /// Current monitoring interval
+    pub interval_ms: u64,
+    pub rate_hz: f64,
+    /// Rate used while anything is changing
+    pub fast_interval_ms: u64,
+    /// Slowest rate when idle
+    pub idle_interval_ms: u64,
+}
+
+/// Malformed or unexpected frames received from the hardware
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProtocolErrors {
+    /// All dropped frames
+    pub total: u64,
+    /// Channel status frames for a channel outside the configured range
+    pub unknown_channel: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Periodic hardware status reads that failed (see `hardware.read_retries`)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadErrors {
+    /// Attempts that ran past `hardware.read_timeout_ms`
+    pub timeouts: u64,
+    /// Attempts that returned an error
+    pub errors: u64,
+    /// Reads that only succeeded on a retry
+    pub recovered: u64,
+    /// Reads that failed every attempt, by kind
+    pub failed: BTreeMap<ReadKind, u64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Readings compared to decide whether the PDM is idle
+#[derive(Debug, Clone)]
+struct RateSample {
+    input_voltage: f32,
+    system_severity: u8,
+    /// (channel, status, current, voltage, temperature)
+    channels: Vec<(u8, ChannelStatus, f32, f32, Option<f32>)>,
+}
+
+impl RateSample {
+    fn capture(state: &PdmState) -> Self {
+        let mut channels: Vec<_> = state.channels.iter()
+            .map(|(&id, ch)| (id, ch.status.clone(), ch.current, ch.voltage, ch.temperature))
+            .collect();
+        channels.sort_by_key(|(id, ..)| *id);
+        Self {
+            input_voltage: state.input_voltage,
+            system_severity: state.system_status.severity(),
+            channels,
+        }
+    }
+    
+    /// Whether `other` is within the deadbands of this sample with no status change
+    fn steady(&self, other: &RateSample, config: &AdaptiveRateConfig) -> bool {
+        self.system_severity == other.system_severity
+            && (self.input_voltage - other.input_voltage).abs() <= config.voltage_deadband
+            && self.channels.len() == other.channels.len()
+            && self.channels.iter().zip(&other.channels).all(|(a, b)| {
+                a.0 == b.0
+                    && a.1 == b.1
+                    && (a.2 - b.2).abs() <= config.current_deadband
+                    && (a.3 - b.3).abs() <= config.voltage_deadband
+                    && match (a.4, b.4) {
+                        (Some(x), Some(y)) => (x - y).abs() <= config.temperature_deadband,
+                        (x, y) => x.is_none() && y.is_none(),
+                    }
+            })
+    }
+}
+
+/// How long the readings have stayed steady
+#[derive(Debug, Default)]
+struct AdaptiveRate {
+    /// Sample the current steady run is compared against
+    reference: Option<RateSample>,
+    steady_samples: u32,
+}
+
+/// Debounces system status downgrades
+///
+/// A value hovering near a threshold would otherwise flip the status every
+/// cycle. Escalations are reported at once; a downgrade is reported only
+/// after the status has stayed below the reported one for a number of
+/// consecutive cycles, and then to the worst status seen during that run.
+#[derive(Debug, Default)]
+pub(crate) struct StatusDebounce {
+    /// Worst status seen since the evaluated status dropped below the reported one
+    pending: Option<SystemStatus>,
+    below_cycles: u32,
+}
+
+impl StatusDebounce {
+    /// Status to report, given the one reported last cycle and this cycle's evaluation
+    pub(crate) fn apply(&mut self, reported: SystemStatus, evaluated: SystemStatus, downgrade_cycles: u32) -> SystemStatus {
+        if evaluated.severity() >= reported.severity() {
+            *self = Self::default();
+            return evaluated;
+        }
+        
+        self.below_cycles += 1;
+        let worst = match self.pending.take() {
+            Some(pending) if pending.severity() > evaluated.severity() => pending,
+            _ => evaluated,
+        };
+        if self.below_cycles >= downgrade_cycles {
+            *self = Self::default();
+            worst
+        } else {
+            self.pending = Some(worst);
+            reported
+        }
+    }
+}
+
 /// Hardware manager handles all PDM hardware communication
 pub struct HardwareManager {
-    config: Config,
+    /// Current configuration, swapped as a whole on reload
+    config: ArcSwap<Config>,
+    /// Mode in use, which differs from `hardware.simulation_mode` after a fallback
     simulation_mode: bool,
+    /// Why real mode fell back to the simulator
+    mode_fallback: Option<String>,
+    connection_state: Mutex<ConnectionState>,
+    events: Arc<EventLog>,
+    history: Mutex<HistoryBuffer>,
+    /// Pinned simulated readings per channel (simulation mode only)
+    sim_overrides: Mutex<HashMap<u8, SimReading>>,
+    /// Channels failing on their own (simulation only)
+    sim_failures: Mutex<FailureInjector>,
+    /// When energy was last accumulated
+    energy_tick: Mutex<Option<Instant>>,
+    /// When thermal shedding last shed or restored a channel
+    shed_step: Mutex<Option<Instant>>,
+    /// Battery state-of-charge estimate
+    battery: Mutex<SocEstimator>,
+    /// Runtime, starts, energy and ON-time across restarts
+    lifetime: Arc<LifetimeCounters>,
+    /// Recent input voltage samples for ripple estimation
+    voltage_window: Mutex<VecDeque<f32>>,
+    /// Reference time for time-dependent simulation
+    started: Instant,
+    /// Identity of this unit (fixed at startup)
+    unit: UnitIdentity,
+    /// System status last reported as an event
+    reported_status: Mutex<SystemStatus>,
+    status_debounce: Mutex<StatusDebounce>,
+    /// I²C current sensors, opened on the first read (real mode only)
+    current_sensors: Arc<Mutex<Option<CurrentSensors>>>,
+    /// Simulated ignition input (simulation mode only)
+    sim_ignition: AtomicBool,
+    /// Simulated safety interlock input (simulation mode only)
+    sim_interlock: AtomicBool,
+    /// Safety interlock last read open, or not read yet; channels may not be switched on
+    interlock_open: AtomicBool,
+    adaptive_rate: Mutex<AdaptiveRate>,
+    /// Channel monitoring interval currently in use (ms)
+    monitoring_interval_ms: AtomicU64,
+    /// Wakes the monitoring loop back to the fast rate
+    activity: Notify,
+    /// Fault LEDs / buzzer (fixed at startup)
+    indicators: Mutex<IndicatorBank>,
+    /// Threshold alert rules, rebuilt on config reload
+    alerts: Mutex<AlertEngine>,
+    /// Simulator demo run
+    demo: Demo,
+    protocol_errors: Mutex<ProtocolErrors>,
+    read_errors: Mutex<ReadErrors>,
 }
 
 impl HardwareManager {
+    /// Create the hardware manager for startup
+    ///
+    /// Real mode first checks that a serial port or CAN interface is present;
+    /// without one it refuses to start or falls back to the simulator, per
+    /// `hardware.missing_transport`.
+    pub fn open(config: Config) -> Result<Self> {
+        if config.hardware.simulation_mode {
+            return Self::new(config);
+        }
+        
+        match detect_transport(&config.hardware) {
+            Ok(links) => {
+                info!("Hardware transport: {}", links.join(", "));
+                Self::new(config)
+            }
+            Err(problem) => match config.hardware.missing_transport {
+                MissingTransportAction::Refuse => Err(anyhow!(
+                    "hardware.simulation_mode is false but {}; connect the PDM, fix the config, or set \
+                     hardware.missing_transport = \"Simulate\"", problem
+                )),
+                MissingTransportAction::Simulate => {
+                    warn!("!!! REAL HARDWARE REQUESTED BUT {} - RUNNING THE SIMULATOR INSTEAD !!!",
+                          problem.to_uppercase());
+                    let mut manager = Self::with_mode(config, true)?;
+                    manager.mode_fallback = Some(problem);
+                    Ok(manager)
+                }
+            },
+        }
+    }
+    
+    /// Use these lifetime counters instead of ones covering only this manager
+    ///
+    /// Startup opens the persisted counters in main and hands them in here,
+    /// so managers created elsewhere never touch the counter file.
+    pub fn with_lifetime(mut self, lifetime: Arc<LifetimeCounters>) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+    
     /// Create a new hardware manager
     pub fn new(config: Config) -> Result<Self> {
         let simulation_mode = config.hardware.simulation_mode;
+        Self::with_mode(config, simulation_mode)
+    }
+    
+    fn with_mode(config: Config, simulation_mode: bool) -> Result<Self> {
         
         if simulation_mode {
             info!("Hardware manager initialized in SIMULATION mode");
@@ -26,194 +283,2081 @@ impl HardwareManager {
             // TODO: Initialize actual hardware connections here
         }
         
+        let unit = config.unit.resolve();
+        info!("Unit {} ({})", unit.id, unit.name);
+        
+        // The simulator is always "connected"
+        let connection_state = if simulation_mode {
+            ConnectionState::Connected
+        } else {
+            ConnectionState::Unknown
+        };
+        
         Ok(Self {
-            config,
             simulation_mode,
+            mode_fallback: None,
+            connection_state: Mutex::new(connection_state),
+            events: Arc::new(EventLog::with_unit(unit.id.clone())),
+            unit,
+            history: Mutex::new(HistoryBuffer::new(&config.history)),
+            sim_overrides: Mutex::new(HashMap::new()),
+            sim_failures: Mutex::new(FailureInjector::new(&config.simulation.failures)),
+            energy_tick: Mutex::new(None),
+            shed_step: Mutex::new(None),
+            battery: Mutex::new(SocEstimator::default()),
+            lifetime: Arc::new(LifetimeCounters::in_memory()),
+            voltage_window: Mutex::new(VecDeque::new()),
+            started: Instant::now(),
+            reported_status: Mutex::new(SystemStatus::Normal),
+            status_debounce: Mutex::new(StatusDebounce::default()),
+            current_sensors: Arc::new(Mutex::new(None)),
+            sim_ignition: AtomicBool::new(true),
+            sim_interlock: AtomicBool::new(true),
+            // Fail safe: an enabled interlock counts as open until it is first read
+            interlock_open: AtomicBool::new(config.interlock.enabled),
+            adaptive_rate: Mutex::new(AdaptiveRate::default()),
+            monitoring_interval_ms: AtomicU64::new(monitoring_intervals(&config).channels.as_millis() as u64),
+            activity: Notify::new(),
+            indicators: Mutex::new(IndicatorBank::new(&config.indicators, simulation_mode)?),
+            alerts: Mutex::new(AlertEngine::new(&config.alerts, config.hardware.channel_count)?),
+            demo: Demo::default(),
+            protocol_errors: Mutex::new(ProtocolErrors::default()),
+            read_errors: Mutex::new(ReadErrors::default()),
+            config: ArcSwap::from_pointee(config),
         })
     }
     
+    /// Get a snapshot of the current configuration
+    pub fn config(&self) -> Arc<Config> {
+        self.config.load_full()
+    }
+    
+    /// Replace the configuration at runtime
+    ///
+    /// The new config is swapped in as a single pointer, so readers see either
+    /// the old or the new config, never a mix. The channel loadout and the
+    /// simulation/channel-count hardware settings only apply at startup.
+    pub fn reload_config(&self, config: Config) -> Result<()> {
+        let current = self.config();
+        if config.hardware.simulation_mode != current.hardware.simulation_mode {
+            return Err(anyhow!("hardware.simulation_mode cannot be changed without a restart"));
+        }
+        if config.hardware.channel_count != current.hardware.channel_count {
+            return Err(anyhow!("hardware.channel_count cannot be changed without a restart"));
+        }
+        // Reuse the startup validation
+        PdmState::from_config(&config)?;
+        // Unchanged rules keep their raised alerts
+        let alerts = (config.alerts != current.alerts)
+            .then(|| AlertEngine::new(&config.alerts, config.hardware.channel_count))
+            .transpose()?;
+        
+        // Output precision is process-wide; startup sets it in main
+        crate::precision::apply(&config.precision);
+        if let Some(alerts) = alerts {
+            *self.alerts.lock().unwrap() = alerts;
+        }
+        self.config.store(Arc::new(config));
+        info!("Configuration reloaded");
+        Ok(())
+    }
+    
+    /// Identity of this unit
+    pub fn unit(&self) -> &UnitIdentity {
+        &self.unit
+    }
+    
+    /// Get the system event log
+    pub fn events(&self) -> &Arc<EventLog> {
+        &self.events
+    }
+    
+    /// Get the telemetry history buffer
+    pub fn history(&self) -> &Mutex<HistoryBuffer> {
+        &self.history
+    }
+    
+    /// Whether the manager is running the simulator
+    pub fn is_simulation(&self) -> bool {
+        self.simulation_mode
+    }
+    
+    /// Why real mode fell back to the simulator, if it did
+    pub fn mode_fallback(&self) -> Option<&str> {
+        self.mode_fallback.as_deref()
+    }
+    
+    /// Current hardware link state
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.connection_state.lock().unwrap()
+    }
+    
+    /// Read the hardware firmware version and compare it against the configured minimum
+    ///
+    /// The version and result are stored in the state for display. Depending
+    /// on `hardware.firmware_check`, firmware that is too old is either
+    /// logged or refuses startup.
+    pub async fn check_firmware_version(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let config = self.config();
+        let version = if self.simulation_mode {
+            SIMULATED_FIRMWARE_VERSION.to_string()
+        } else {
+            match self.read_real_firmware_version().await {
+                Ok(version) => version,
+                Err(e) => {
+                    warn!("Could not read hardware firmware version, skipping check: {}", e);
+                    return Ok(());
+                }
+            }
+        };
+        info!("Hardware firmware version {}", version);
+        
+        let compatible = match &config.hardware.min_firmware_version {
+            Some(minimum) => Some(firmware_at_least(&version, minimum)?),
+            None => None,
+        };
+        
+        {
+            let mut state = pdm_state.write().await;
+            state.firmware_version = Some(version.clone());
+            state.firmware_compatible = compatible;
+        }
+        
+        if compatible == Some(false) {
+            let minimum = config.hardware.min_firmware_version.as_deref().unwrap_or_default();
+            warn!("Hardware firmware {} is older than the minimum supported {}", version, minimum);
+            if config.hardware.firmware_check == CheckAction::Refuse {
+                return Err(anyhow!(
+                    "Hardware firmware {} is older than the minimum supported {}; refusing to start",
+                    version, minimum
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Compare configured channel limits against the hardware's ratings
+    ///
+    /// The ratings are stored on each channel for display. Depending on
+    /// `hardware.rating_check`, a limit above the rating is either logged or
+    /// refuses startup.
+    pub async fn startup_self_check(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let config = self.config();
+        self.check_firmware_version(pdm_state).await?;
+        
+        let ratings = if self.simulation_mode {
+            self.simulated_channel_ratings(pdm_state).await
+        } else {
+            match self.read_real_channel_ratings().await {
+                Ok(ratings) => ratings,
+                Err(e) => {
+                    warn!("Could not read hardware channel ratings, skipping self-check: {}", e);
+                    return Ok(());
+                }
+            }
+        };
+        
+        let violations = {
+            let mut state = pdm_state.write().await;
+            for (ch, rating) in ratings {
+                if let Some(channel) = state.channels.get_mut(&ch) {
+                    channel.hardware_rating = Some(rating);
+                }
+            }
+            state.limits_exceeding_rating()
+        };
+        
+        for (ch, limit, rating) in &violations {
+            warn!("Channel {} current limit {:.1}A exceeds hardware rating {:.1}A", ch, limit, rating);
+        }
+        
+        if !violations.is_empty() && config.hardware.rating_check == CheckAction::Refuse {
+            return Err(anyhow!(
+                "{} channel limit(s) exceed the hardware rating; refusing to start",
+                violations.len()
+            ));
+        }
+        
+        info!("Startup self-check complete ({} rating warning(s))", violations.len());
+        Ok(())
+    }
+    
+    /// Wait for the board to finish booting and confirm it answers
+    ///
+    /// Waits `startup_delay_ms` after the link is opened, then pings up to
+    /// `handshake_attempts` times. Without an answer the manager stays in
+    /// the degraded `Unknown` state and the monitoring loop keeps retrying.
+    /// Returns whether the board is ready.
+    pub async fn bring_up(&self) -> bool {
+        if self.simulation_mode {
+            return true;
+        }
+        
+        let hardware = &self.config().hardware;
+        if hardware.startup_delay_ms > 0 {
+            info!("Waiting {}ms for the PDM board to settle", hardware.startup_delay_ms);
+            tokio::time::sleep(Duration::from_millis(hardware.startup_delay_ms)).await;
+        }
+        
+        let attempts = hardware.handshake_attempts.max(1);
+        for attempt in 1..=attempts {
+            match self.handshake().await {
+                Ok(()) => return true,
+                Err(message) => warn!("PDM board handshake attempt {}/{} failed: {}", attempt, attempts, message),
+            }
+            if attempt < attempts {
+                tokio::time::sleep(Duration::from_millis(hardware.handshake_retry_ms)).await;
+            }
+        }
+        error!("PDM board did not answer {} handshake attempt(s) - running degraded until it does", attempts);
+        false
+    }
+    
+    /// Ping the board once, leaving the degraded state if it answers
+    async fn handshake(&self) -> std::result::Result<(), String> {
+        let (acknowledged, message) = ack_result(self.send_command(HardwareMessage::Ping).await);
+        if !acknowledged {
+            return Err(message);
+        }
+        info!("PDM board handshake OK: {}", message);
+        *self.connection_state.lock().unwrap() = ConnectionState::Connected;
+        Ok(())
+    }
+    
     /// Start the hardware monitoring loop
+    ///
+    /// Each cycle works from one config snapshot taken at its start. The
+    /// timers are rebuilt when a reload changes the intervals.
     pub async fn start_monitoring(&self, pdm_state: Arc<RwLock<PdmState>>) -> Result<()> {
-    info!("Starting hardware monitoring loop");
+        info!("Starting hardware monitoring loop");
         
-        let mut status_interval = interval(Duration::from_millis(
-            self.config.hardware.status_update_interval_ms
-        ));
-        
-        let mut monitoring_interval = interval(Duration::from_millis(
-            self.config.hardware.monitoring_interval_ms
-        ));
+        let mut intervals = monitoring_intervals(&self.config());
+        let mut status_interval = interval(intervals.system);
+        let mut monitoring_interval = interval(intervals.channels);
+        let mut temperature_interval = interval(intervals.temperature);
         
         loop {
             tokio::select! {
+                _ = temperature_interval.tick() => {
+                    let config = self.config();
+                    if let Err(e) = self.update_temperature(&config, &pdm_state).await {
+                        error!("Failed to update temperature: {}", e);
+                    }
+                }
                 _ = status_interval.tick() => {
-                    if let Err(e) = self.update_system_status(&pdm_state).await {
+                    let config = self.config();
+                    if let Err(e) = self.update_system_status(&config, &pdm_state).await {
                         error!("Failed to update system status: {}", e);
                     }
                 }
                 _ = monitoring_interval.tick() => {
-                    if let Err(e) = self.monitor_channels(&pdm_state).await {
+                    let config = self.config();
+                    if let Err(e) = self.monitor_channels(&config, &pdm_state).await {
                         error!("Failed to monitor channels: {}", e);
                     }
+                    
+                    let next = self.next_monitoring_interval(&config, &*pdm_state.read().await);
+                    if next != monitoring_interval.period() {
+                        debug!("Channel monitoring interval -> {}ms", next.as_millis());
+                        monitoring_interval = interval_at(tokio::time::Instant::now() + next, next);
+                    }
+                }
+                _ = self.activity.notified() => {
+                    // Ticks immediately, so the action is picked up right away
+                    monitoring_interval = interval(intervals.channels);
                 }
             }
+            
+            let configured = monitoring_intervals(&self.config());
+            if configured != intervals {
+                info!("Monitoring intervals changed to {}ms system / {}ms channels / {}ms temperature",
+                      configured.system.as_millis(), configured.channels.as_millis(),
+                      configured.temperature.as_millis());
+                intervals = configured;
+                status_interval = interval(intervals.system);
+                monitoring_interval = interval(intervals.channels);
+                temperature_interval = interval(intervals.temperature);
+            }
         }
     }
     
-    /// Update overall system status (voltage, temperature, etc.)
-    async fn update_system_status(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        if self.simulation_mode {
-            self.simulate_system_status(pdm_state).await
+    /// Pick the next channel monitoring interval from how steady the readings are
+    pub(crate) fn next_monitoring_interval(&self, config: &Config, state: &PdmState) -> Duration {
+        let fast = monitoring_intervals(config).channels;
+        let adaptive = &config.hardware.adaptive_rate;
+        
+        let mut rate = self.adaptive_rate.lock().unwrap();
+        let sample = RateSample::capture(state);
+        let steady = adaptive.enabled
+            && rate.reference.as_ref().is_some_and(|reference| reference.steady(&sample, adaptive));
+        if steady {
+            rate.steady_samples = rate.steady_samples.saturating_add(1);
         } else {
-            self.read_real_system_status(pdm_state).await
+            rate.reference = Some(sample);
+            rate.steady_samples = 0;
         }
+        
+        // Double the interval for every `settle_samples` steady samples
+        let steps = (rate.steady_samples / adaptive.settle_samples.max(1)).min(16);
+        let idle = Duration::from_millis(adaptive.idle_interval_ms).max(fast);
+        let next = fast.saturating_mul(1 << steps).min(idle);
+        self.monitoring_interval_ms.store(next.as_millis() as u64, Ordering::Relaxed);
+        next
     }
     
-    /// Monitor individual channel status
-    async fn monitor_channels(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        if self.simulation_mode {
-            self.simulate_channel_readings(pdm_state).await
-        } else {
-            self.read_real_channel_status(pdm_state).await
+    /// Return to the fast monitoring rate after a control action
+    pub fn note_activity(&self) {
+        {
+            let mut rate = self.adaptive_rate.lock().unwrap();
+            rate.reference = None;
+            rate.steady_samples = 0;
+        }
+        let fast = monitoring_intervals(&self.config()).channels.as_millis() as u64;
+        if self.monitoring_interval_ms.swap(fast, Ordering::Relaxed) > fast {
+            self.activity.notify_one();
         }
     }
     
-    /// Control a specific channel (turn on/off, set limits)
-    pub async fn control_channel(&self, channel: u8, enable: bool) -> Result<()> {
+    /// Frames dropped by `ingest_response`
+    pub fn protocol_errors(&self) -> ProtocolErrors {
+        self.protocol_errors.lock().unwrap().clone()
+    }
+    
+    /// Failed periodic status reads
+    pub fn read_errors(&self) -> ReadErrors {
+        self.read_errors.lock().unwrap().clone()
+    }
+    
+    /// Run a status read with the configured timeout and retries
+    ///
+    /// A read that fails every attempt is handled per
+    /// `hardware.read_failure_action`; only `Fault` returns the error. A
+    /// successful read clears the stale flag.
+    pub(crate) async fn read_with_retries<F, Fut>(
+        &self,
+        kind: ReadKind,
+        config: &HardwareConfig,
+        pdm_state: &Arc<RwLock<PdmState>>,
+        mut read: F,
+    ) -> Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let timeout = Duration::from_millis(config.read_timeout_ms.max(1));
+        let mut last_error = None;
+        for attempt in 0..=config.read_retries {
+            let error = match tokio::time::timeout(timeout, read()).await {
+                Ok(Ok(())) => {
+                    if attempt > 0 {
+                        self.read_errors.lock().unwrap().recovered += 1;
+                        debug!("{:?} status read succeeded on attempt {}", kind, attempt + 1);
+                    }
+                    pdm_state.write().await.stale_readings.remove(&kind);
+                    return Ok(());
+                }
+                Ok(Err(e)) => {
+                    self.read_errors.lock().unwrap().errors += 1;
+                    e
+                }
+                Err(_) => {
+                    self.read_errors.lock().unwrap().timeouts += 1;
+                    anyhow!("{:?} status read timed out after {}ms", kind, config.read_timeout_ms)
+                }
+            };
+            debug!("{:?} status read attempt {} failed: {}", kind, attempt + 1, error);
+            last_error = Some(error);
+        }
+        
+        let error = last_error.expect("at least one attempt is made");
+        {
+            let mut errors = self.read_errors.lock().unwrap();
+            *errors.failed.entry(kind).or_default() += 1;
+            errors.last_error = Some(error.to_string());
+            errors.last_error_at = Some(chrono::Utc::now());
+        }
+        let attempts = config.read_retries + 1;
+        match config.read_failure_action {
+            ReadFailureAction::KeepLast => {
+                warn!("{:?} status read failed {} times ({}) - keeping the last readings", kind, attempts, error);
+                Ok(())
+            }
+            ReadFailureAction::MarkStale => {
+                warn!("{:?} status read failed {} times ({}) - readings marked stale", kind, attempts, error);
+                pdm_state.write().await.stale_readings.insert(kind);
+                Ok(())
+            }
+            ReadFailureAction::Fault => {
+                error!("{:?} status read failed {} times ({})", kind, attempts, error);
+                let mut state = pdm_state.write().await;
+                state.stale_readings.insert(kind);
+                state.system_status = SystemStatus::Fault;
+                Err(error)
+            }
+        }
+    }
+    
+    /// Counters kept across restarts
+    pub fn lifetime(&self) -> &Arc<LifetimeCounters> {
+        &self.lifetime
+    }
+    
+    /// The simulator demo
+    pub fn demo(&self) -> &Demo {
+        &self.demo
+    }
+    
+    /// Alerts currently raised by the alert rules
+    pub fn alerts(&self) -> Vec<ActiveAlert> {
+        self.alerts.lock().unwrap().active()
+    }
+    
+    /// Current state of the fault indicators
+    pub fn indicators(&self) -> IndicatorReport {
+        self.indicators.lock().unwrap().report()
+    }
+    
+    /// Channel monitoring rate currently in use
+    pub fn monitoring_rate(&self) -> MonitoringRate {
+        let config = self.config();
+        let interval_ms = self.monitoring_interval_ms.load(Ordering::Relaxed).max(1);
+        let fast_interval_ms = config.hardware.monitoring_interval_ms.max(1);
+        MonitoringRate {
+            adaptive: config.hardware.adaptive_rate.enabled,
+            interval_ms,
+            rate_hz: 1000.0 / interval_ms as f64,
+            fast_interval_ms,
+            idle_interval_ms: config.hardware.adaptive_rate.idle_interval_ms.max(fast_interval_ms),
+        }
+    }
+    
+    /// Update overall system status (voltage, temperature, etc.)
+    pub(crate) async fn update_system_status(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let previous = pdm_state.read().await.system_status.clone();
         if self.simulation_mode {
-            info!("[SIM] Channel {} -> {}", channel, if enable { "ON" } else { "OFF" });
-            // In simulation, just log the action
-            Ok(())
+            self.simulate_system_status(config, pdm_state).await?;
         } else {
-            self.send_real_channel_command(channel, enable).await
+            self.read_with_retries(ReadKind::System, &config.hardware, pdm_state,
+                                   || self.read_real_system_status(pdm_state)).await?;
+        }
+        
+        {
+            let mut state = pdm_state.write().await;
+            self.update_input_ripple(config, &mut state);
+            state.update_efficiency();
+        }
+        self.update_energy(config, pdm_state, Instant::now()).await;
+        self.enforce_safety_limits(config, pdm_state).await?;
+        self.check_interlock(config, pdm_state).await;
+        self.check_heartbeat(config, pdm_state).await;
+        self.check_ignition(config, pdm_state).await;
+        self.update_thermal_shedding(config, pdm_state, Instant::now()).await;
+        self.update_battery_shedding(config, pdm_state).await;
+        {
+            let mut state = pdm_state.write().await;
+            let evaluated = state.system_status.clone();
+            state.system_status = self.status_debounce.lock().unwrap()
+                .apply(previous, evaluated, config.safety.status_downgrade_cycles);
         }
+        self.report_status_change(pdm_state).await;
+        Ok(())
     }
     
-    /// Emergency shutdown all channels
-    pub async fn emergency_shutdown(&self) -> Result<()> {
+    /// Take a fresh PDM temperature reading
+    ///
+    /// Runs on its own, usually slower, timer; the system status checks use
+    /// the latest reading.
+    pub(crate) async fn update_temperature(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
         if self.simulation_mode {
-            warn!("[SIM] EMERGENCY SHUTDOWN - All channels OFF");
+            self.simulate_temperature(pdm_state).await;
             Ok(())
         } else {
-            self.send_real_emergency_shutdown().await
+            self.read_with_retries(ReadKind::Temperature, &config.hardware, pdm_state,
+                                   || self.read_real_temperature(pdm_state)).await
         }
     }
     
-    // ===== SIMULATION MODE FUNCTIONS =====
+    /// Emit an event when the system status moves to a different severity
+    pub(crate) async fn report_status_change(&self, pdm_state: &Arc<RwLock<PdmState>>) {
+        let current = pdm_state.read().await.system_status.clone();
+        let previous = std::mem::replace(&mut *self.reported_status.lock().unwrap(), current.clone());
+        
+        if previous.severity() != current.severity() {
+            self.events.emit(EventKind::SystemStatusChanged, None,
+                             format!("System status {:?} -> {:?}", previous, current));
+        }
+    }
     
-    /// Simulate system status updates for development
-    async fn simulate_system_status(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        let mut state = pdm_state.write().await;
+    /// Track input voltage ripple over a short window and flag excessive ripple
+    pub(crate) fn update_input_ripple(&self, config: &Config, state: &mut PdmState) {
+        let window_size = config.safety.ripple_window_samples.max(1);
+        let (ripple_pp, ripple_std) = {
+            let mut window = self.voltage_window.lock().unwrap();
+            while window.len() >= window_size {
+                window.pop_front();
+            }
+            window.push_back(state.input_voltage);
+            ripple_stats(window.make_contiguous())
+        };
         
-        // Simulate realistic voltage fluctuations
-        state.input_voltage = 13.8 + (rand::random::<f32>() - 0.5) * 0.4;
+        state.input_ripple_pp = ripple_pp;
+        state.input_ripple_std = ripple_std;
         
-        // Calculate total current from active channels
-        let total_current: f32 = state.channels.values()
-            .filter(|ch| ch.status == ChannelStatus::On)
-            .map(|ch| ch.current)
-            .sum();
+        // Excessive ripple (failing alternator/regulator) is a warning, never a fault
+        if ripple_pp > config.safety.max_input_ripple
+            && state.system_status.severity() < SystemStatus::Warning.severity()
+        {
+            debug!("Input ripple {:.2}V exceeds {:.2}V", ripple_pp, config.safety.max_input_ripple);
+            state.system_status = SystemStatus::Warning;
+        }
+    }
+    
+    /// Emergency shutdown on total overcurrent
+    ///
+    /// If the violation persists after shutdown (e.g. a stuck output), the
+    /// shutdown is re-issued every cycle and the Emergency status stays latched.
+    /// Returns whether a shutdown was issued this cycle.
+    pub(crate) async fn enforce_safety_limits(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<bool> {
+        let max_total_current = config.safety.max_total_current;
         
-        state.total_current = total_current + (rand::random::<f32>() - 0.5) * 0.5;
+        let violation = {
+            let mut state = pdm_state.write().await;
+            let violation = state.total_current > max_total_current;
+            
+            if violation {
+                if state.emergency_latched {
+                    error!("FAIL-SAFE: total current {:.1}A still exceeds {:.1}A after emergency shutdown - possible stuck output, re-issuing shutdown",
+                           state.total_current, max_total_current);
+                } else {
+                    error!("Total current {:.1}A exceeds {:.1}A - EMERGENCY SHUTDOWN",
+                           state.total_current, max_total_current);
+                    self.events.emit(EventKind::EmergencyShutdown, None,
+                                     format!("Total current {:.1}A exceeded {:.1}A", state.total_current, max_total_current));
+                }
+                state.emergency_shutdown();
+                state.emergency_latched = true;
+            }
+            
+            if state.emergency_latched {
+                state.system_status = SystemStatus::Emergency;
+            }
+            violation
+        };
         
-        // Simulate temperature based on load
-        let base_temp = 25.0;
-        let load_factor = total_current / 50.0; // Heat up with load
-        state.temperature = base_temp + (load_factor * 15.0) + (rand::random::<f32>() * 2.0);
+        if violation {
+            let report = self.emergency_shutdown().await;
+            if !report.confirmed_all_off {
+                pdm_state.write().await.mark_shutdown_unconfirmed(&report.unconfirmed_channels);
+            }
+        }
+        Ok(violation)
+    }
+    
+    /// Shut everything down if the controlling client stopped sending heartbeats
+    ///
+    /// The switch disarms after tripping and re-arms on the next heartbeat.
+    /// Returns whether a shutdown was issued.
+    pub(crate) async fn check_heartbeat(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> bool {
+        let heartbeat = &config.heartbeat;
+        if !heartbeat.enabled {
+            return false;
+        }
         
-        // Update system status based on conditions
-        state.system_status = if state.input_voltage < self.config.safety.min_input_voltage ||
-                                state.input_voltage > self.config.safety.max_input_voltage ||
-                                state.temperature > self.config.safety.max_temperature {
-            SystemStatus::Fault
-        } else if state.total_current > self.config.safety.max_total_current * 0.8 ||
-                  state.temperature > self.config.safety.max_temperature * 0.8 {
-            SystemStatus::Warning
+        let timeout = Duration::from_millis(heartbeat.timeout_ms);
+        {
+            let mut state = pdm_state.write().await;
+            let Some(last) = state.last_heartbeat else {
+                state.heartbeat_remaining_ms = None;
+                return false;
+            };
+            
+            let elapsed = last.elapsed();
+            if elapsed < timeout {
+                state.heartbeat_remaining_ms = Some((timeout - elapsed).as_millis() as u64);
+                return false;
+            }
+            
+            error!("Heartbeat lost: no heartbeat for {}ms - EMERGENCY SHUTDOWN", elapsed.as_millis());
+            state.last_heartbeat = None;
+            state.heartbeat_remaining_ms = None;
+            state.emergency_shutdown();
+            state.emergency_latched = true;
+            state.system_status = SystemStatus::Emergency;
+        }
+        
+        self.events.emit(EventKind::HeartbeatLost, None,
+                         format!("No client heartbeat within {}ms, emergency shutdown", heartbeat.timeout_ms));
+        
+        let report = self.emergency_shutdown().await;
+        if !report.confirmed_all_off {
+            pdm_state.write().await.mark_shutdown_unconfirmed(&report.unconfirmed_channels);
+        }
+        true
+    }
+    
+    /// Track the ignition input and run the switch-off sequence once it has been off long enough
+    pub(crate) async fn check_ignition(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) {
+        let ignition = &config.ignition;
+        if !ignition.enabled {
+            return;
+        }
+        
+        let on = if self.simulation_mode {
+            self.sim_ignition.load(Ordering::Relaxed)
         } else {
-            SystemStatus::Normal
+            match self.read_real_ignition(config).await {
+                Ok(on) => on,
+                Err(e) => {
+                    warn!("Failed to read ignition input: {}", e);
+                    return;
+                }
+            }
         };
         
-        debug!("System status updated: V={:.1}V, I={:.1}A, T={:.1}°C", 
-               state.input_voltage, state.total_current, state.temperature);
+        let to_disable: Vec<u8> = {
+            let mut state = pdm_state.write().await;
+            if state.ignition_on != Some(on) {
+                info!("Ignition {}", if on { "ON" } else { "OFF" });
+                state.ignition_off_at = (!on).then(Instant::now);
+            }
+            state.ignition_on = Some(on);
+            
+            let due = state.ignition_off_at
+                .is_some_and(|at| at.elapsed() >= Duration::from_millis(ignition.off_delay_ms));
+            if !due {
+                return;
+            }
+            state.ignition_off_at = None;
+            if !ignition.sequence_off {
+                return;
+            }
+            
+            let mut channels: Vec<u8> = state.channels.values()
+                .filter(|channel| !channel.critical && !channel.overridden && channel.status == ChannelStatus::On)
+                .map(|channel| channel.ch)
+                .collect();
+            channels.sort_unstable();
+            channels
+        };
         
-        Ok(())
+        self.switch_off_in_order(pdm_state, to_disable, TransitionTrigger::Ignition, "ignition off").await;
     }
     
-    /// Simulate channel readings
-    async fn simulate_channel_readings(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        let mut state = pdm_state.write().await;
+    /// Track the safety interlock input and hold every channel off while it is open
+    ///
+    /// Closing the interlock restores nothing: channels stay off until they
+    /// are switched on again.
+    pub async fn check_interlock(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) {
+        if !config.interlock.enabled {
+            self.interlock_open.store(false, Ordering::Relaxed);
+            if pdm_state.read().await.interlock_closed.is_some() {
+                pdm_state.write().await.interlock_closed = None;
+            }
+            return;
+        }
         
-        for channel in state.channels.values_mut() {
-            match channel.status {
-                ChannelStatus::On => {
-                    // Simulate realistic voltage and current for ON channels
-                    channel.voltage = state.input_voltage - (rand::random::<f32>() * 0.2);
-                    
-                    // Simulate current based on channel type
-                    let base_current = match channel.name.as_str() {
-                        "FUEL PUMP" => 4.2,
-                        "IGNITION" => 2.1,
-                        "COOLING FAN" => 8.5,
-                        "HEADLIGHTS" => 6.8,
-                        "ECU MAIN" => 1.5,
-                        _ => 0.5, // Spare channels
-                    };
-                    
-                    channel.current = base_current + (rand::random::<f32>() - 0.5) * 0.5;
-                }
-                ChannelStatus::Off => {
-                    channel.voltage = 0.0;
-                    channel.current = 0.0;
+        let closed = if self.simulation_mode {
+            self.sim_interlock.load(Ordering::Relaxed)
+        } else {
+            match self.read_real_interlock(config).await {
+                Ok(closed) => closed,
+                Err(e) => {
+                    // Fail safe: an input that can't be read is treated as open
+                    warn!("Failed to read safety interlock input: {} - treating it as open", e);
+                    false
                 }
-                ChannelStatus::Fault => {
-                    channel.voltage = 0.0;
-                    channel.current = 0.0;
+            }
+        };
+        self.interlock_open.store(!closed, Ordering::Relaxed);
+        
+        {
+            let mut state = pdm_state.write().await;
+            let changed = state.interlock_closed != Some(closed);
+            state.interlock_closed = Some(closed);
+            if closed {
+                if changed {
+                    info!("Safety interlock CLOSED - outputs may be switched on again");
+                    self.events.emit(EventKind::InterlockClosed, None, "Safety interlock closed".to_string());
                 }
+                return;
             }
+            
+            state.system_status = SystemStatus::Emergency;
+            let any_on = state.channels.values().any(|channel| channel.status == ChannelStatus::On);
+            if !changed && !any_on {
+                return;
+            }
+            if changed {
+                error!("Safety interlock OPEN - all channels OFF");
+                self.events.emit(EventKind::InterlockOpened, None,
+                                 "Safety interlock opened, all channels switched off".to_string());
+            }
+            state.interlock_shutdown();
         }
         
-        Ok(())
+        let report = self.emergency_shutdown().await;
+        if !report.confirmed_all_off {
+            pdm_state.write().await.mark_shutdown_unconfirmed(&report.unconfirmed_channels);
+        }
     }
     
-    // ===== REAL HARDWARE FUNCTIONS =====
+    /// Whether the safety interlock is enabled and was last read open or has not been read yet
+    pub fn interlock_open(&self) -> bool {
+        self.interlock_open.load(Ordering::Relaxed)
+    }
     
-    /// Read actual system status from hardware
-    async fn read_real_system_status(&self, _pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        // TODO: Implement actual hardware communication
-        // This would involve:
-        // 1. Sending status request over USB/CAN
-        // 2. Parsing hardware response
-        // 3. Updating PDM state with real readings
+    /// Shed or restore one channel per step as the PDM temperature crosses the shedding thresholds
+    ///
+    /// Between the recovery and shed temperatures nothing changes, and a shed
+    /// channel stays off for at least `min_shed_ms`, so a load hovering at the
+    /// threshold isn't switched on and off every cycle.
+    pub(crate) async fn update_thermal_shedding(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>, now: Instant) {
+        let shedding = &config.safety.thermal_shedding;
+        if !shedding.enabled {
+            return;
+        }
         
-        warn!("Real hardware communication not yet implemented");
+        let (ch, shed, temperature) = {
+            let mut state = pdm_state.write().await;
+            // Whatever else switched a shed channel on (or faulted it) takes it out of shedding
+            for channel in state.channels.values_mut().filter(|channel| channel.shed && channel.status != ChannelStatus::Off) {
+                channel.shed = false;
+                channel.shed_at = None;
+            }
+            
+            let last_step = *self.shed_step.lock().unwrap();
+            if last_step.is_some_and(|last| now.saturating_duration_since(last) < Duration::from_millis(shedding.step_ms)) {
+                return;
+            }
+            
+            let temperature = state.temperature;
+            let next = if temperature > shedding.shed_temperature {
+                shedding.channels.iter().copied()
+                    .find(|ch| state.channels.get(ch).is_some_and(|channel| {
+                        channel.status == ChannelStatus::On && !channel.critical && !channel.overridden
+                    }))
+                    .map(|ch| (ch, true))
+            } else if temperature <= shedding.recovery_temperature {
+                let min_shed = Duration::from_millis(shedding.min_shed_ms);
+                shedding.channels.iter().rev().copied()
+                    .find(|ch| state.channels.get(ch).is_some_and(|channel| {
+                        channel.shed && channel.shed_at.is_none_or(|at| now.saturating_duration_since(at) >= min_shed)
+                    }))
+                    // Restoring is a switch-on like any other; a blocked channel waits its turn
+                    .filter(|&ch| state.check_switch_on(&[ch], &config.safety).is_ok())
+                    .map(|ch| (ch, false))
+            } else {
+                None
+            };
+            let Some((ch, shed)) = next else { return };
+            (ch, shed, temperature)
+        };
+        
+        *self.shed_step.lock().unwrap() = Some(now);
+        if shed {
+            self.ramp_down(pdm_state, &[ch]).await;
+        }
+        if let Err(e) = self.control_channel(ch, !shed).await {
+            warn!("Thermal shedding failed to switch channel {} {}: {}", ch, if shed { "OFF" } else { "ON" }, e);
+            return;
+        }
+        
+        let mut state = pdm_state.write().await;
+        let Some(channel) = state.channels.get_mut(&ch) else { return };
+        let message = if shed {
+            channel.set_status(ChannelStatus::Off, TransitionTrigger::LoadShedding);
+            channel.ramp_duty = None;
+            channel.switched_on_at = None;
+            channel.shed = true;
+            channel.shed_at = Some(now);
+            format!("Channel {} shed at {:.1}°C (above {:.1}°C)", ch, temperature, shedding.shed_temperature)
+        } else {
+            channel.set_status(ChannelStatus::On, TransitionTrigger::LoadShedding);
+            channel.switched_on_at = Some(Instant::now());
+            channel.shed = false;
+            channel.shed_at = None;
+            format!("Channel {} restored at {:.1}°C (recovery {:.1}°C)", ch, temperature, shedding.recovery_temperature)
+        };
+        channel.last_update = chrono::Utc::now();
+        warn!("{}", message);
+        self.events.emit(EventKind::LoadShedding, Some(ch), message);
+    }
+    
+    /// Shed and restore channels by the battery state of charge
+    ///
+    /// Below a stage's `below_soc` its ON channels are switched off in order;
+    /// they come back once the charge is `recovery_margin` above it again and
+    /// the shared switch-on gate lets them.
+    pub(crate) async fn update_battery_shedding(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) {
+        let battery = &config.battery;
+        if !battery.enabled {
+            return;
+        }
+        
+        let (soc, to_shed, to_restore) = {
+            let mut state = pdm_state.write().await;
+            // Whatever else switched a shed channel on (or faulted it) takes it out of shedding
+            for channel in state.channels.values_mut().filter(|channel| channel.battery_shed && channel.status != ChannelStatus::Off) {
+                channel.battery_shed = false;
+            }
+            let Some(soc) = state.battery_soc_percent else { return };
+            
+            let mut to_shed = Vec::new();
+            for stage in battery.shedding.iter().filter(|stage| soc < stage.below_soc) {
+                for &ch in &stage.channels {
+                    let sheddable = state.channels.get(&ch).is_some_and(|channel| {
+                        channel.status == ChannelStatus::On && !channel.critical && !channel.overridden
+                    });
+                    if sheddable && !to_shed.contains(&ch) {
+                        to_shed.push(ch);
+                    }
+                }
+            }
+            
+            // A channel listed in several stages comes back once all of them have recovered
+            let holding = |ch: u8| battery.shedding.iter()
+                .any(|stage| soc < stage.below_soc + battery.recovery_margin && stage.channels.contains(&ch));
+            let mut to_restore: Vec<u8> = state.channels.values()
+                .filter(|channel| channel.battery_shed && !holding(channel.ch))
+                .map(|channel| channel.ch)
+                .collect();
+            to_restore.sort_unstable();
+            (soc, to_shed, to_restore)
+        };
+        
+        if !to_shed.is_empty() {
+            let message = format!("Battery at {:.0}% - shedding channels {:?}", soc, to_shed);
+            warn!("{}", message);
+            self.events.emit(EventKind::LoadShedding, None, message);
+            self.switch_off_in_order(pdm_state, to_shed.clone(), TransitionTrigger::LoadShedding, "battery load shedding").await;
+            let mut state = pdm_state.write().await;
+            for channel in state.channels.values_mut().filter(|channel| to_shed.contains(&channel.ch)) {
+                channel.battery_shed = channel.status == ChannelStatus::Off;
+            }
+        }
+        
+        for ch in to_restore {
+            // Checked one at a time, as each restore adds to the power and active channels
+            if let Err(block) = pdm_state.read().await.check_switch_on(&[ch], &config.safety) {
+                debug!("Battery load shedding holds channel {} off: {}", ch, block);
+                continue;
+            }
+            if let Err(e) = self.control_channel(ch, true).await {
+                warn!("Battery load shedding failed to restore channel {}: {}", ch, e);
+                continue;
+            }
+            let mut state = pdm_state.write().await;
+            let Some(channel) = state.channels.get_mut(&ch) else { continue };
+            channel.set_status(ChannelStatus::On, TransitionTrigger::LoadShedding);
+            channel.switched_on_at = Some(Instant::now());
+            channel.battery_shed = false;
+            channel.last_update = chrono::Utc::now();
+            let message = format!("Channel {} restored with the battery at {:.0}%", ch, soc);
+            info!("{}", message);
+            self.events.emit(EventKind::LoadShedding, Some(ch), message);
+        }
+    }
+    
+    /// Switch channels off one at a time, so loads drop in a predictable order
+    async fn switch_off_in_order(&self, pdm_state: &Arc<RwLock<PdmState>>, channels: Vec<u8>,
+                                 trigger: TransitionTrigger, reason: &str) {
+        for ch in channels {
+            self.ramp_down(pdm_state, &[ch]).await;
+            if let Err(e) = self.control_channel(ch, false).await {
+                warn!("Failed to switch off channel {} after {}: {}", ch, reason, e);
+                continue;
+            }
+            
+            let mut state = pdm_state.write().await;
+            if let Some(channel) = state.channels.get_mut(&ch) {
+                channel.set_status(ChannelStatus::Off, trigger);
+                channel.ramp_duty = None;
+                channel.switched_on_at = None;
+                channel.last_update = chrono::Utc::now();
+                info!("Channel {} switched OFF after {}", ch, reason);
+            }
+        }
+    }
+    
+    /// Integrate the system power into the energy counter and enforce the budget
+    ///
+    /// Non-critical channels are switched off in the power-off order once the
+    /// budget is used up.
+    pub(crate) async fn update_energy(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>, now: Instant) {
+        let elapsed = {
+            let mut tick = self.energy_tick.lock().unwrap();
+            let max_step = Duration::from_millis(config.hardware.max_time_step_ms);
+            let elapsed = tick.map(|last| time_step(last, now, max_step).as_secs_f64());
+            if tick.is_none_or(|last| now > last) {
+                *tick = Some(now);
+            }
+            elapsed
+        };
+        
+        let (to_disable, low, depleted, consumed) = {
+            let mut state = pdm_state.write().await;
+            let power = state.total_power();
+            state.energy.accumulate(power, elapsed.unwrap_or(0.0));
+            self.lifetime.accumulate(&state, elapsed.unwrap_or(0.0));
+            // An input voltage that could not be read would throw the estimate off
+            state.battery_soc_percent = if !config.battery.enabled {
+                None
+            } else if state.stale_readings.contains(&ReadKind::System) {
+                state.battery_soc_percent
+            } else {
+                Some(self.battery.lock().unwrap().update(&config.battery, state.input_voltage, state.input_current,
+                                                         elapsed.unwrap_or(0.0)))
+            };
+            let (low, depleted) = state.energy.check_budget(&config.energy);
+            
+            let to_disable: Vec<u8> = if depleted {
+                state.power_off_order(&config.sequence.power_off_order).into_iter()
+                    .filter(|ch| state.channels.get(ch).is_some_and(|channel| {
+                        !channel.critical && !channel.overridden && channel.status == ChannelStatus::On
+                    }))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            (to_disable, low, depleted, state.energy.consumed_wh)
+        };
+        
+        if low && !depleted {
+            let message = format!("Energy budget running low: {:.1}Wh used", consumed);
+            warn!("{}", message);
+            self.events.emit(EventKind::EnergyBudget, None, message);
+        }
+        if depleted {
+            let message = format!("Energy budget used up ({:.1}Wh) - switching off non-critical channels {:?}",
+                                  consumed, to_disable);
+            error!("{}", message);
+            self.events.emit(EventKind::EnergyBudget, None, message);
+            self.switch_off_in_order(pdm_state, to_disable, TransitionTrigger::EnergyBudget, "energy budget depleted").await;
+        }
+    }
+    
+    /// Set the simulated ignition input
+    pub fn set_sim_ignition(&self, on: bool) {
+        info!("[SIM] Ignition set {}", if on { "ON" } else { "OFF" });
+        self.sim_ignition.store(on, Ordering::Relaxed);
+    }
+    
+    /// Set the simulated safety interlock input
+    pub fn set_sim_interlock(&self, closed: bool) {
+        info!("[SIM] Safety interlock set {}", if closed { "CLOSED" } else { "OPEN" });
+        self.sim_interlock.store(closed, Ordering::Relaxed);
+    }
+    
+    /// Read the safety interlock input from its sysfs GPIO
+    async fn read_real_interlock(&self, config: &Config) -> Result<bool> {
+        let interlock = &config.interlock;
+        let Some(pin) = interlock.gpio_pin else {
+            return Err(anyhow!("interlock.gpio_pin is not set"));
+        };
+        
+        let value = tokio::fs::read_to_string(format!("/sys/class/gpio/gpio{}/value", pin)).await?;
+        let high = value.trim() == "1";
+        Ok(high != interlock.active_low)
+    }
+    
+    /// Read the ignition input from its sysfs GPIO
+    async fn read_real_ignition(&self, config: &Config) -> Result<bool> {
+        let ignition = &config.ignition;
+        let Some(pin) = ignition.gpio_pin else {
+            return Err(anyhow!("ignition.gpio_pin is not set"));
+        };
+        
+        let value = tokio::fs::read_to_string(format!("/sys/class/gpio/gpio{}/value", pin)).await?;
+        let high = value.trim() == "1";
+        Ok(high != ignition.active_low)
+    }
+    
+    /// Monitor individual channel status
+    pub(crate) async fn monitor_channels(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        if self.simulation_mode {
+            self.simulate_channel_readings(config, pdm_state).await?;
+        } else {
+            // Readings from a board that has not answered a handshake are not trusted
+            if self.connection_state() == ConnectionState::Unknown {
+                if let Err(message) = self.handshake().await {
+                    debug!("PDM board still not answering: {}", message);
+                    return Ok(());
+                }
+            }
+            let result = self.read_with_retries(ReadKind::Channels, &config.hardware, pdm_state,
+                                                || self.read_real_channel_status(pdm_state)).await;
+            self.update_connection_state(result.is_ok(), pdm_state).await;
+            result?;
+        }
+        
+        self.process_channel_faults(config, pdm_state).await?;
+        self.regulate_current_limits(pdm_state).await;
+        
+        let mut state = pdm_state.write().await;
+        state.update_current_warnings(config.safety.current_warning_fraction);
+        
+        for ch in state.update_current_anomalies(&config.safety.anomaly) {
+            if let Some(channel) = state.channels.get(&ch) {
+                warn!("Channel {} current anomaly: {:.2}A vs {:.2}A average",
+                      ch, channel.current, channel.average_current);
+            }
+        }
+        
+        for ch in state.update_expected_ranges(Instant::now()) {
+            let Some(channel) = state.channels.get(&ch) else { continue };
+            let Some(range) = channel.expected_current else { continue };
+            let concern = if channel.current < range.min { "possible disconnection" } else { "possible developing short" };
+            let message = format!("Channel {} ({}) drawing {:.2}A outside its expected {:.2}-{:.2}A - {}",
+                                  ch, channel.name, channel.current, range.min, range.max, concern);
+            warn!("{}", message);
+            self.events.emit(EventKind::MaintenanceConcern, Some(ch), message);
+        }
+        
+        let switch_rate = &config.safety.switch_rate;
+        for ch in state.update_switch_rates(switch_rate, Instant::now()) {
+            let Some(channel) = state.channels.get(&ch) else { continue };
+            let message = format!("Channel {} ({}) switched {} times in {}s, more than the {} allowed - check for a control bug or flapping automation",
+                                  ch, channel.name, channel.switch_times.len(), switch_rate.window_ms / 1000, switch_rate.max_switches);
+            warn!("{}", message);
+            self.events.emit(EventKind::SwitchRate, Some(ch), message);
+        }
+        
+        state.update_display_currents();
+        state.update_learning(Instant::now());
+        {
+            let now = Instant::now();
+            let mut history = self.history.lock().unwrap();
+            history.record(&state, now);
+            history.update_trends(&mut state, &config.history, config.safety.max_total_current, now);
+        }
+        self.indicators.lock().unwrap().update(&state);
+        self.alerts.lock().unwrap().evaluate(&state, Instant::now(), &self.events);
         Ok(())
     }
     
-    /// Read actual channel status from hardware
-    async fn read_real_channel_status(&self, _pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
-        // TODO: Implement actual hardware communication
-        warn!("Real hardware communication not yet implemented");
+    /// Control a specific channel (turn on/off, set limits)
+    pub async fn control_channel(&self, channel: u8, enable: bool) -> Result<()> {
+        self.note_activity();
+        if enable && self.interlock_open() {
+            return Err(anyhow!("Safety interlock is open; channel {} stays OFF", channel));
+        }
+        if self.simulation_mode {
+            // Mimic the round trip of a real serial/CAN command
+            let config = self.config();
+            let sim = &config.simulation;
+            if sim.control_latency_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(sim.control_latency_ms)).await;
+            }
+            if rand::random::<f32>() < sim.control_failure_probability {
+                warn!("[SIM] Channel {} command failed (simulated)", channel);
+                return Err(anyhow!("Simulated command failure for channel {}", channel));
+            }
+            
+            info!("[SIM] Channel {} -> {}", channel, if enable { "ON" } else { "OFF" });
+            Ok(())
+        } else {
+            self.send_real_channel_command(channel, enable).await
+        }
+    }
+    
+    /// Emergency shutdown all channels
+    ///
+    /// If the broadcast shutdown fails, each channel is switched off
+    /// individually. Channels that still can't be confirmed OFF are listed in
+    /// the report rather than assumed off.
+    pub async fn emergency_shutdown(&self) -> ShutdownReport {
+        let config = self.config();
+        if self.simulation_mode {
+            warn!("[SIM] EMERGENCY SHUTDOWN - All channels OFF");
+            return ShutdownReport {
+                confirmed_all_off: true,
+                broadcast_acknowledged: true,
+                unconfirmed_channels: Vec::new(),
+            };
+        }
+        
+        if let Err(e) = self.send_real_emergency_shutdown().await {
+            error!("Broadcast emergency shutdown failed: {} - switching channels off individually", e);
+        } else {
+            return ShutdownReport {
+                confirmed_all_off: true,
+                broadcast_acknowledged: true,
+                unconfirmed_channels: Vec::new(),
+            };
+        }
+        
+        let mut unconfirmed_channels = Vec::new();
+        for ch in 1..=config.hardware.channel_count {
+            if let Err(e) = self.send_real_channel_command(ch, false).await {
+                error!("Channel {} could not be confirmed OFF: {}", ch, e);
+                unconfirmed_channels.push(ch);
+            }
+        }
+        
+        ShutdownReport {
+            confirmed_all_off: unconfirmed_channels.is_empty(),
+            broadcast_acknowledged: false,
+            unconfirmed_channels,
+        }
+    }
+    
+    /// Switch off the channels of one zone, confirming each individually
+    pub async fn zone_shutdown(&self, channels: &[u8]) -> ShutdownReport {
+        if self.simulation_mode {
+            warn!("[SIM] ZONE SHUTDOWN - channels {:?} OFF", channels);
+            return ShutdownReport {
+                confirmed_all_off: true,
+                broadcast_acknowledged: false,
+                unconfirmed_channels: Vec::new(),
+            };
+        }
+        
+        let mut unconfirmed_channels = Vec::new();
+        for &ch in channels {
+            if let Err(e) = self.send_real_channel_command(ch, false).await {
+                error!("Channel {} could not be confirmed OFF: {}", ch, e);
+                unconfirmed_channels.push(ch);
+            }
+        }
+        
+        ShutdownReport {
+            confirmed_all_off: unconfirmed_channels.is_empty(),
+            broadcast_acknowledged: false,
+            unconfirmed_channels,
+        }
+    }
+    
+    /// Track hardware link transitions and re-sync outputs after a reconnect
+    pub(crate) async fn update_connection_state(&self, connected: bool, pdm_state: &Arc<RwLock<PdmState>>) {
+        let new_state = if connected { ConnectionState::Connected } else { ConnectionState::Disconnected };
+        let previous = std::mem::replace(&mut *self.connection_state.lock().unwrap(), new_state);
+        
+        match (previous, new_state) {
+            (ConnectionState::Connected, ConnectionState::Disconnected) => {
+                error!("PDM hardware disconnected");
+                self.events.emit(EventKind::HardwareDisconnected, None, "PDM hardware disconnected");
+            }
+            (ConnectionState::Disconnected, ConnectionState::Connected) => {
+                info!("PDM hardware reconnected - re-syncing channel states");
+                self.events.emit(EventKind::HardwareReconnected, None, "PDM hardware reconnected");
+                self.resync_channel_states(pdm_state).await;
+            }
+            (ConnectionState::Unknown, ConnectionState::Connected) => {
+                info!("PDM hardware connected");
+            }
+            _ => {}
+        }
+    }
+    
+    /// Push the desired channel states to the hardware
+    ///
+    /// A reconnected board boots with all outputs off, so anything the
+    /// software view has ON must be commanded again.
+    async fn resync_channel_states(&self, pdm_state: &Arc<RwLock<PdmState>>) {
+        let mut desired: Vec<(u8, bool)> = pdm_state.read().await.channels.values()
+            .map(|channel| (channel.ch, channel.status == ChannelStatus::On))
+            .collect();
+        desired.sort_unstable();
+        
+        for (ch, enable) in desired {
+            if let Err(e) = self.control_channel(ch, enable).await {
+                error!("Failed to re-sync channel {}: {}", ch, e);
+            }
+        }
+    }
+    
+    /// Apply an unsolicited status frame from the hardware to the state
+    ///
+    /// A frame for a channel outside the configured range (protocol glitch,
+    /// mismatched firmware) is counted and dropped without touching the state.
+    /// Returns whether the frame was applied.
+    pub fn ingest_response(&self, state: &mut PdmState, response: HardwareResponse) -> bool {
+        let polarity = self.config().system.ground_polarity;
+        match response {
+            HardwareResponse::ChannelStatus { channel, voltage, current, status, fault } => {
+                let Some(ch) = state.channels.get_mut(&channel) else {
+                    let message = format!("Status frame for unknown channel {} (configured 1-{})",
+                                          channel, state.channels.len());
+                    warn!("{} - frame dropped", message);
+                    let mut errors = self.protocol_errors.lock().unwrap();
+                    errors.total += 1;
+                    errors.unknown_channel += 1;
+                    errors.last_error = Some(message);
+                    errors.last_error_at = Some(chrono::Utc::now());
+                    return false;
+                };
+                ch.voltage = polarity.normalize(voltage);
+                ch.current = polarity.normalize(current);
+                ch.status = status;
+                ch.fault = fault;
+                ch.last_update = chrono::Utc::now();
+                state.last_update = chrono::Utc::now();
+            }
+            HardwareResponse::SystemStatus { input_voltage, temperature, total_current } => {
+                state.input_voltage = polarity.normalize(input_voltage);
+                state.temperature = temperature;
+                state.total_current = polarity.normalize(total_current);
+                state.last_update = chrono::Utc::now();
+            }
+            HardwareResponse::CommandAck { success, message } => {
+                // Acks belong to the command that is waiting for them
+                debug!("Ignoring unsolicited command ack (success={}): {}", success, message);
+                return false;
+            }
+        }
+        true
+    }
+    
+    /// Send a command and wait for the hardware's response
+    pub async fn send_command(&self, message: HardwareMessage) -> Result<HardwareResponse> {
+        if self.simulation_mode {
+            debug!("[SIM] Command {:?}", message);
+            // Duty changes are channel commands and fail like them
+            let is_channel_command = matches!(message, HardwareMessage::SetDuty { .. });
+            if is_channel_command && rand::random::<f32>() < self.config().simulation.control_failure_probability {
+                return Ok(HardwareResponse::CommandAck {
+                    success: false,
+                    message: "[SIM] command failed (simulated)".to_string(),
+                });
+            }
+            Ok(HardwareResponse::CommandAck {
+                success: true,
+                message: "[SIM] acknowledged".to_string(),
+            })
+        } else {
+            self.send_real_command(message).await
+        }
+    }
+    
+    /// Adjust the PWM duty of current-limiting channels to hold them at their limit
+    ///
+    /// If the duty needed to hold a channel at its limit cannot be set, the
+    /// limiter is marked failed and overcurrent trips the channel on the next
+    /// fault check instead of leaving it running above its limit.
+    pub(crate) async fn regulate_current_limits(&self, pdm_state: &Arc<RwLock<PdmState>>) {
+        let changes: Vec<(u8, u8)> = {
+            let state = pdm_state.read().await;
+            state.channels.values()
+                .filter(|channel| channel.overcurrent_mode == OvercurrentMode::Limit
+                        && channel.status == ChannelStatus::On
+                        && channel.ramp_duty.is_none())
+                .filter_map(|channel| {
+                    let duty = channel.limiting_duty();
+                    (duty != channel.limit_duty.unwrap_or(100)).then_some((channel.ch, duty))
+                })
+                .collect()
+        };
+        
+        for (channel, duty_percent) in changes {
+            let (acknowledged, message) =
+                ack_result(self.send_command(HardwareMessage::SetDuty { channel, duty_percent }).await);
+            let mut state = pdm_state.write().await;
+            let Some(ch) = state.channels.get_mut(&channel) else { continue };
+            if !acknowledged {
+                if duty_percent < 100 {
+                    error!("Channel {} current limiter could not set {}% duty, falling back to tripping: {}",
+                           channel, duty_percent, message);
+                    ch.current_limiting = false;
+                    ch.limit_duty = None;
+                    ch.limiter_failed = true;
+                } else {
+                    warn!("Channel {} current limiter could not restore full duty: {}", channel, message);
+                }
+                continue;
+            }
+            
+            ch.limiter_failed = false;
+            let limiting = duty_percent < 100;
+            if limiting != ch.current_limiting {
+                if limiting {
+                    warn!("Channel {} current limiting at {:.1}A ({}% duty)", channel, ch.effective_current_limit, duty_percent);
+                } else {
+                    info!("Channel {} no longer current limiting", channel);
+                }
+            }
+            ch.current_limiting = limiting;
+            ch.limit_duty = limiting.then_some(duty_percent);
+        }
+    }
+    
+    /// Ramp the PWM duty of inductive channels down to zero before they are switched off
+    ///
+    /// Only ON channels with a `ramp_down_ms` are ramped; they ramp together,
+    /// each reaching zero after its own ramp time, with progress shown as
+    /// `ramp_duty`. A failed duty command aborts the ramp so the caller's
+    /// hard switch-off still happens.
+    pub async fn ramp_down(&self, pdm_state: &Arc<RwLock<PdmState>>, channels: &[u8]) {
+        let ramps: Vec<(u8, u64)> = {
+            let state = pdm_state.read().await;
+            channels.iter()
+                .filter_map(|ch| state.channels.get(ch))
+                .filter(|channel| channel.status == ChannelStatus::On && channel.ramp_down_ms > 0)
+                .map(|channel| (channel.ch, channel.ramp_down_ms))
+                .collect()
+        };
+        if ramps.is_empty() {
+            return;
+        }
+        
+        let started = Instant::now();
+        loop {
+            let elapsed = started.elapsed().as_millis() as f32;
+            let duties: Vec<(u8, u8)> = ramps.iter()
+                .map(|&(ch, ramp_ms)| (ch, (100.0 * (1.0 - elapsed / ramp_ms as f32)).clamp(0.0, 100.0) as u8))
+                .collect();
+            
+            for &(channel, duty_percent) in &duties {
+                let (acknowledged, message) =
+                    ack_result(self.send_command(HardwareMessage::SetDuty { channel, duty_percent }).await);
+                if !acknowledged {
+                    warn!("Channel {} ramp-down aborted, switching off directly: {}", channel, message);
+                    return;
+                }
+            }
+            {
+                let mut state = pdm_state.write().await;
+                for &(ch, duty) in &duties {
+                    if let Some(channel) = state.channels.get_mut(&ch) {
+                        channel.ramp_duty = Some(duty);
+                    }
+                }
+            }
+            
+            if duties.iter().all(|&(_, duty)| duty == 0) {
+                debug!("Ramp-down of channels {:?} complete", channels);
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(RAMP_STEP_MS)).await;
+        }
+    }
+    
+    /// Push every channel's configuration to the hardware and save it to non-volatile memory
+    pub async fn commission(&self, pdm_state: &Arc<RwLock<PdmState>>) -> CommissionReport {
+        let mut channels: Vec<(u8, String, f32)> = pdm_state.read().await.channels.values()
+            .map(|ch| (ch.ch, ch.name.clone(), ch.current_limit))
+            .collect();
+        channels.sort_by_key(|(ch, _, _)| *ch);
+        
+        let mut results = Vec::with_capacity(channels.len());
+        for (channel, name, current_limit) in channels {
+            let response = self.send_command(HardwareMessage::StoreChannelConfig {
+                channel,
+                name,
+                current_limit,
+            }).await;
+            let (success, message) = ack_result(response);
+            if !success {
+                warn!("Commissioning channel {} failed: {}", channel, message);
+            }
+            results.push(ChannelCommissionResult { channel, success, message });
+        }
+        
+        // Only persist a complete loadout
+        let all_stored = results.iter().all(|r| r.success);
+        let saved = if all_stored {
+            let (saved, message) = ack_result(self.send_command(HardwareMessage::SaveConfig).await);
+            if !saved {
+                error!("Saving configuration to hardware failed: {}", message);
+            }
+            saved
+        } else {
+            false
+        };
+        
+        info!("Commissioning finished: {}/{} channels stored, saved={}",
+              results.iter().filter(|r| r.success).count(), results.len(), saved);
+        
+        CommissionReport {
+            success: all_stored && saved,
+            saved,
+            channels: results,
+        }
+    }
+    
+    // ===== FAULT HANDLING =====
+    
+    /// Detect channel faults and apply the configured per-fault-type action
+    pub(crate) async fn process_channel_faults(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let safety = &config.safety;
+        let mut to_disable = Vec::new();
+        let mut to_retry = Vec::new();
+        let mut tripped = Vec::new();
+        
+        {
+            let mut state = pdm_state.write().await;
+            let now = Instant::now();
+            state.update_effective_limits(safety.max_channel_temperature);
+            // Automatic retries wait while the master switch is off or the interlock is open
+            let master_enabled = state.master_enabled && !state.interlock_open();
+            let input_voltage = state.input_voltage;
+            
+            for channel in state.channels.values_mut() {
+                if channel.test_mode_until.is_some_and(|until| now >= until) {
+                    channel.test_mode = false;
+                    channel.test_mode_until = None;
+                    warn!("Channel {} test mode expired - overcurrent protection restored", channel.ch);
+                }
+                match channel.status {
+                    ChannelStatus::On => {
+                        if let Some(fault) = evaluate_channel_faults(channel, safety, now) {
+                            channel.fault_source = Some(fault_source(fault, input_voltage, safety));
+                            self.events.emit(EventKind::ChannelFault, Some(channel.ch),
+                                             format!("Channel {} {:?}", channel.ch, fault));
+                            to_disable.push(channel.ch);
+                            tripped.push(channel.ch);
+                        }
+                    }
+                    ChannelStatus::Fault => {
+                        if let Some(deadline) = channel.recover_until {
+                            if now > deadline {
+                                channel.recover_until = None;
+                                error!("Channel {} {:?} did not clear within the recovery window - channel latched OFF",
+                                       channel.ch, channel.fault);
+                            } else if master_enabled && supply_fault_cleared(channel.fault, input_voltage, safety) {
+                                if channel.prior_status == Some(ChannelStatus::On) {
+                                    to_retry.push((channel.ch, true));
+                                } else {
+                                    channel.recover_until = None;
+                                }
+                            }
+                        } else if safety.fault_handling.recover_system_faults && master_enabled
+                            && channel.fault_source == Some(FaultSource::System)
+                            && supply_fault_cleared(channel.fault, input_voltage, safety)
+                        {
+                            // The input is back: undo what the supply did, back to the pre-fault state
+                            channel.retry_at = None;
+                            if channel.prior_status == Some(ChannelStatus::On) {
+                                to_retry.push((channel.ch, true));
+                            } else {
+                                info!("Channel {} {:?} cleared with the input restored", channel.ch, channel.fault);
+                                channel.set_status(ChannelStatus::Off, TransitionTrigger::Safety);
+                                channel.fault = None;
+                                channel.fault_source = None;
+                                channel.last_update = chrono::Utc::now();
+                            }
+                        } else if master_enabled && channel.retry_at.is_some_and(|at| now >= at) {
+                            to_retry.push((channel.ch, false));
+                        }
+                    }
+                    ChannelStatus::Off => {}
+                }
+            }
+            
+            // A paralleled member drifting from the group mean suggests a failing FET
+            for group in &config.parallel_groups {
+                let Some((imbalance, ch)) = current_imbalance(group, &state.channels) else { continue };
+                if imbalance <= group.imbalance_tolerance {
+                    continue;
+                }
+                let Some(channel) = state.channels.get_mut(&ch) else { continue };
+                // Members rarely share inrush evenly
+                if channel.in_inrush_window(now) {
+                    continue;
+                }
+                warn!("Parallel group '{}' imbalance {:.0}% exceeds {:.0}% (channel {} at {:.2}A)",
+                      group.name, imbalance * 100.0, group.imbalance_tolerance * 100.0, ch, channel.current);
+                if let Some(fault) = apply_fault_action(channel, ChannelFault::CurrentImbalance, safety, now) {
+                    self.events.emit(EventKind::ChannelFault, Some(ch),
+                                     format!("Channel {} {:?} in parallel group '{}'", ch, fault, group.name));
+                    to_disable.push(ch);
+                    tripped.push(ch);
+                }
+            }
+            
+            // A fault on one mirrored channel faults the whole group, sharing its retry schedule
+            for ch in tripped {
+                let Some(origin) = state.channels.get(&ch) else { continue };
+                let (fault, fault_source, retry_at, retry_attempts, recover_until) =
+                    (origin.fault, origin.fault_source, origin.retry_at, origin.retry_attempts, origin.recover_until);
+                let peers = origin.mirrored_with.clone();
+                
+                for peer in peers {
+                    let Some(channel) = state.channels.get_mut(&peer) else { continue };
+                    if channel.status != ChannelStatus::On || channel.overridden {
+                        continue;
+                    }
+                    warn!("Channel {} switched OFF with mirrored channel {}", peer, ch);
+                    trip_channel(channel, fault.unwrap_or(ChannelFault::Overcurrent));
+                    channel.fault_source = fault_source;
+                    channel.retry_at = retry_at;
+                    channel.retry_attempts = retry_attempts;
+                    channel.recover_until = recover_until;
+                    to_disable.push(peer);
+                }
+            }
+        }
+        
+        // Send hardware commands without holding the state lock
+        for ch in to_disable {
+            if let Err(e) = self.control_channel(ch, false).await {
+                error!("Failed to switch off faulted channel {}: {}", ch, e);
+            }
+        }
+        
+        for (ch, recovery) in to_retry {
+            // A retry switches on like anything else; a refused one stays due, without using up an attempt
+            {
+                let mut state = pdm_state.write().await;
+                if let Err(block) = state.check_switch_on(&[ch], safety) {
+                    debug!("Channel {} held OFF instead of re-enabling: {}", ch, block);
+                    continue;
+                }
+                if let Some(channel) = state.channels.get_mut(&ch) {
+                    channel.retry_at = None;
+                    channel.recover_until = None;
+                }
+            }
+            if let Err(e) = self.control_channel(ch, true).await {
+                error!("Failed to re-enable channel {}: {}", ch, e);
+                continue;
+            }
+            
+            let mut state = pdm_state.write().await;
+            if let Some(channel) = state.channels.get_mut(&ch) {
+                // An operator may have cleared the channel while the command was in flight
+                if channel.status == ChannelStatus::Fault {
+                    if recovery {
+                        let fault_ms = channel.fault_entered_at.map_or(0, |at| at.elapsed().as_millis());
+                        info!("Channel {} recovered from transient {:?} after {}ms", ch, channel.fault, fault_ms);
+                    } else {
+                        channel.retry_attempts += 1;
+                        info!("Channel {} re-enabled (attempt {})", ch, channel.retry_attempts);
+                    }
+                    channel.set_status(ChannelStatus::On, TransitionTrigger::Safety);
+                    channel.fault = None;
+                    channel.fault_source = None;
+                    channel.switched_on_at = Some(Instant::now());
+                    channel.last_update = chrono::Utc::now();
+                }
+            }
+        }
+        
+        pdm_state.write().await.update_parallel_groups(&config.parallel_groups);
         Ok(())
     }
     
-    /// Send actual channel control command to hardware
-    async fn send_real_channel_command(&self, _channel: u8, _enable: bool) -> Result<()> {
-        // TODO: Implement actual hardware communication
-        // This would involve:
-        // 1. Formatting command for hardware protocol
-        // 2. Sending over USB/CAN
-        // 3. Waiting for acknowledgment
-        // 4. Error handling for communication failures
+    // ===== SIMULATION MODE FUNCTIONS =====
+    
+    /// Pin a channel's simulated voltage/current until cleared
+    pub fn set_sim_override(&self, channel: u8, reading: SimReading) {
+        info!("[SIM] Channel {} reading pinned to {:.2}V / {:.2}A", channel, reading.voltage, reading.current);
+        self.sim_overrides.lock().unwrap().insert(channel, reading);
+    }
+    
+    /// Clear a pinned reading, returning whether one was set
+    pub fn clear_sim_override(&self, channel: u8) -> bool {
+        let cleared = self.sim_overrides.lock().unwrap().remove(&channel).is_some();
+        if cleared {
+            info!("[SIM] Channel {} reading override cleared", channel);
+        }
+        cleared
+    }
+    
+    /// Simulate system status updates for development
+    async fn simulate_system_status(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let mut state = pdm_state.write().await;
         
-        Err(anyhow!("Real hardware communication not yet implemented"))
+        // Simulate realistic voltage fluctuations plus optional alternator ripple
+        let sim = &config.simulation;
+        let t = self.started.elapsed().as_secs_f32();
+        let ripple = sim.ripple_amplitude * (2.0 * std::f32::consts::PI * sim.ripple_frequency_hz * t).sin();
+        state.input_voltage = sim.nominal_voltage + (rand::random::<f32>() - 0.5) * 0.4 + ripple;
+        
+        // Calculate total current from active channels
+        let total_current: f32 = state.channels.values()
+            .filter(|ch| ch.status == ChannelStatus::On)
+            .map(|ch| ch.current)
+            .sum();
+        
+        state.total_current = total_current + (rand::random::<f32>() - 0.5) * 0.5;
+        
+        // The supply also carries the PDM's own draw and the switching losses
+        state.input_current = sim.quiescent_current + total_current / sim.efficiency.clamp(0.01, 1.0);
+        
+        // Update system status based on conditions (a latched emergency is left alone)
+        if state.emergency_latched {
+            return Ok(());
+        }
+        state.system_status = if state.input_voltage < config.safety.min_input_voltage ||
+                                state.input_voltage > config.safety.max_input_voltage ||
+                                state.temperature > config.safety.max_temperature {
+            SystemStatus::Fault
+        } else if state.total_current > config.safety.max_total_current * 0.8 ||
+                  state.temperature > config.safety.max_temperature * 0.8 {
+            SystemStatus::Warning
+        } else {
+            SystemStatus::Normal
+        };
+        
+        debug!("System status updated: V={:.1}V, I={:.1}A, T={:.1}°C", 
+               state.input_voltage, state.total_current, state.temperature);
+        
+        Ok(())
     }
     
-    /// Send actual emergency shutdown command
-    async fn send_real_emergency_shutdown(&self) -> Result<()> {
-        // TODO: Implement actual emergency shutdown
-        Err(anyhow!("Real hardware communication not yet implemented"))
+    /// Simulate the PDM temperature from the load
+    async fn simulate_temperature(&self, pdm_state: &Arc<RwLock<PdmState>>) {
+        let mut state = pdm_state.write().await;
+        let total_current: f32 = state.channels.values()
+            .filter(|ch| ch.status == ChannelStatus::On)
+            .map(|ch| ch.current)
+            .sum();
+        
+        let base_temp = 25.0;
+        let load_factor = total_current / 50.0; // Heat up with load
+        state.temperature = base_temp + (load_factor * 15.0) + (rand::random::<f32>() * 2.0);
     }
-}
+    
+    /// Simulate channel readings
+    pub(crate) async fn simulate_channel_readings(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let mut state = pdm_state.write().await;
+        let input_voltage = state.input_voltage;
+        let ambient = state.temperature;
+        
+        // Channels that have worn out get a pinned failure reading
+        if config.simulation.failures.enabled {
+            let mut overrides = self.sim_overrides.lock().unwrap();
+            let failures = self.sim_failures.lock().unwrap()
+                .tick(&config.simulation.failures, &config.safety, &state, &overrides, Instant::now(),
+                      Duration::from_millis(config.hardware.max_time_step_ms));
+            for failure in failures {
+                warn!("[SIM] Channel {} developed a {:?} failure", failure.ch, failure.fault);
+                overrides.insert(failure.ch, failure.reading);
+            }
+        }
+        let overrides = self.sim_overrides.lock().unwrap().clone();
+        let load_models: HashMap<u8, LoadModel> = config.channels.iter()
+            .map(|entry| (entry.ch, entry.load_model))
+            .collect();
+        
+        for channel in state.channels.values_mut() {
+            match channel.status {
+                ChannelStatus::On if overrides.contains_key(&channel.ch) => {
+                    // Pinned reading replaces the random generator
+                    let reading = overrides[&channel.ch];
+                    channel.voltage = reading.voltage;
+                    channel.current = reading.current;
+                }
+                ChannelStatus::On => {
+                    // Simulate realistic voltage and current for ON channels
+                    channel.voltage = input_voltage - (rand::random::<f32>() * 0.2);
+                    
+                    // Simulate current based on channel type, drawn at the nominal voltage
+                    let base_current = match channel.name.as_str() {
+                        "FUEL PUMP" => 4.2,
+                        "IGNITION" => 2.1,
+                        "COOLING FAN" => 8.5,
+                        "HEADLIGHTS" => 6.8,
+                        "ECU MAIN" => 1.5,
+                        _ => 0.5, // Spare channels
+                    };
+                    
+                    let load_model = load_models.get(&channel.ch).copied().unwrap_or_default();
+                    let scale = load_model.current_scale(input_voltage, config.simulation.nominal_voltage);
+                    channel.current = (base_current + (rand::random::<f32>() - 0.5) * 0.5) * scale;
+                }
+                ChannelStatus::Off => {
+                    channel.voltage = 0.0;
+                    channel.current = 0.0;
+                }
+                ChannelStatus::Fault => {
+                    channel.voltage = 0.0;
+                    channel.current = 0.0;
+                }
+            }
+            
+            // Current scales with the duty while ramping down or current limiting
+            if let Some(duty) = channel.ramp_duty.or(channel.limit_duty).filter(|_| channel.status == ChannelStatus::On) {
+                channel.current *= f32::from(duty) / 100.0;
+            }
+            
+            // FET temperature settles toward ambient plus the I²R dissipation rise
+            let sim = &config.simulation;
+            let dissipation = channel.current * channel.current * sim.fet_resistance_ohms;
+            let target = ambient + dissipation * sim.thermal_resistance_c_per_w;
+            let temperature = channel.temperature.unwrap_or(ambient);
+            channel.temperature = Some(temperature + (target - temperature) * sim.thermal_smoothing);
+        }
+        
+        Ok(())
+    }
+    
+    /// Simulated per-channel hardware ratings
+    async fn simulated_channel_ratings(&self, pdm_state: &Arc<RwLock<PdmState>>) -> HashMap<u8, f32> {
+        pdm_state.read().await.channels.keys()
+            .map(|ch| (*ch, 25.0)) // 25A high-side switches
+            .collect()
+    }
+    
+    // ===== REAL HARDWARE FUNCTIONS =====
+    
+    /// Query the firmware version reported by the hardware
+    async fn read_real_firmware_version(&self) -> Result<String> {
+        // TODO: Query the firmware version over the hardware protocol
+        Err(anyhow!("Real hardware communication not yet implemented"))
+    }
+    
+    /// Query the hardware's per-channel maximum current ratings
+    async fn read_real_channel_ratings(&self) -> Result<HashMap<u8, f32>> {
+        // TODO: Implement actual hardware communication
+        Err(anyhow!("Real hardware communication not yet implemented"))
+    }
+    
+    /// Read actual system status from hardware
+    async fn read_real_system_status(&self, _pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        // TODO: Implement actual hardware communication
+        // This would involve:
+        // 1. Sending status request over USB/CAN
+        // 2. Parsing hardware response
+        // 3. Updating PDM state with real readings (including the measured input_current)
+        
+        warn!("Real hardware communication not yet implemented");
+        Ok(())
+    }
+    
+    /// Read the PDM temperature from hardware
+    async fn read_real_temperature(&self, _pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        // TODO: Implement actual hardware communication
+        // A temperature-only status request, sent less often than the
+        // voltage/current reads to save bus traffic
+        
+        warn!("Real hardware communication not yet implemented");
+        Ok(())
+    }
+    
+    /// Read actual channel status from hardware
+    async fn read_real_channel_status(&self, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let config = self.config();
+        if config.hardware.current_sensors.enabled {
+            return self.read_current_sensors(&config, pdm_state).await;
+        }
+        
+        // TODO: Implement actual hardware communication
+        // Decoded status frames should be applied with `ingest_response`
+        warn!("Real hardware communication not yet implemented");
+        Ok(())
+    }
+    
+    /// Take channel voltages and currents from the I²C current sensors
+    ///
+    /// Channels whose sensor can't be read keep their last readings; the read
+    /// only fails (and is retried) when no sensor answers. The bus is reopened
+    /// on the next read after that.
+    async fn read_current_sensors(&self, config: &Config, pdm_state: &Arc<RwLock<PdmState>>) -> Result<()> {
+        let sensors = Arc::clone(&self.current_sensors);
+        let sensors_config = config.hardware.current_sensors.clone();
+        let readings = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut sensors = sensors.lock().unwrap();
+            let opened = match sensors.take() {
+                Some(opened) => opened,
+                None => CurrentSensors::open(&sensors_config)?,
+            };
+            let readings = sensors.insert(opened).read();
+            if !readings.is_empty() && readings.iter().all(|(_, reading)| reading.is_err()) {
+                *sensors = None;
+            }
+            Ok(readings)
+        }).await??;
+        
+        let polarity = config.system.ground_polarity;
+        let mut state = pdm_state.write().await;
+        let mut failed = Vec::new();
+        for (ch, reading) in readings {
+            match reading {
+                Ok(reading) => {
+                    if let Some(channel) = state.channels.get_mut(&ch) {
+                        channel.voltage = polarity.normalize(reading.voltage);
+                        channel.current = polarity.normalize(reading.current);
+                        channel.last_update = chrono::Utc::now();
+                    }
+                }
+                Err(e) => failed.push(format!("{:#}", e)),
+            }
+        }
+        if !failed.is_empty() && failed.len() == config.hardware.current_sensors.sensors.len() {
+            return Err(anyhow!("No current sensor answered: {}", failed.join("; ")));
+        }
+        for error in failed {
+            warn!("Current sensor read failed: {}", error);
+        }
+        state.last_update = chrono::Utc::now();
+        Ok(())
+    }
+    
+    /// Send actual channel control command to hardware
+    async fn send_real_channel_command(&self, _channel: u8, _enable: bool) -> Result<()> {
+        // TODO: Implement actual hardware communication
+        // This would involve:
+        // 1. Formatting command for hardware protocol
+        // 2. Sending over USB/CAN
+        // 3. Waiting for acknowledgment
+        // 4. Error handling for communication failures
+        
+        Err(anyhow!("Real hardware communication not yet implemented"))
+    }
+    
+    /// Send an arbitrary command to the hardware and wait for its response
+    async fn send_real_command(&self, _message: HardwareMessage) -> Result<HardwareResponse> {
+        // TODO: Implement actual hardware communication
+        Err(anyhow!("Real hardware communication not yet implemented"))
+    }
+    
+    /// Send actual emergency shutdown command
+    async fn send_real_emergency_shutdown(&self) -> Result<()> {
+        // TODO: Implement actual emergency shutdown
+        Err(anyhow!("Real hardware communication not yet implemented"))
+    }
+}
+
+/// Whether `version` is at least `minimum` (dotted numeric, e.g. "1.2.0")
+///
+/// Missing trailing components count as zero and a leading "v" is ignored.
+pub fn firmware_at_least(version: &str, minimum: &str) -> Result<bool> {
+    let parse = |text: &str| -> Result<Vec<u32>> {
+        text.trim().trim_start_matches(['v', 'V'])
+            .split('.')
+            .map(|part| part.parse::<u32>().map_err(|_| anyhow!("Invalid firmware version \"{}\"", text)))
+            .collect()
+    };
+    
+    let mut version = parse(version)?;
+    let mut minimum = parse(minimum)?;
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    Ok(version >= minimum)
+}
+
+/// Interpret a command response as (acknowledged, message)
+fn ack_result(response: Result<HardwareResponse>) -> (bool, String) {
+    match response {
+        Ok(HardwareResponse::CommandAck { success, message }) => (success, message),
+        Ok(other) => (false, format!("Unexpected response: {:?}", other)),
+        Err(e) => (false, e.to_string()),
+    }
+}
+
+/// Check an ON channel's readings and apply the configured fault action
+///
+/// Shared by simulated and real readings; it only touches the channel, so
+/// hardware commands and events are left to the caller. Returns the fault
+/// if the channel was tripped and must be switched off.
+pub fn evaluate_channel_faults(channel: &mut Channel, safety: &SafetyConfig, now: Instant) -> Option<ChannelFault> {
+    track_low_current(channel, now);
+    let fault = detect_channel_fault(channel, safety, now)?;
+    apply_fault_action(channel, fault, safety, now)
+}
+
+/// Apply the configured action for a detected fault
+///
+/// Returns the fault unless the configuration ignores it.
+fn apply_fault_action(channel: &mut Channel, fault: ChannelFault, safety: &SafetyConfig, now: Instant) -> Option<ChannelFault> {
+    match safety.fault_handling.action_for(fault) {
+        FaultAction::Latch | FaultAction::AutoRetry { .. } | FaultAction::RecoverIfCleared { .. }
+            if fault == ChannelFault::Overcurrent && !channel.resettable =>
+        {
+            // A blown fuse stays open until it is physically replaced
+            error!("Channel {} {:?} - fuse blown, replace the fuse to restore", channel.ch, fault);
+            trip_channel(channel, fault);
+            channel.fuse_blown = true;
+            channel.retry_at = None;
+        }
+        FaultAction::Ignore => {
+            debug!("Channel {} {:?} ignored by fault handling config", channel.ch, fault);
+            return None;
+        }
+        FaultAction::Latch => {
+            error!("Channel {} {:?} - channel latched OFF", channel.ch, fault);
+            trip_channel(channel, fault);
+        }
+        FaultAction::AutoRetry { attempts, delay_ms } => {
+            trip_channel(channel, fault);
+            
+            if channel.retry_attempts < *attempts {
+                channel.retry_at = Some(now + Duration::from_millis(*delay_ms));
+                warn!("Channel {} {:?} - retry {}/{} in {}ms",
+                      channel.ch, fault, channel.retry_attempts + 1, attempts, delay_ms);
+            } else {
+                error!("Channel {} {:?} - retries exhausted, channel latched OFF",
+                       channel.ch, fault);
+            }
+        }
+        FaultAction::RecoverIfCleared { window_ms } => {
+            trip_channel(channel, fault);
+            channel.recover_until = Some(now + Duration::from_millis(*window_ms));
+            warn!("Channel {} {:?} - recovers if cleared within {}ms", channel.ch, fault, window_ms);
+        }
+    }
+    
+    Some(fault)
+}
+
+/// Whether the supply condition behind a fault is back in range
+///
+/// Only supply faults can be judged while the channel is off.
+fn supply_fault_cleared(fault: Option<ChannelFault>, input_voltage: f32, safety: &SafetyConfig) -> bool {
+    match fault {
+        Some(ChannelFault::Undervoltage) => input_voltage >= safety.min_input_voltage,
+        Some(ChannelFault::Overvoltage) => input_voltage <= safety.max_input_voltage,
+        _ => false,
+    }
+}
+
+/// Whether a fault was caused by the input supply or by the channel
+///
+/// Under/overvoltage counts as a system fault while the input itself is out
+/// of range; anything else, or a channel voltage fault with a healthy input,
+/// is the channel's own.
+pub fn fault_source(fault: ChannelFault, input_voltage: f32, safety: &SafetyConfig) -> FaultSource {
+    let supply_fault = matches!(fault, ChannelFault::Undervoltage | ChannelFault::Overvoltage);
+    if supply_fault && !supply_fault_cleared(Some(fault), input_voltage, safety) {
+        FaultSource::System
+    } else {
+        FaultSource::Channel
+    }
+}
+
+/// How often each class of reading is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitoringIntervals {
+    /// Input voltage and total current, plus the system checks
+    pub system: Duration,
+    /// Channel voltages and currents (the fastest rate of the adaptive monitoring)
+    pub channels: Duration,
+    /// PDM temperature
+    pub temperature: Duration,
+}
+
+/// Monitoring periods from the config
+pub fn monitoring_intervals(config: &Config) -> MonitoringIntervals {
+    let hardware = &config.hardware;
+    MonitoringIntervals {
+        system: Duration::from_millis(hardware.status_update_interval_ms.max(1)),
+        channels: Duration::from_millis(hardware.monitoring_interval_ms.max(1)),
+        temperature: Duration::from_millis(hardware.temperature_interval_ms.unwrap_or(hardware.status_update_interval_ms).max(1)),
+    }
+}
+
+/// Hardware links that are present: the serial port (configured, or the
+/// first USB serial device when auto-detecting) and the CAN interface
+///
+/// Returns what is missing when there is neither.
+pub fn detect_transport(hardware: &HardwareConfig) -> std::result::Result<Vec<String>, String> {
+    let mut links = Vec::new();
+    let mut missing = Vec::new();
+    
+    match &hardware.serial_port {
+        Some(port) if std::path::Path::new(port).exists() => links.push(format!("serial {}", port)),
+        Some(port) => missing.push(format!("serial port {} not found", port)),
+        None => {
+            let detected = std::fs::read_dir("/dev").ok().and_then(|entries| {
+                let mut ports: Vec<String> = entries.flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .filter(|name| name.starts_with("ttyUSB") || name.starts_with("ttyACM"))
+                    .collect();
+                ports.sort();
+                ports.into_iter().next()
+            });
+            match detected {
+                Some(port) => links.push(format!("serial /dev/{} (auto-detected)", port)),
+                None => missing.push("no USB serial port detected".to_string()),
+            }
+        }
+    }
+    
+    let sensors = &hardware.current_sensors;
+    if sensors.enabled {
+        if std::path::Path::new(&sensors.bus).exists() {
+            links.push(format!("I2C current sensors {}", sensors.bus));
+        } else {
+            missing.push(format!("I2C bus {} not found", sensors.bus));
+        }
+    }
+    
+    if let Some(interface) = &hardware.can_interface {
+        if std::path::Path::new("/sys/class/net").join(interface).exists() {
+            links.push(format!("CAN {}", interface));
+        } else {
+            missing.push(format!("CAN interface {} not found", interface));
+        }
+    }
+    
+    if links.is_empty() {
+        Err(missing.join(" and "))
+    } else {
+        Ok(links)
+    }
+}
+
+/// Check a running channel's readings against its limits
+///
+/// Overcurrent is checked against the derated limit and is not reported
+/// while the channel is inside its inrush window or in test mode. Current-limiting channels
+/// do not trip on overcurrent, unless their limiter has failed; they fault as a
+/// short once holding the limit would take less than `MIN_LIMIT_DUTY`.
+fn detect_channel_fault(channel: &Channel, safety: &SafetyConfig, now: Instant) -> Option<ChannelFault> {
+    let limiting = channel.overcurrent_mode == OvercurrentMode::Limit && !channel.limiter_failed;
+    if limiting && channel.limiting_duty() < MIN_LIMIT_DUTY {
+        // Not even a sliver of duty keeps the current down: treat it as a short
+        Some(ChannelFault::ShortCircuit)
+    } else if !limiting && !channel.test_mode && channel.current > channel.effective_current_limit
+        && !channel.in_inrush_window(now)
+    {
+        Some(ChannelFault::Overcurrent)
+    } else if channel.current < -safety.max_reverse_current {
+        // Backfeed is dangerous even while inrush is being tolerated
+        Some(ChannelFault::ReverseCurrent)
+    } else if channel.temperature.is_some_and(|t| t > safety.max_channel_temperature) {
+        Some(ChannelFault::Overtemperature)
+    } else if channel.voltage > safety.max_input_voltage {
+        Some(ChannelFault::Overvoltage)
+    } else if channel.voltage < safety.min_input_voltage {
+        Some(ChannelFault::Undervoltage)
+    } else if channel.low_current_since.is_some_and(|since| {
+        now.saturating_duration_since(since) >= Duration::from_millis(safety.open_load_debounce_ms)
+    }) {
+        // A burnt-out element or a disconnected load
+        Some(ChannelFault::OpenLoad)
+    } else {
+        None
+    }
+}
+
+/// Start or clear the OpenLoad debounce of an ON channel
+///
+/// Inrush and ramps don't count: the load isn't drawing its steady current yet.
+fn track_low_current(channel: &mut Channel, now: Instant) {
+    let low = channel.min_current.is_some_and(|min| channel.current < min)
+        && !channel.in_inrush_window(now)
+        && channel.ramp_duty.is_none();
+    if low {
+        channel.low_current_since.get_or_insert(now);
+    } else {
+        channel.low_current_since = None;
+    }
+}
+
+/// Put a channel into the fault state
+///
+/// The readings that caused the fault are kept in `fault_snapshot` before the
+/// live readings are zeroed.
+fn trip_channel(channel: &mut Channel, fault: ChannelFault) {
+    if channel.status != ChannelStatus::Fault {
+        channel.prior_status = Some(channel.status.clone());
+        channel.fault_entered_at = Some(Instant::now());
+    }
+    channel.recover_until = None;
+    // Faults always hard-switch, cutting short any ramp in progress
+    channel.ramp_duty = None;
+    channel.set_status(ChannelStatus::Fault, TransitionTrigger::Safety);
+    channel.fault = Some(fault);
+    channel.fault_source = Some(FaultSource::Channel);
+    channel.fault_snapshot = Some((channel.voltage, channel.current));
+    channel.voltage = 0.0;
+    channel.current = 0.0;
+    channel.last_update = chrono::Utc::now();
+}
 
 // Add rand dependency for simulation
 use rand;
@@ -230,3 +2374,1352 @@ pub fn test_memcached() -> anyhow::Result<()> {
     client.delete("test_key")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::{ChannelStatus, PdmState};
+    use crate::test_support::json_body;
+    
+    #[test]
+    fn test_input_ripple_raises_warning() {
+        use crate::models::{ripple_stats, SystemStatus};
+        
+        let (pp, std) = ripple_stats(&[13.0, 14.0, 13.0, 14.0]);
+        assert_eq!(pp, 1.0);
+        assert!((std - 0.5).abs() < 1e-6);
+        
+        let hardware_manager = crate::hardware::HardwareManager::new(Config::default()).unwrap();
+        let mut state = PdmState::new();
+        for voltage in [13.0, 14.5, 13.0, 14.5] {
+            state.input_voltage = voltage;
+            state.system_status = SystemStatus::Normal;
+            hardware_manager.update_input_ripple(&hardware_manager.config(), &mut state);
+        }
+        assert_eq!(state.input_ripple_pp, 1.5);
+        assert!(matches!(state.system_status, SystemStatus::Warning));
+    }
+    
+    #[tokio::test]
+    async fn test_fault_auto_retry_reenables_then_latches() {
+        use crate::config::{FaultAction, Precondition};
+        use crate::models::ChannelFault;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.safety.fault_handling.overcurrent = FaultAction::AutoRetry { attempts: 1, delay_ms: 0 };
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // Overcurrent on channel 1 trips it
+        pdm_state.write().await.update_channel(1, 13.5, 20.0, ChannelStatus::On);
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            let channel = state.channels.get(&1).unwrap();
+            assert_eq!(channel.status, ChannelStatus::Fault);
+            assert_eq!(channel.fault, Some(ChannelFault::Overcurrent));
+            // The live readings are zeroed but the tripping reading is retained
+            assert_eq!(channel.current, 0.0);
+            assert_eq!(channel.fault_snapshot, Some((13.5, 20.0)));
+        }
+        
+        // The retry goes through the switch-on gate: an unmet precondition holds it off
+        {
+            let mut state = pdm_state.write().await;
+            state.temperature = 80.0;
+            state.channels.get_mut(&1).unwrap().preconditions = vec![Precondition::TemperatureBelow { max: 70.0 }];
+        }
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&1].status, ChannelStatus::Fault);
+            assert_eq!(state.channels[&1].retry_attempts, 0);
+        }
+        pdm_state.write().await.temperature = 30.0;
+        
+        // Retry delay has elapsed, so the channel comes back on
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels.get(&1).unwrap().status, ChannelStatus::On);
+        assert_eq!(pdm_state.read().await.channels[&1].retry_attempts, 1);
+        
+        // Second trip exhausts the single retry and latches
+        pdm_state.write().await.update_channel(1, 13.5, 20.0, ChannelStatus::On);
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels.get(&1).unwrap().status, ChannelStatus::Fault);
+    }
+    
+    #[tokio::test]
+    async fn test_inrush_window_suppresses_overcurrent() {
+        use crate::config::ChannelConfig;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+        use tokio::sync::RwLock;
+        
+        let config = Config {
+            channels: vec![ChannelConfig { ch: 3, inrush_ignore_ms: Some(500), ..Default::default() }],
+            ..Default::default()
+        };
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        
+        // Inrush spike right after switch-on does not trip the channel
+        {
+            let mut state = pdm_state.write().await;
+            state.update_channel(3, 13.5, 40.0, ChannelStatus::On);
+            state.channels.get_mut(&3).unwrap().switched_on_at = Some(Instant::now());
+        }
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels.get(&3).unwrap().status, ChannelStatus::On);
+        
+        // Once the window has passed the same current is a fault
+        pdm_state.write().await.channels.get_mut(&3).unwrap().switched_on_at =
+            Instant::now().checked_sub(Duration::from_secs(1));
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels.get(&3).unwrap().status, ChannelStatus::Fault);
+    }
+    
+    #[tokio::test]
+    async fn test_fail_safe_reissues_shutdown_for_stuck_channel() {
+        use crate::models::SystemStatus;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let config = Config::default();
+        let max_total_current = config.safety.max_total_current;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // Channel 1 is stuck on and keeps reporting current after every shutdown
+        for _ in 0..3 {
+            {
+                let mut state = pdm_state.write().await;
+                state.update_channel(1, 13.5, max_total_current + 20.0, ChannelStatus::On);
+                state.total_current = max_total_current + 20.0;
+            }
+            let issued = hardware_manager.enforce_safety_limits(&hardware_manager.config(), &pdm_state).await.unwrap();
+            assert!(issued);
+            assert!(matches!(pdm_state.read().await.system_status, SystemStatus::Emergency));
+        }
+        
+        // Violation clears, but the emergency stays latched
+        pdm_state.write().await.total_current = 0.0;
+        let issued = hardware_manager.enforce_safety_limits(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert!(!issued);
+        let state = pdm_state.read().await;
+        assert!(state.emergency_latched);
+        assert!(matches!(state.system_status, SystemStatus::Emergency));
+    }
+    
+    #[tokio::test]
+    async fn test_hardware_reconnect_emits_events() {
+        use crate::events::EventKind;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.hardware.simulation_mode = false;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        hardware_manager.update_connection_state(true, &pdm_state).await;
+        hardware_manager.update_connection_state(false, &pdm_state).await;
+        hardware_manager.update_connection_state(false, &pdm_state).await;
+        hardware_manager.update_connection_state(true, &pdm_state).await;
+        
+        // Only the transitions are recorded, not the initial connect or repeats
+        let kinds: Vec<EventKind> = hardware_manager.events().recent(10).iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![EventKind::HardwareDisconnected, EventKind::HardwareReconnected]);
+        assert_eq!(hardware_manager.connection_state(), crate::hardware::ConnectionState::Connected);
+    }
+    
+    #[tokio::test]
+    async fn test_sim_reading_override() {
+        use crate::models::SimReading;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let hardware_manager = crate::hardware::HardwareManager::new(Config::default()).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        pdm_state.write().await.update_channel(2, 0.0, 0.0, ChannelStatus::On);
+        
+        hardware_manager.set_sim_override(2, SimReading { voltage: 12.34, current: 5.67 });
+        hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            let channel = state.channels.get(&2).unwrap();
+            assert_eq!(channel.voltage, 12.34);
+            assert_eq!(channel.current, 5.67);
+        }
+        
+        // Cleared override goes back to the random generator
+        assert!(hardware_manager.clear_sim_override(2));
+        hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_ne!(pdm_state.read().await.channels.get(&2).unwrap().current, 5.67);
+    }
+    
+    #[tokio::test]
+    async fn test_startup_self_check_refuses_limit_above_rating() {
+        use crate::config::CheckAction;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.hardware.rating_check = CheckAction::Refuse;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // Default 15A limits are within the simulated 25A rating
+        assert!(hardware_manager.startup_self_check(&pdm_state).await.is_ok());
+        assert_eq!(pdm_state.read().await.channels.get(&1).unwrap().hardware_rating, Some(25.0));
+        
+        pdm_state.write().await.channels.get_mut(&4).unwrap().current_limit = 30.0;
+        assert_eq!(pdm_state.read().await.limits_exceeding_rating(), vec![(4, 30.0, 25.0)]);
+        assert!(hardware_manager.startup_self_check(&pdm_state).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_commission_reports_every_channel() {
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        let hardware_manager = crate::hardware::HardwareManager::new(Config::default()).unwrap();
+        let report = hardware_manager.commission(&pdm_state).await;
+        assert!(report.success && report.saved);
+        assert_eq!(report.channels.len(), 8);
+        
+        // Without a hardware link every channel is reported as failed and nothing is saved
+        let mut config = Config::default();
+        config.hardware.simulation_mode = false;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let report = hardware_manager.commission(&pdm_state).await;
+        assert!(!report.success && !report.saved);
+        assert!(report.channels.iter().all(|r| !r.success));
+    }
+    
+    #[tokio::test]
+    async fn test_unconfirmed_emergency_shutdown_flags_channels() {
+        use crate::models::ChannelFault;
+        
+        // Real-hardware mode with no link: neither the broadcast nor per-channel commands succeed
+        let mut config = Config::default();
+        config.hardware.simulation_mode = false;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        
+        let report = hardware_manager.emergency_shutdown().await;
+        assert!(!report.confirmed_all_off);
+        assert!(!report.broadcast_acknowledged);
+        assert_eq!(report.unconfirmed_channels, (1..=8).collect::<Vec<u8>>());
+        
+        let mut state = PdmState::new();
+        state.emergency_shutdown();
+        state.mark_shutdown_unconfirmed(&report.unconfirmed_channels);
+        let channel = state.channels.get(&1).unwrap();
+        assert_eq!(channel.status, ChannelStatus::Fault);
+        assert_eq!(channel.fault, Some(ChannelFault::ShutdownUnconfirmed));
+    }
+    
+    #[tokio::test]
+    async fn test_lost_heartbeat_triggers_shutdown() {
+        use crate::models::SystemStatus;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.heartbeat.enabled = true;
+        config.heartbeat.timeout_ms = 1000;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        pdm_state.write().await.update_channel(1, 13.5, 4.0, ChannelStatus::On);
+        
+        // Not armed until the first heartbeat
+        assert!(!hardware_manager.check_heartbeat(&hardware_manager.config(), &pdm_state).await);
+        
+        // Recent heartbeat keeps the channels on and reports the time left
+        pdm_state.write().await.last_heartbeat = Some(Instant::now());
+        assert!(!hardware_manager.check_heartbeat(&hardware_manager.config(), &pdm_state).await);
+        assert!(pdm_state.read().await.heartbeat_remaining_ms.is_some_and(|ms| ms <= 1000));
+        
+        // Missed heartbeat shuts everything down and disarms
+        pdm_state.write().await.last_heartbeat = Instant::now().checked_sub(Duration::from_secs(2));
+        assert!(hardware_manager.check_heartbeat(&hardware_manager.config(), &pdm_state).await);
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels.get(&1).unwrap().status, ChannelStatus::Off);
+            assert!(matches!(state.system_status, SystemStatus::Emergency));
+            assert!(state.last_heartbeat.is_none());
+        }
+        assert!(!hardware_manager.check_heartbeat(&hardware_manager.config(), &pdm_state).await);
+        
+        let events = hardware_manager.events().recent(10);
+        assert_eq!(events.last().unwrap().kind, crate::events::EventKind::HeartbeatLost);
+    }
+    
+    #[tokio::test]
+    async fn test_firmware_version_check() {
+        use crate::config::CheckAction;
+        use crate::hardware::firmware_at_least;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        assert!(firmware_at_least("1.2.0", "1.2").unwrap());
+        assert!(firmware_at_least("v1.10.0", "1.9.3").unwrap());
+        assert!(!firmware_at_least("1.2.9", "1.3.0").unwrap());
+        assert!(firmware_at_least("1.2-beta", "1.0").is_err());
+        
+        // Too-old firmware is recorded and refuses startup when configured to
+        let mut config = Config::default();
+        config.hardware.min_firmware_version = Some("2.0.0".to_string());
+        config.hardware.firmware_check = CheckAction::Refuse;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        assert!(hardware_manager.startup_self_check(&pdm_state).await.is_err());
+        let state = pdm_state.read().await;
+        assert!(state.firmware_version.is_some());
+        assert_eq!(state.firmware_compatible, Some(false));
+    }
+    
+    #[tokio::test]
+    async fn test_simulated_control_latency_and_failure() {
+        use std::time::{Duration, Instant};
+        
+        let mut config = Config::default();
+        config.simulation.control_latency_ms = 50;
+        let hardware_manager = crate::hardware::HardwareManager::new(config.clone()).unwrap();
+        
+        let started = Instant::now();
+        hardware_manager.control_channel(1, true).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        
+        config.simulation.control_latency_ms = 0;
+        config.simulation.control_failure_probability = 1.0;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        assert!(hardware_manager.control_channel(1, true).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_channel_overtemperature_trips_channel() {
+        use crate::models::ChannelFault;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let hardware_manager = crate::hardware::HardwareManager::new(Config::default()).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // Simulated FET temperature rises with load
+        pdm_state.write().await.update_channel(3, 13.5, 0.0, ChannelStatus::On);
+        for _ in 0..5 {
+            hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        }
+        let ambient = pdm_state.read().await.temperature;
+        assert!(pdm_state.read().await.channels.get(&3).unwrap().temperature.is_some_and(|t| t > ambient));
+        
+        // One hot output trips on its own
+        pdm_state.write().await.channels.get_mut(&3).unwrap().temperature = Some(140.0);
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels.get(&3).unwrap().fault, Some(ChannelFault::Overtemperature));
+        assert_eq!(state.channels.get(&1).unwrap().status, ChannelStatus::Off);
+    }
+    
+    #[tokio::test]
+    async fn test_ignition_off_sequences_non_critical_channels() {
+        use crate::config::{ChannelConfig, IgnitionConfig};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let config = Config {
+            ignition: IgnitionConfig { enabled: true, off_delay_ms: 0, ..Default::default() },
+            channels: vec![ChannelConfig { ch: 1, critical: true, ..Default::default() }],
+            ..Default::default()
+        };
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        {
+            let mut state = pdm_state.write().await;
+            state.update_channel(1, 13.5, 2.0, ChannelStatus::On);
+            state.update_channel(2, 13.5, 2.0, ChannelStatus::On);
+        }
+        
+        hardware_manager.check_ignition(&hardware_manager.config(), &pdm_state).await;
+        assert_eq!(pdm_state.read().await.ignition_on, Some(true));
+        assert_eq!(pdm_state.read().await.channels[&2].status, ChannelStatus::On);
+        
+        // Ignition off: the non-critical channel goes off, the critical one stays on
+        hardware_manager.set_sim_ignition(false);
+        hardware_manager.check_ignition(&hardware_manager.config(), &pdm_state).await;
+        let state = pdm_state.read().await;
+        assert_eq!(state.ignition_on, Some(false));
+        assert_eq!(state.channels[&1].status, ChannelStatus::On);
+        assert_eq!(state.channels[&2].status, ChannelStatus::Off);
+    }
+    
+    #[tokio::test]
+    async fn test_current_limit_derates_with_temperature() {
+        use crate::config::{ChannelConfig, DeratingConfig};
+        use crate::models::ChannelFault;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let config = Config {
+            channels: vec![ChannelConfig {
+                ch: 1,
+                current_limit: Some(20.0),
+                derating: Some(DeratingConfig { start_temperature: 50.0, min_fraction: 0.5 }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        
+        // Cool: 12A is within the full 20A limit
+        {
+            let mut state = pdm_state.write().await;
+            state.update_channel(1, 13.5, 12.0, ChannelStatus::On);
+            state.channels.get_mut(&1).unwrap().temperature = Some(40.0);
+        }
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels[&1].status, ChannelStatus::On);
+        assert_eq!(pdm_state.read().await.channels[&1].effective_current_limit, 20.0);
+        
+        // Hot: the limit is derated to 15A at 87.5°C (halfway to 125°C), still fine
+        pdm_state.write().await.channels.get_mut(&1).unwrap().temperature = Some(87.5);
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels[&1].effective_current_limit, 15.0);
+        assert_eq!(pdm_state.read().await.channels[&1].status, ChannelStatus::On);
+        
+        // Near the maximum the derated limit drops below 12A and the channel trips
+        pdm_state.write().await.channels.get_mut(&1).unwrap().temperature = Some(120.0);
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels[&1].status, ChannelStatus::Fault);
+        assert_eq!(state.channels[&1].fault, Some(ChannelFault::Overcurrent));
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_config_reload_applies_to_running_monitor() {
+        use crate::config::ChannelConfig;
+        use crate::models::SimReading;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        use tokio::time::{sleep, Duration};
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig { ch: 1, current_limit: Some(100.0), ..Default::default() }],
+            ..Default::default()
+        };
+        config.hardware.status_update_interval_ms = 10;
+        config.hardware.monitoring_interval_ms = 10;
+        // Keep the simulated FET cool so only the total current matters
+        config.simulation.fet_resistance_ohms = 0.0;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = Arc::new(crate::hardware::HardwareManager::new(config.clone()).unwrap());
+        
+        pdm_state.write().await.channels.get_mut(&1).unwrap().status = ChannelStatus::On;
+        hardware_manager.set_sim_override(1, SimReading { voltage: 13.5, current: 40.0 });
+        let monitor = tokio::spawn({
+            let hardware_manager = Arc::clone(&hardware_manager);
+            let pdm_state = Arc::clone(&pdm_state);
+            async move { hardware_manager.start_monitoring(pdm_state).await }
+        });
+        
+        // 40A is within the default 100A total limit
+        sleep(Duration::from_millis(100)).await;
+        assert!(!pdm_state.read().await.emergency_latched);
+        
+        // Tighter limit and faster intervals take effect without a restart
+        config.safety.max_total_current = 30.0;
+        config.hardware.status_update_interval_ms = 5;
+        hardware_manager.reload_config(config.clone()).unwrap();
+        assert_eq!(hardware_manager.config().safety.max_total_current, 30.0);
+        sleep(Duration::from_millis(100)).await;
+        assert!(pdm_state.read().await.emergency_latched);
+        assert!(!monitor.is_finished());
+        
+        // Settings that only apply at startup are refused
+        config.hardware.channel_count = 4;
+        assert!(hardware_manager.reload_config(config).is_err());
+        assert_eq!(hardware_manager.config().hardware.channel_count, 8);
+        monitor.abort();
+    }
+    
+    #[test]
+    fn test_evaluate_channel_faults_applies_configured_action() {
+        use crate::config::FaultAction;
+        use crate::hardware::evaluate_channel_faults;
+        use crate::models::{Channel, ChannelFault};
+        use std::time::Instant;
+        
+        let mut safety = Config::default().safety;
+        let mut channel = Channel::new(1, "TEST", 15.0);
+        channel.status = ChannelStatus::On;
+        channel.voltage = 13.5;
+        channel.current = 20.0;
+        
+        // Ignored faults leave the channel running
+        safety.fault_handling.overcurrent = FaultAction::Ignore;
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, Instant::now()), None);
+        assert_eq!(channel.status, ChannelStatus::On);
+        
+        // Latched faults trip it, keeping the offending reading
+        safety.fault_handling.overcurrent = FaultAction::Latch;
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, Instant::now()), Some(ChannelFault::Overcurrent));
+        assert_eq!(channel.status, ChannelStatus::Fault);
+        assert_eq!(channel.fault_snapshot, Some((13.5, 20.0)));
+        assert!(channel.retry_at.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_input_current_includes_quiescent_draw_and_losses() {
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.simulation.quiescent_current = 0.2;
+        config.simulation.efficiency = 0.9;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        {
+            let mut state = pdm_state.write().await;
+            state.update_channel(1, 13.5, 6.0, ChannelStatus::On);
+            state.update_channel(2, 13.5, 3.0, ChannelStatus::On);
+        }
+        
+        hardware_manager.update_system_status(&hardware_manager.config(), &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        assert!((state.input_current - (0.2 + 9.0 / 0.9)).abs() < 1e-4);
+        let efficiency = state.efficiency.unwrap();
+        assert!(efficiency > 0.8 && efficiency < 0.9, "{}", efficiency);
+    }
+    
+    #[test]
+    fn test_adaptive_monitoring_rate() {
+        use crate::hardware::HardwareManager;
+        
+        let mut config = Config::default();
+        config.hardware.monitoring_interval_ms = 50;
+        config.hardware.adaptive_rate.enabled = true;
+        config.hardware.adaptive_rate.idle_interval_ms = 300;
+        config.hardware.adaptive_rate.settle_samples = 2;
+        let mut state = PdmState::from_config(&config).unwrap();
+        let hardware_manager = HardwareManager::new(config).unwrap();
+        let config = hardware_manager.config();
+        
+        // Steady readings double the interval every two samples, capped at idle
+        let intervals: Vec<u128> = (0..9)
+            .map(|_| hardware_manager.next_monitoring_interval(&config, &state).as_millis())
+            .collect();
+        assert_eq!(intervals, vec![50, 50, 100, 100, 200, 200, 300, 300, 300]);
+        assert_eq!(hardware_manager.monitoring_rate().interval_ms, 300);
+        
+        // Drift inside the deadband keeps the idle rate
+        state.channels.get_mut(&1).unwrap().current += 0.05;
+        assert_eq!(hardware_manager.next_monitoring_interval(&config, &state).as_millis(), 300);
+        
+        // A real change drops straight back to the fast rate
+        state.channels.get_mut(&1).unwrap().current += 1.0;
+        assert_eq!(hardware_manager.next_monitoring_interval(&config, &state).as_millis(), 50);
+        
+        // So does a control action
+        for _ in 0..4 {
+            hardware_manager.next_monitoring_interval(&config, &state);
+        }
+        assert!(hardware_manager.monitoring_rate().interval_ms > 50);
+        hardware_manager.note_activity();
+        let rate = hardware_manager.monitoring_rate();
+        assert_eq!(rate.interval_ms, 50);
+        assert_eq!(rate.rate_hz, 20.0);
+        assert_eq!(hardware_manager.next_monitoring_interval(&config, &state).as_millis(), 50);
+    }
+    
+    #[tokio::test]
+    async fn test_reverse_current_trips_channel() {
+        use crate::models::{ChannelFault, SimReading};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.simulation.fet_resistance_ohms = 0.0;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        pdm_state.write().await.update_channel(3, 0.0, 0.0, ChannelStatus::On);
+        
+        // Slight negative reading within the threshold is tolerated
+        hardware_manager.set_sim_override(3, SimReading { voltage: 13.0, current: -0.3 });
+        hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels[&3].status, ChannelStatus::On);
+        
+        // Backfeed beyond it trips the channel
+        hardware_manager.set_sim_override(3, SimReading { voltage: 13.0, current: -2.0 });
+        hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        let channel = &state.channels[&3];
+        assert_eq!(channel.status, ChannelStatus::Fault);
+        assert_eq!(channel.fault, Some(ChannelFault::ReverseCurrent));
+        assert_eq!(channel.fault_snapshot, Some((13.0, -2.0)));
+    }
+    
+    #[tokio::test]
+    async fn test_transient_undervoltage_recovers_sustained_latches() {
+        use crate::config::FaultAction;
+        use crate::models::ChannelFault;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.safety.fault_handling.undervoltage = FaultAction::RecoverIfCleared { window_ms: 100 };
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let config = hardware_manager.config();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        let process = || hardware_manager.process_channel_faults(&config, &pdm_state);
+        let state_ref = &pdm_state;
+        let set_input = move |voltage: f32| async move { state_ref.write().await.input_voltage = voltage };
+        
+        // Cranking sag trips the channel
+        set_input(7.5).await;
+        pdm_state.write().await.update_channel(1, 7.4, 2.0, ChannelStatus::On);
+        process().await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            let channel = &state.channels[&1];
+            assert_eq!(channel.status, ChannelStatus::Fault);
+            assert_eq!(channel.fault, Some(ChannelFault::Undervoltage));
+            assert_eq!(channel.prior_status, Some(ChannelStatus::On));
+            assert!(channel.fault_entered_at.is_some());
+        }
+        
+        // Still low: the channel waits inside the window
+        process().await.unwrap();
+        assert_eq!(pdm_state.read().await.channels[&1].status, ChannelStatus::Fault);
+        
+        // Supply recovers in time, so the channel comes back without using a retry
+        set_input(13.8).await;
+        process().await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&1].status, ChannelStatus::On);
+            assert_eq!(state.channels[&1].fault, None);
+            assert_eq!(state.channels[&1].retry_attempts, 0);
+        }
+        
+        // A sag that outlasts the window latches, even once the supply returns
+        set_input(7.5).await;
+        pdm_state.write().await.update_channel(1, 7.4, 2.0, ChannelStatus::On);
+        process().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        process().await.unwrap();
+        set_input(13.8).await;
+        process().await.unwrap();
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels[&1].status, ChannelStatus::Fault);
+        assert_eq!(state.channels[&1].fault, Some(ChannelFault::Undervoltage));
+        assert!(state.channels[&1].recover_until.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_parallel_group_trips_on_current_imbalance() {
+        use crate::config::ParallelGroupConfig;
+        use crate::models::{ChannelFault, SimReading};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config {
+            parallel_groups: vec![ParallelGroupConfig {
+                name: "Radiator fan".to_string(),
+                channels: vec![3, 4],
+                imbalance_tolerance: 0.25,
+                min_current: 2.0,
+            }],
+            ..Default::default()
+        };
+        config.simulation.fet_resistance_ohms = 0.0;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let config = hardware_manager.config();
+        for ch in [3, 4] {
+            pdm_state.write().await.update_channel(ch, 0.0, 0.0, ChannelStatus::On);
+        }
+        let cycle = |currents: [f32; 2]| {
+            let state_ref = &pdm_state;
+            let hardware_manager = &hardware_manager;
+            let config = &config;
+            async move {
+                hardware_manager.set_sim_override(3, SimReading { voltage: 13.5, current: currents[0] });
+                hardware_manager.set_sim_override(4, SimReading { voltage: 13.5, current: currents[1] });
+                hardware_manager.simulate_channel_readings(config, state_ref).await.unwrap();
+                hardware_manager.process_channel_faults(config, state_ref).await.unwrap();
+            }
+        };
+        
+        // A modest spread is reported as one virtual channel and tolerated
+        cycle([7.0, 5.0]).await;
+        {
+            let state = pdm_state.read().await;
+            let group = &state.parallel_groups[0];
+            assert_eq!(group.status, ChannelStatus::On);
+            assert_eq!(group.current, 12.0);
+            assert!((group.imbalance.unwrap() - 1.0 / 6.0).abs() < 1e-4);
+        }
+        
+        // One member carrying most of the load trips the whole group
+        cycle([10.0, 2.0]).await;
+        let state = pdm_state.read().await;
+        for ch in [3, 4] {
+            assert_eq!(state.channels[&ch].status, ChannelStatus::Fault);
+            assert_eq!(state.channels[&ch].fault, Some(ChannelFault::CurrentImbalance));
+        }
+        assert_eq!(state.parallel_groups[0].status, ChannelStatus::Fault);
+        assert_eq!(state.parallel_groups[0].imbalance, None);
+        
+        // A channel cannot be both mirrored and paralleled
+        let config = Config {
+            mirror_groups: vec![vec![1, 2]],
+            parallel_groups: vec![ParallelGroupConfig {
+                name: "Pump".to_string(),
+                channels: vec![2, 3],
+                imbalance_tolerance: 0.25,
+                min_current: 2.0,
+            }],
+            ..Default::default()
+        };
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[test]
+    fn test_unknown_channel_frame_is_dropped() {
+        use crate::models::HardwareResponse;
+        
+        let config = Config::default();
+        let mut state = PdmState::from_config(&config).unwrap();
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let before = serde_json::to_value(&state.channels).unwrap();
+        
+        for channel in [0, 9, 255] {
+            let applied = hardware_manager.ingest_response(&mut state, HardwareResponse::ChannelStatus {
+                channel,
+                voltage: 13.0,
+                current: 99.0,
+                status: ChannelStatus::On,
+                fault: None,
+            });
+            assert!(!applied);
+        }
+        assert_eq!(serde_json::to_value(&state.channels).unwrap(), before);
+        let errors = hardware_manager.protocol_errors();
+        assert_eq!((errors.total, errors.unknown_channel), (3, 3));
+        assert!(errors.last_error.unwrap().contains("255"));
+        
+        // A frame for a configured channel is applied
+        assert!(hardware_manager.ingest_response(&mut state, HardwareResponse::ChannelStatus {
+            channel: 8,
+            voltage: 13.0,
+            current: 2.5,
+            status: ChannelStatus::On,
+            fault: None,
+        }));
+        assert_eq!(state.channels[&8].current, 2.5);
+        assert_eq!(hardware_manager.protocol_errors().total, 3);
+    }
+    
+    #[tokio::test]
+    async fn test_limit_mode_holds_current_instead_of_tripping() {
+        use crate::config::{ChannelConfig, OvercurrentMode};
+        use crate::models::{ChannelFault, SimReading};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig {
+                ch: 2,
+                current_limit: Some(15.0),
+                overcurrent_mode: OvercurrentMode::Limit,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        config.simulation.fet_resistance_ohms = 0.0;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let config = hardware_manager.config();
+        for ch in [2, 3] {
+            pdm_state.write().await.update_channel(ch, 13.5, 0.0, ChannelStatus::On);
+        }
+        let cycle = |load: f32| {
+            let (hardware_manager, config, pdm_state) = (&hardware_manager, &config, &pdm_state);
+            async move {
+                for ch in [2, 3] {
+                    hardware_manager.set_sim_override(ch, SimReading { voltage: 13.5, current: load });
+                }
+                hardware_manager.simulate_channel_readings(config, pdm_state).await.unwrap();
+                hardware_manager.process_channel_faults(config, pdm_state).await.unwrap();
+                hardware_manager.regulate_current_limits(pdm_state).await;
+            }
+        };
+        
+        // A 20A load on a 15A channel: Trip mode faults, Limit mode folds back to 75% duty
+        cycle(20.0).await;
+        cycle(20.0).await;
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&3].fault, Some(ChannelFault::Overcurrent));
+            let channel = &state.channels[&2];
+            assert_eq!(channel.status, ChannelStatus::On);
+            assert!(channel.current_limiting);
+            assert_eq!(channel.limit_duty, Some(75));
+            assert!((channel.current - 15.0).abs() < 0.01);
+        }
+        
+        // Once the load drops the limiter releases
+        cycle(5.0).await;
+        cycle(5.0).await;
+        {
+            let state = pdm_state.read().await;
+            assert!(!state.channels[&2].current_limiting);
+            assert_eq!(state.channels[&2].limit_duty, None);
+        }
+        
+        // A load that would need almost no duty is a short and still trips
+        cycle(200.0).await;
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels[&2].fault, Some(ChannelFault::ShortCircuit));
+        assert!(!state.channels[&2].current_limiting);
+    }
+    
+    #[tokio::test]
+    async fn test_failed_limiter_falls_back_to_tripping() {
+        use crate::config::{ChannelConfig, OvercurrentMode};
+        use crate::models::{ChannelFault, SimReading};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig {
+                ch: 2,
+                current_limit: Some(15.0),
+                overcurrent_mode: OvercurrentMode::Limit,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        config.simulation.fet_resistance_ohms = 0.0;
+        // Every SetDuty is refused
+        config.simulation.control_failure_probability = 1.0;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let config = hardware_manager.config();
+        pdm_state.write().await.update_channel(2, 13.5, 0.0, ChannelStatus::On);
+        hardware_manager.set_sim_override(2, SimReading { voltage: 13.5, current: 20.0 });
+        
+        hardware_manager.simulate_channel_readings(&config, &pdm_state).await.unwrap();
+        hardware_manager.process_channel_faults(&config, &pdm_state).await.unwrap();
+        hardware_manager.regulate_current_limits(&pdm_state).await;
+        {
+            let state = pdm_state.read().await;
+            let channel = &state.channels[&2];
+            assert_eq!(channel.status, ChannelStatus::On);
+            assert!(channel.limiter_failed && !channel.current_limiting);
+        }
+        
+        // The next fault check trips it like a Trip-mode channel
+        hardware_manager.simulate_channel_readings(&config, &pdm_state).await.unwrap();
+        hardware_manager.process_channel_faults(&config, &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels[&2].fault, Some(ChannelFault::Overcurrent));
+        assert!(!state.channels[&2].limiter_failed);
+    }
+    
+    #[tokio::test]
+    async fn test_bring_up_waits_then_runs_degraded_without_handshake() {
+        use crate::hardware::{ConnectionState, HardwareManager};
+        
+        // The simulator is ready immediately
+        let hardware_manager = HardwareManager::new(Config::default()).unwrap();
+        assert!(hardware_manager.bring_up().await);
+        assert_eq!(hardware_manager.connection_state(), ConnectionState::Connected);
+        
+        // A board that never answers: settle delay, then every ping fails
+        let mut config = Config::default();
+        config.hardware.simulation_mode = false;
+        config.hardware.startup_delay_ms = 50;
+        config.hardware.handshake_attempts = 2;
+        config.hardware.handshake_retry_ms = 10;
+        let hardware_manager = HardwareManager::new(config).unwrap();
+        
+        let started = std::time::Instant::now();
+        assert!(!hardware_manager.bring_up().await);
+        assert!(started.elapsed() >= std::time::Duration::from_millis(60));
+        assert_eq!(hardware_manager.connection_state(), ConnectionState::Unknown);
+    }
+    
+    #[tokio::test]
+    async fn test_sim_load_models_follow_input_voltage() {
+        use crate::config::{ChannelConfig, LoadModel};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        // Three 4.2A fuel pumps, differing only in how they respond to voltage
+        let pump = |ch: u8, load_model: LoadModel| ChannelConfig {
+            ch,
+            name: Some("FUEL PUMP".to_string()),
+            load_model,
+            ..Default::default()
+        };
+        let config = Config {
+            channels: vec![
+                pump(1, LoadModel::ConstantCurrent),
+                pump(2, LoadModel::ConstantResistance),
+                pump(3, LoadModel::ConstantPower),
+            ],
+            ..Default::default()
+        };
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        
+        // Supply at twice the nominal 13.8V
+        {
+            let mut state = pdm_state.write().await;
+            state.input_voltage = 27.6;
+            for ch in 1..=3 {
+                state.update_channel(ch, 0.0, 0.0, ChannelStatus::On);
+            }
+        }
+        hardware_manager.simulate_channel_readings(&hardware_manager.config(), &pdm_state).await.unwrap();
+        
+        let state = pdm_state.read().await;
+        let current = |ch: u8| state.channels[&ch].current;
+        assert!((current(1) - 4.2).abs() <= 0.25, "constant current drew {}A", current(1));
+        assert!((current(2) - 8.4).abs() <= 0.5, "constant resistance drew {}A", current(2));
+        assert!((current(3) - 2.1).abs() <= 0.125, "constant power drew {}A", current(3));
+    }
+    
+    #[tokio::test]
+    async fn test_status_read_retries_and_failure_action() {
+        use crate::config::ReadFailureAction;
+        use crate::hardware::HardwareManager;
+        use crate::models::{ReadKind, SystemStatus};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        use tokio::time::Duration;
+        
+        let mut config = Config::default();
+        config.hardware.read_timeout_ms = 20;
+        config.hardware.read_retries = 2;
+        let hardware_manager = HardwareManager::new(config.clone()).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // Times out once, then answers: recovered on the retry
+        let calls = AtomicU32::new(0);
+        let flaky = || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            Ok(())
+        };
+        hardware_manager.read_with_retries(ReadKind::System, &config.hardware, &pdm_state, flaky).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let errors = hardware_manager.read_errors();
+        assert_eq!((errors.timeouts, errors.recovered), (1, 1));
+        
+        // Never answers: all three attempts fail and the readings go stale
+        let dead = || async { Err(anyhow::anyhow!("no response")) };
+        hardware_manager.read_with_retries(ReadKind::Channels, &config.hardware, &pdm_state, dead).await.unwrap();
+        let errors = hardware_manager.read_errors();
+        assert_eq!(errors.errors, 3);
+        assert_eq!(errors.failed.get(&ReadKind::Channels), Some(&1));
+        assert!(pdm_state.read().await.stale_readings.contains(&ReadKind::Channels));
+        
+        // A good read clears the flag
+        hardware_manager.read_with_retries(ReadKind::Channels, &config.hardware, &pdm_state, || async { Ok(()) }).await.unwrap();
+        assert!(pdm_state.read().await.stale_readings.is_empty());
+        
+        // Fault passes the error on and faults the system
+        config.hardware.read_failure_action = ReadFailureAction::Fault;
+        config.hardware.read_retries = 0;
+        let result = hardware_manager.read_with_retries(ReadKind::System, &config.hardware, &pdm_state, dead).await;
+        assert!(result.is_err());
+        assert_eq!(pdm_state.read().await.system_status, SystemStatus::Fault);
+    }
+    
+    #[tokio::test]
+    async fn test_time_steps_survive_clock_jumps() {
+        use crate::config::{FailureModeConfig, SimFailureConfig};
+        use crate::hardware::HardwareManager;
+        use crate::models::{time_step, ChannelFault};
+        use crate::sim_failures::FailureInjector;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        use std::time::{Duration, Instant};
+        
+        let max = Duration::from_secs(5);
+        let start = Instant::now();
+        assert_eq!(time_step(start + Duration::from_secs(10), start, max), Duration::ZERO);
+        assert_eq!(time_step(start, start + Duration::from_secs(86_400), max), max);
+        
+        // 120W load: a stall or a backwards step must neither explode nor reduce the counter
+        let mut config = Config::default();
+        config.hardware.max_time_step_ms = 5000;
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        {
+            let mut state = pdm_state.write().await;
+            state.input_voltage = 12.0;
+            state.total_current = 10.0;
+            state.update_channel(1, 12.0, 10.0, ChannelStatus::On);
+        }
+        let hardware_manager = HardwareManager::new(config.clone()).unwrap();
+        
+        let consumed = || async { pdm_state.read().await.energy.consumed_wh };
+        hardware_manager.update_energy(&config, &pdm_state, start + Duration::from_secs(600)).await;
+        hardware_manager.update_energy(&config, &pdm_state, start + Duration::from_secs(601)).await;
+        let one_second = consumed().await;
+        assert!(one_second > 0.0);
+        hardware_manager.update_energy(&config, &pdm_state, start + Duration::from_secs(300)).await;
+        assert_eq!(consumed().await, one_second);
+        hardware_manager.update_energy(&config, &pdm_state, start + Duration::from_secs(602)).await;
+        assert!((consumed().await - 2.0 * one_second).abs() < 1e-9);
+        hardware_manager.update_energy(&config, &pdm_state, start + Duration::from_secs(1_000_000)).await;
+        assert!((consumed().await - 7.0 * one_second).abs() < 1e-9);
+        
+        // Simulated ON-time never runs backwards or jumps past the step limit
+        let failures = SimFailureConfig {
+            enabled: true,
+            mtbf_secs: 3600.0,
+            seed: Some(7),
+            modes: vec![FailureModeConfig { fault: ChannelFault::Overcurrent, weight: 1.0 }],
+            ..Default::default()
+        };
+        let state = pdm_state.read().await;
+        let pinned: HashMap<u8, ()> = HashMap::new();
+        let mut injector = FailureInjector::new(&failures);
+        for seconds in [0, 1_000_000_000, 10] {
+            let now = start + Duration::from_secs(seconds);
+            assert!(injector.tick(&failures, &config.safety, &state, &pinned, now, max).is_empty());
+        }
+        
+        // A limit shorter than an update interval is refused
+        let mut config = Config::default();
+        config.hardware.max_time_step_ms = config.hardware.status_update_interval_ms - 1;
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_real_mode_without_transport_refuses_or_falls_back() {
+        use crate::config::MissingTransportAction;
+        use crate::hardware::{detect_transport, HardwareManager};
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        use tower::ServiceExt;
+        
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        config.hardware.simulation_mode = false;
+        config.hardware.serial_port = Some("/dev/pdm-test-missing".to_string());
+        config.hardware.can_interface = Some("pdmtest9".to_string());
+        let problem = detect_transport(&config.hardware).unwrap_err();
+        assert!(problem.contains("/dev/pdm-test-missing") && problem.contains("pdmtest9"));
+        
+        assert!(HardwareManager::open(config.clone()).is_err());
+        
+        config.hardware.missing_transport = MissingTransportAction::Simulate;
+        let hardware_manager = Arc::new(HardwareManager::open(config.clone()).unwrap());
+        assert!(hardware_manager.is_simulation());
+        assert_eq!(hardware_manager.mode_fallback(), Some(problem.as_str()));
+        
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let fleet = Arc::new(crate::fleet::FleetMonitor::new(config.aggregator.clone()).unwrap());
+        let app = crate::api::create_router(pdm_state, hardware_manager, fleet);
+        let response = app.oneshot(Request::get("/api/config").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["simulation_mode"], true);
+        assert_eq!(body["requested_simulation_mode"], false);
+        assert_eq!(body["mode_fallback"], problem.as_str());
+    }
+    
+    #[test]
+    fn test_positive_ground_readings_are_normalized() {
+        use crate::config::{GroundPolarity, SystemConfig};
+        use crate::models::HardwareResponse;
+        
+        let config = Config {
+            system: SystemConfig { ground_polarity: GroundPolarity::Positive },
+            ..Default::default()
+        };
+        let mut state = PdmState::from_config(&config).unwrap();
+        let hardware_manager = crate::hardware::HardwareManager::new(config.clone()).unwrap();
+        assert!(config.startup_summary().iter().any(|line| line.starts_with("Ground:    positive")));
+        
+        assert!(hardware_manager.ingest_response(&mut state, HardwareResponse::SystemStatus {
+            input_voltage: -13.2,
+            temperature: 31.0,
+            total_current: -4.0,
+        }));
+        assert!(hardware_manager.ingest_response(&mut state, HardwareResponse::ChannelStatus {
+            channel: 1,
+            voltage: -13.1,
+            current: -4.0,
+            status: ChannelStatus::On,
+            fault: None,
+        }));
+        assert_eq!((state.input_voltage, state.temperature, state.total_current), (13.2, 31.0, 4.0));
+        assert_eq!((state.channels[&1].voltage, state.channels[&1].current), (13.1, 4.0));
+    }
+    
+    #[tokio::test]
+    async fn test_thermal_shedding_does_not_flap() {
+        use crate::config::ThermalSheddingConfig;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.safety.thermal_shedding = ThermalSheddingConfig {
+            enabled: true,
+            channels: vec![3, 2],
+            shed_temperature: 70.0,
+            recovery_temperature: 60.0,
+            min_shed_ms: 60_000,
+            step_ms: 1_000,
+        };
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config.clone()).unwrap();
+        for ch in [2, 3] {
+            pdm_state.write().await.channels.get_mut(&ch).unwrap().status = ChannelStatus::On;
+        }
+        let status = |state: &PdmState, ch: u8| (state.channels[&ch].status.clone(), state.channels[&ch].shed);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let step = |temperature: f32, secs: u64| {
+            let pdm_state = Arc::clone(&pdm_state);
+            let hardware_manager = &hardware_manager;
+            let config = &config;
+            async move {
+                pdm_state.write().await.temperature = temperature;
+                hardware_manager.update_thermal_shedding(config, &pdm_state, at(secs)).await;
+            }
+        };
+        
+        // Hot: the lowest-priority channel goes first, the next only after a step
+        step(75.0, 0).await;
+        step(75.0, 0).await;
+        assert_eq!(status(&*pdm_state.read().await, 3), (ChannelStatus::Off, true));
+        assert_eq!(status(&*pdm_state.read().await, 2), (ChannelStatus::On, false));
+        
+        // Just below the shed threshold: nothing more is shed and nothing comes back
+        for secs in 2..30 {
+            step(69.0 - (secs % 3) as f32, secs).await;
+        }
+        assert_eq!(status(&*pdm_state.read().await, 3), (ChannelStatus::Off, true));
+        assert_eq!(status(&*pdm_state.read().await, 2), (ChannelStatus::On, false));
+        
+        // Cool enough, but the minimum shed time hasn't passed
+        step(55.0, 40).await;
+        assert!(pdm_state.read().await.channels[&3].shed);
+        
+        step(55.0, 61).await;
+        assert_eq!(status(&*pdm_state.read().await, 3), (ChannelStatus::On, false));
+        assert_eq!(
+            pdm_state.read().await.channels[&3].transitions.back().map(|t| t.trigger),
+            Some(crate::models::TransitionTrigger::LoadShedding)
+        );
+        
+        config.safety.thermal_shedding.recovery_temperature = 75.0;
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_input_restoration_clears_only_system_faults() {
+        use crate::models::{ChannelFault, FaultSource};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.safety.fault_handling.recover_system_faults = true;
+        let hardware_manager = crate::hardware::HardwareManager::new(config).unwrap();
+        let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+        
+        // The input sags: channels 1 and 2 see it; channel 3 trips on its own overcurrent
+        {
+            let mut state = pdm_state.write().await;
+            state.input_voltage = 8.0;
+            state.update_channel(1, 8.0, 2.0, ChannelStatus::On);
+            state.update_channel(2, 8.0, 3.0, ChannelStatus::On);
+            state.update_channel(3, 13.5, 40.0, ChannelStatus::On);
+        }
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        {
+            let state = pdm_state.read().await;
+            for ch in [1, 2] {
+                assert_eq!(state.channels[&ch].fault, Some(ChannelFault::Undervoltage));
+                assert_eq!(state.channels[&ch].fault_source, Some(FaultSource::System));
+            }
+            assert_eq!(state.channels[&3].fault, Some(ChannelFault::Overcurrent));
+            assert_eq!(state.channels[&3].fault_source, Some(FaultSource::Channel));
+        }
+        
+        // Still low: nothing recovers
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        assert_eq!(pdm_state.read().await.channels[&1].status, ChannelStatus::Fault);
+        
+        pdm_state.write().await.input_voltage = 13.8;
+        hardware_manager.process_channel_faults(&hardware_manager.config(), &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        for ch in [1, 2] {
+            assert_eq!(state.channels[&ch].status, ChannelStatus::On);
+            assert_eq!(state.channels[&ch].fault, None);
+            assert_eq!(state.channels[&ch].fault_source, None);
+        }
+        assert_eq!(state.channels[&3].status, ChannelStatus::Fault);
+        assert_eq!(state.channels[&3].fault, Some(ChannelFault::Overcurrent));
+    }
+    
+    #[test]
+    fn test_open_load_after_debounce() {
+        use crate::hardware::evaluate_channel_faults;
+        use crate::models::{Channel, ChannelFault};
+        use std::time::{Duration, Instant};
+        
+        let safety = Config::default().safety;
+        let debounce = Duration::from_millis(safety.open_load_debounce_ms);
+        let mut channel = Channel::new(1, "HEATER", 15.0);
+        channel.status = ChannelStatus::On;
+        channel.voltage = 13.5;
+        channel.current = 4.0;
+        channel.min_current = Some(1.0);
+        let start = Instant::now();
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start), None);
+        
+        // The element burns out: nothing until the low reading has lasted the debounce
+        channel.current = 0.02;
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start), None);
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start + debounce / 2), None);
+        
+        // A reading back in range restarts the debounce
+        channel.current = 4.0;
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start + debounce / 2), None);
+        channel.current = 0.02;
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start + debounce), None);
+        assert_eq!(evaluate_channel_faults(&mut channel, &safety, start + debounce * 2), Some(ChannelFault::OpenLoad));
+        assert_eq!(channel.status, ChannelStatus::Fault);
+        assert_eq!(channel.low_current_since, None);
+        
+        // Thresholds at or above the current limit are rejected
+        let mut config = Config::default();
+        config.channels.push(crate::config::ChannelConfig { ch: 1, min_current: Some(20.0), ..Default::default() });
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_temperature_sampled_on_its_own_timer() {
+        use crate::config::MAX_TEMPERATURE_INTERVAL_MS;
+        use crate::hardware::monitoring_intervals;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        use tokio::time::{sleep, Duration};
+        
+        // By default temperature is read with the system status
+        let mut config = Config::default();
+        let intervals = monitoring_intervals(&config);
+        assert_eq!(intervals.temperature, intervals.system);
+        
+        // Too slow to catch overtemperature
+        config.hardware.temperature_interval_ms = Some(MAX_TEMPERATURE_INTERVAL_MS + 1);
+        assert!(PdmState::from_config(&config).is_err());
+        
+        config.hardware.status_update_interval_ms = 5;
+        config.hardware.monitoring_interval_ms = 5;
+        config.hardware.temperature_interval_ms = Some(300);
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = Arc::new(crate::hardware::HardwareManager::new(config).unwrap());
+        let monitor = tokio::spawn({
+            let hardware_manager = Arc::clone(&hardware_manager);
+            let pdm_state = Arc::clone(&pdm_state);
+            async move { hardware_manager.start_monitoring(pdm_state).await }
+        });
+        
+        // The fast status updates leave the temperature alone between its reads
+        sleep(Duration::from_millis(50)).await;
+        pdm_state.write().await.temperature = -40.0;
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(pdm_state.read().await.temperature, -40.0);
+        sleep(Duration::from_millis(250)).await;
+        assert!(pdm_state.read().await.temperature > 0.0);
+        monitor.abort();
+    }
+    
+    #[tokio::test]
+    async fn test_battery_soc_and_load_shedding() {
+        use crate::battery::{soc_from_voltage, SocEstimator};
+        use crate::config::{SocPoint, SocShedStage};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let mut config = Config::default();
+        config.battery.enabled = true;
+        let battery = config.battery.clone();
+        assert!((soc_from_voltage(&battery.soc_curve, 12.2) - 50.0).abs() < 1e-3);
+        assert_eq!(soc_from_voltage(&battery.soc_curve, 13.8), 100.0);
+        assert_eq!(soc_from_voltage(&battery.soc_curve, 10.0), 0.0);
+        
+        // Starts from the curve, then counts the charge drawn: 10A for 6 minutes is 1% of 100Ah
+        let mut estimator = SocEstimator::default();
+        assert_eq!(estimator.update(&battery, 12.7, 10.0, 0.0), 100.0);
+        assert!((estimator.update(&battery, 12.1, 10.0, 360.0) - 99.0).abs() < 1e-4);
+        // At rest the voltage pulls the estimate toward the curve
+        assert!(estimator.update(&battery, 12.1, 0.2, 0.1) < 99.0);
+        
+        config.battery.shedding = vec![SocShedStage { below_soc: 50.0, channels: vec![3, 4] }];
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config.clone()).unwrap();
+        let set_soc = |soc: f32| {
+            let pdm_state = Arc::clone(&pdm_state);
+            async move { pdm_state.write().await.battery_soc_percent = Some(soc) }
+        };
+        pdm_state.write().await.channels.get_mut(&3).unwrap().status = ChannelStatus::On;
+        
+        // Below the stage its ON channels are shed
+        set_soc(40.0).await;
+        hardware_manager.update_battery_shedding(&config, &pdm_state).await;
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&3].status, ChannelStatus::Off);
+            assert!(state.channels[&3].battery_shed);
+            assert!(!state.channels[&4].battery_shed);
+        }
+        
+        // They stay off until the charge is past the recovery margin
+        set_soc(52.0).await;
+        hardware_manager.update_battery_shedding(&config, &pdm_state).await;
+        assert_eq!(pdm_state.read().await.channels[&3].status, ChannelStatus::Off);
+        set_soc(56.0).await;
+        
+        // Restores go through the shared switch-on gate
+        let mut full = config.clone();
+        full.safety.max_active_channels = Some(0);
+        hardware_manager.update_battery_shedding(&full, &pdm_state).await;
+        assert!(pdm_state.read().await.channels[&3].battery_shed);
+        
+        hardware_manager.update_battery_shedding(&config, &pdm_state).await;
+        {
+            let state = pdm_state.read().await;
+            assert_eq!(state.channels[&3].status, ChannelStatus::On);
+            assert!(!state.channels[&3].battery_shed);
+        }
+        
+        // A curve that doesn't rise is rejected
+        config.battery.soc_curve = vec![SocPoint { voltage: 12.5, soc: 100.0 }, SocPoint { voltage: 12.0, soc: 0.0 }];
+        assert!(PdmState::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+/*!
+ * Telemetry History Buffer
+ *
+ * Monitoring runs at a high rate (20Hz by default), which would fill a
+ * history buffer in seconds. Samples are therefore decimated: all readings
+ * within a configurable interval are aggregated into one stored point
+ * (mean/max current, min voltage) so long windows stay representative.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::config::HistoryConfig;
+use crate::models::{ChannelStatus, PdmState};
+use crate::precision;
+
+/// Aggregated readings for one channel over a history interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelHistorySample {
+    pub ch: u8,
+    #[serde(serialize_with = "precision::current")]
+    pub mean_current: f32,
+    #[serde(serialize_with = "precision::current")]
+    pub max_current: f32,
+    #[serde(serialize_with = "precision::voltage")]
+    pub min_voltage: f32,
+    /// Status at the end of the interval
+    pub status: ChannelStatus,
+}
+
+/// One stored history point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySample {
+    /// End of the aggregation interval
+    pub timestamp: DateTime<Utc>,
+    /// Number of monitoring samples aggregated into this point
+    pub sample_count: u32,
+    #[serde(serialize_with = "precision::voltage")]
+    pub mean_input_voltage: f32,
+    #[serde(serialize_with = "precision::voltage")]
+    pub min_input_voltage: f32,
+    #[serde(serialize_with = "precision::current")]
+    pub mean_total_current: f32,
+    #[serde(serialize_with = "precision::current")]
+    pub max_total_current: f32,
+    #[serde(serialize_with = "precision::temperature")]
+    pub max_temperature: f32,
+    pub channels: Vec<ChannelHistorySample>,
+}
+
+/// Running sums for the interval being aggregated
+#[derive(Debug, Default)]
+struct ChannelAccumulator {
+    current_sum: f32,
+    max_current: f32,
+    min_voltage: f32,
+    status: Option<ChannelStatus>,
+}
+
+#[derive(Debug)]
+struct Accumulator {
+    started: Instant,
+    count: u32,
+    input_voltage_sum: f32,
+    min_input_voltage: f32,
+    total_current_sum: f32,
+    max_total_current: f32,
+    max_temperature: f32,
+    channels: BTreeMap<u8, ChannelAccumulator>,
+}
+
+impl Accumulator {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            count: 0,
+            input_voltage_sum: 0.0,
+            min_input_voltage: f32::MAX,
+            total_current_sum: 0.0,
+            max_total_current: f32::MIN,
+            max_temperature: f32::MIN,
+            channels: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, state: &PdmState) {
+        self.count += 1;
+        self.input_voltage_sum += state.input_voltage;
+        self.min_input_voltage = self.min_input_voltage.min(state.input_voltage);
+        self.total_current_sum += state.total_current;
+        self.max_total_current = self.max_total_current.max(state.total_current);
+        self.max_temperature = self.max_temperature.max(state.temperature);
+
+        for channel in state.channels.values() {
+            let acc = self.channels.entry(channel.ch).or_insert_with(|| ChannelAccumulator {
+                min_voltage: f32::MAX,
+                max_current: f32::MIN,
+                ..Default::default()
+            });
+            acc.current_sum += channel.current;
+            acc.max_current = acc.max_current.max(channel.current);
+            acc.min_voltage = acc.min_voltage.min(channel.voltage);
+            acc.status = Some(channel.status.clone());
+        }
+    }
+
+    fn finish(self) -> HistorySample {
+        let count = self.count.max(1) as f32;
+
+        HistorySample {
+            timestamp: Utc::now(),
+            sample_count: self.count,
+            mean_input_voltage: self.input_voltage_sum / count,
+            min_input_voltage: self.min_input_voltage,
+            mean_total_current: self.total_current_sum / count,
+            max_total_current: self.max_total_current,
+            max_temperature: self.max_temperature,
+            channels: self.channels.into_iter()
+                .map(|(ch, acc)| ChannelHistorySample {
+                    ch,
+                    mean_current: acc.current_sum / count,
+                    max_current: acc.max_current,
+                    min_voltage: acc.min_voltage,
+                    status: acc.status.unwrap_or(ChannelStatus::Off),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Bounded, decimated history of system readings
+#[derive(Debug)]
+pub struct HistoryBuffer {
+    capacity: usize,
+    interval: Duration,
+    /// Stored points with the monotonic time they were stored
+    samples: VecDeque<(Instant, HistorySample)>,
+    pending: Option<Accumulator>,
+}
+
+impl HistoryBuffer {
+    /// Create an empty history buffer
+    pub fn new(config: &HistoryConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            interval: Duration::from_millis(config.sample_interval_ms),
+            samples: VecDeque::with_capacity(config.capacity),
+            pending: None,
+        }
+    }
+
+    /// Add a monitoring sample, storing an aggregated point once the interval has elapsed
+    pub fn record(&mut self, state: &PdmState, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let pending = self.pending.get_or_insert_with(|| Accumulator::new(now));
+        pending.add(state);
+
+        if now.duration_since(pending.started) >= self.interval {
+            if let Some(pending) = self.pending.take() {
+                if self.samples.len() == self.capacity {
+                    self.samples.pop_front();
+                }
+                self.samples.push_back((now, pending.finish()));
+            }
+        }
+    }
+
+    /// The most recent `limit` stored points, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<HistorySample> {
+        let start = self.samples.len().saturating_sub(limit);
+        self.samples.iter().skip(start).map(|(_, sample)| sample.clone()).collect()
+    }
+
+    /// Stored points from the last `max_age` before `now`, oldest first
+    ///
+    /// Ages are measured on the monotonic clock, so a wall-clock change
+    /// doesn't shift the window.
+    pub fn since(&self, max_age: Duration, now: Instant) -> Vec<HistorySample> {
+        self.samples.iter()
+            .filter(|(stored, _)| now.saturating_duration_since(*stored) < max_age)
+            .map(|(_, sample)| sample.clone())
+            .collect()
+    }
+
+    /// `value` of the stored points from the last `max_age`, as (seconds before `now`, value)
+    fn series(&self, max_age: Duration, now: Instant, value: impl Fn(&HistorySample) -> Option<f32>) -> Vec<(f32, f32)> {
+        self.samples.iter()
+            .filter(|(stored, _)| now.saturating_duration_since(*stored) <= max_age)
+            .filter_map(|(stored, sample)| {
+                Some((-now.saturating_duration_since(*stored).as_secs_f32(), value(sample)?))
+            })
+            .collect()
+    }
+    
+    /// Predict from the stored trend when each ON channel, and the total, reaches its current limit
+    ///
+    /// A channel only uses points taken wholly while it was ON, so the step
+    /// at switch-on doesn't read as a rising load.
+    pub fn update_trends(&self, state: &mut PdmState, config: &HistoryConfig, max_total_current: f32, now: Instant) {
+        let window = Duration::from_millis(config.trend_window_ms);
+        let horizon = config.trend_horizon_ms as f32 / 1000.0;
+        let predict = |points: Vec<(f32, f32)>, limit: f32| {
+            (points.len() >= config.trend_min_points.max(2))
+                .then(|| time_to_limit(&points, limit))
+                .flatten()
+                .filter(|seconds| *seconds <= horizon)
+        };
+        
+        state.time_to_limit_seconds = predict(
+            self.series(window, now, |sample| Some(sample.mean_total_current)),
+            max_total_current,
+        );
+        for channel in state.channels.values_mut() {
+            channel.time_to_limit_seconds = match (channel.status == ChannelStatus::On, channel.switched_on_at) {
+                (true, Some(on_since)) => {
+                    let on_for = now.saturating_duration_since(on_since).saturating_sub(self.interval);
+                    let points = self.series(window.min(on_for), now, |sample| {
+                        sample.channels.iter()
+                            .find(|reading| reading.ch == channel.ch && reading.status == ChannelStatus::On)
+                            .map(|reading| reading.mean_current)
+                    });
+                    predict(points, channel.effective_current_limit)
+                }
+                _ => None,
+            };
+        }
+    }
+    
+    /// Number of stored points
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether no points are stored yet
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// Seconds after the latest point until a least-squares line through `points` reaches `limit`
+///
+/// `points` are (time in seconds, value). Returns None for fewer than two
+/// distinct times or a flat or falling trend, and 0 if the fitted line is
+/// already at the limit.
+pub fn time_to_limit(points: &[(f32, f32)], limit: f32) -> Option<f32> {
+    let n = points.len() as f32;
+    let last_time = points.iter().map(|(time, _)| *time).fold(f32::NEG_INFINITY, f32::max);
+    if points.len() < 2 || !last_time.is_finite() {
+        return None;
+    }
+    let mean_time = points.iter().map(|(time, _)| time).sum::<f32>() / n;
+    let mean_value = points.iter().map(|(_, value)| value).sum::<f32>() / n;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (time, value)| {
+        let dt = time - mean_time;
+        (cov + dt * (value - mean_value), var + dt * dt)
+    });
+    if variance <= f32::EPSILON {
+        return None;
+    }
+    
+    let slope = covariance / variance;
+    if !(slope.is_finite() && slope > 0.0) {
+        return None;
+    }
+    let fitted_now = mean_value + slope * (last_time - mean_time);
+    Some(((limit - fitted_now) / slope).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[test]
+    fn test_history_decimation_aggregates_samples() {
+        use crate::config::HistoryConfig;
+        use crate::history::HistoryBuffer;
+        use std::time::{Duration, Instant};
+        
+        let mut history = HistoryBuffer::new(&HistoryConfig { capacity: 10, sample_interval_ms: 1000, ..Default::default() });
+        let mut state = PdmState::new();
+        let start = Instant::now();
+        
+        // 20 samples at 20Hz: only the one that closes the 1s window is stored
+        for i in 0..=20u32 {
+            let current = if i == 5 { 9.0 } else { 4.0 };
+            let voltage = if i == 7 { 11.5 } else { 13.5 };
+            state.update_channel(1, voltage, current, ChannelStatus::On);
+            history.record(&state, start + Duration::from_millis(50 * i as u64));
+        }
+        
+        assert_eq!(history.len(), 1);
+        let point = &history.recent(1)[0];
+        let channel = point.channels.iter().find(|c| c.ch == 1).unwrap();
+        assert_eq!(point.sample_count, 21);
+        assert_eq!(channel.max_current, 9.0);
+        assert_eq!(channel.min_voltage, 11.5);
+        assert!(channel.mean_current > 4.0 && channel.mean_current < 5.0);
+    }
+    
+    #[test]
+    fn test_time_to_limit_regression() {
+        use crate::history::time_to_limit;
+        
+        // Failing motor: +0.5A per second with some noise
+        let rising: Vec<(f32, f32)> = (0..10)
+            .map(|t| (t as f32, 5.0 + 0.5 * t as f32 + if t % 2 == 0 { 0.1 } else { -0.1 }))
+            .collect();
+        let seconds = time_to_limit(&rising, 15.0).unwrap();
+        assert!((seconds - 11.0).abs() < 0.5, "{}", seconds);
+        
+        let flat: Vec<(f32, f32)> = (0..10).map(|t| (t as f32, 5.0)).collect();
+        assert_eq!(time_to_limit(&flat, 15.0), None);
+        let falling: Vec<(f32, f32)> = (0..10).map(|t| (t as f32, 9.0 - 0.5 * t as f32)).collect();
+        assert_eq!(time_to_limit(&falling, 15.0), None);
+        assert_eq!(time_to_limit(&rising, 8.0), Some(0.0));
+        assert_eq!(time_to_limit(&rising[..1], 15.0), None);
+        assert_eq!(time_to_limit(&[(3.0, 1.0), (3.0, 2.0)], 15.0), None);
+        
+        // Channel 1 climbs 0.2A/s towards its 15A limit; channel 2 holds steady
+        use crate::config::HistoryConfig;
+        use crate::history::HistoryBuffer;
+        use std::time::{Duration, Instant};
+        
+        let config = HistoryConfig { sample_interval_ms: 0, ..Default::default() };
+        let mut history = HistoryBuffer::new(&config);
+        let mut state = PdmState::new();
+        let start = Instant::now();
+        for ch in [1, 2] {
+            state.channels.get_mut(&ch).unwrap().switched_on_at = Some(start);
+        }
+        let mut now = start;
+        for t in 1..=20 {
+            now = start + Duration::from_secs(t);
+            state.update_channel(1, 13.5, 5.0 + 0.2 * t as f32, ChannelStatus::On);
+            state.update_channel(2, 13.5, 4.0, ChannelStatus::On);
+            state.total_current = 9.0 + 0.2 * t as f32;
+            history.record(&state, now);
+        }
+        history.update_trends(&mut state, &config, 60.0, now);
+        let seconds = state.time_to_limit_seconds.unwrap();
+        assert!((seconds - 235.0).abs() < 0.5, "{}", seconds);
+        let seconds = state.channels[&1].time_to_limit_seconds.unwrap();
+        assert!((seconds - 30.0).abs() < 0.5, "{}", seconds);
+        assert_eq!(state.channels[&2].time_to_limit_seconds, None);
+        assert_eq!(state.channels[&3].time_to_limit_seconds, None);
+        // 187A to go at 0.2A/s is beyond the 10 minute horizon
+        history.update_trends(&mut state, &config, 200.0, now);
+        assert_eq!(state.time_to_limit_seconds, None);
+    }
+}
@@ -0,0 +1,227 @@
+/*!
+ * Fault Indicator Outputs
+ *
+ * Drives status LEDs and buzzers from the PDM state on embedded
+ * deployments. Each configured output is a Linux GPIO (sysfs) that is
+ * switched on while its condition holds. In simulation mode, or with
+ * indicators disabled, the states are only tracked so they can still be
+ * inspected through `GET /api/diagnostics/indicators`.
+ */
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sysfs_gpio::{Direction, Pin};
+use tracing::{debug, warn};
+
+use crate::config::{IndicatorCondition, IndicatorConfig, IndicatorOutputConfig};
+use crate::models::{ChannelFault, ChannelStatus, PdmState, SystemStatus};
+
+/// Current state of one indicator
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorState {
+    pub name: String,
+    pub pin: u64,
+    pub condition: IndicatorCondition,
+    pub on: bool,
+}
+
+/// Indicator states reported by the diagnostics endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorReport {
+    pub enabled: bool,
+    /// Outputs are tracked but not driven
+    pub simulated: bool,
+    pub indicators: Vec<IndicatorState>,
+}
+
+struct Indicator {
+    config: IndicatorOutputConfig,
+    /// Exported GPIO, absent when simulated
+    pin: Option<Pin>,
+    on: bool,
+}
+
+/// The configured indicator outputs
+pub struct IndicatorBank {
+    enabled: bool,
+    simulated: bool,
+    indicators: Vec<Indicator>,
+    /// Whether the last GPIO write failed, so an outage is logged once
+    failing: bool,
+}
+
+impl IndicatorBank {
+    /// Export the configured GPIOs as outputs, switched off
+    pub fn new(config: &IndicatorConfig, simulated: bool) -> Result<Self> {
+        let drive = config.enabled && !simulated;
+        let indicators = config.outputs.iter()
+            .map(|output| {
+                let pin = if drive {
+                    let pin = Pin::new(output.pin);
+                    pin.export().with_context(|| format!("Failed to export GPIO {} ({})", output.pin, output.name))?;
+                    let off = if output.active_low { Direction::High } else { Direction::Low };
+                    pin.set_direction(off)
+                        .with_context(|| format!("Failed to configure GPIO {} ({})", output.pin, output.name))?;
+                    Some(pin)
+                } else {
+                    None
+                };
+                Ok(Indicator { config: output.clone(), pin, on: false })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            enabled: config.enabled,
+            simulated,
+            indicators,
+            failing: false,
+        })
+    }
+
+    /// Re-evaluate every indicator against the state, writing only changed outputs
+    ///
+    /// An output whose write fails keeps its old state, so the next update
+    /// retries it.
+    pub fn update(&mut self, state: &PdmState) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut failed = false;
+        for indicator in &mut self.indicators {
+            let on = condition_met(indicator.config.condition, state);
+            if on == indicator.on {
+                continue;
+            }
+
+            if let Some(pin) = &indicator.pin {
+                let level = u8::from(on != indicator.config.active_low);
+                if let Err(e) = pin.set_value(level) {
+                    if !self.failing {
+                        warn!("Failed to set indicator {} (GPIO {}): {}", indicator.config.name, indicator.config.pin, e);
+                    }
+                    failed = true;
+                    continue;
+                }
+            }
+            indicator.on = on;
+            debug!("Indicator {} {}", indicator.config.name, if on { "ON" } else { "OFF" });
+        }
+        self.failing = failed;
+    }
+
+    /// Current indicator states
+    pub fn report(&self) -> IndicatorReport {
+        IndicatorReport {
+            enabled: self.enabled,
+            simulated: self.simulated,
+            indicators: self.indicators.iter()
+                .map(|indicator| IndicatorState {
+                    name: indicator.config.name.clone(),
+                    pin: indicator.config.pin,
+                    condition: indicator.config.condition,
+                    on: indicator.on,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Whether a condition holds in the given state
+pub fn condition_met(condition: IndicatorCondition, state: &PdmState) -> bool {
+    match condition {
+        IndicatorCondition::SystemWarning => state.system_status.severity() >= SystemStatus::Warning.severity(),
+        IndicatorCondition::SystemFault => state.system_status.severity() >= SystemStatus::Fault.severity(),
+        IndicatorCondition::Emergency => state.emergency_latched || state.system_status == SystemStatus::Emergency,
+        IndicatorCondition::AnyChannelFault => state.channels.values().any(|channel| channel.status == ChannelStatus::Fault),
+        IndicatorCondition::AnyOvercurrent => state.channels.values()
+            .any(|channel| channel.status == ChannelStatus::Fault && channel.fault == Some(ChannelFault::Overcurrent)),
+        IndicatorCondition::ChannelFault(ch) => state.channels.get(&ch)
+            .is_some_and(|channel| channel.status == ChannelStatus::Fault),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[test]
+    fn test_indicators_follow_fault_conditions() {
+        use crate::config::{IndicatorCondition, IndicatorConfig, IndicatorOutputConfig};
+        use crate::indicators::IndicatorBank;
+        use crate::models::{ChannelFault, SystemStatus};
+        
+        let output = |name: &str, pin: u64, condition: IndicatorCondition| IndicatorOutputConfig {
+            name: name.to_string(),
+            pin,
+            condition,
+            active_low: false,
+        };
+        let config = IndicatorConfig {
+            enabled: true,
+            outputs: vec![
+                output("status", 17, IndicatorCondition::SystemFault),
+                output("overcurrent", 27, IndicatorCondition::AnyOvercurrent),
+                output("pump", 22, IndicatorCondition::ChannelFault(5)),
+                output("buzzer", 23, IndicatorCondition::Emergency),
+            ],
+        };
+        // Simulated: states are tracked without touching any GPIO
+        let mut bank = IndicatorBank::new(&config, true).unwrap();
+        let lit = |bank: &IndicatorBank| -> Vec<bool> { bank.report().indicators.iter().map(|i| i.on).collect() };
+        
+        let mut state = PdmState::new();
+        bank.update(&state);
+        assert_eq!(lit(&bank), [false, false, false, false]);
+        
+        state.update_channel(3, 0.0, 0.0, ChannelStatus::Fault);
+        state.channels.get_mut(&3).unwrap().fault = Some(ChannelFault::Overcurrent);
+        state.system_status = SystemStatus::Fault;
+        bank.update(&state);
+        assert_eq!(lit(&bank), [true, true, false, false]);
+        
+        state.emergency_shutdown();
+        state.emergency_latched = true;
+        state.system_status = SystemStatus::Emergency;
+        bank.update(&state);
+        assert_eq!(lit(&bank), [true, false, false, true]);
+        let report = bank.report();
+        assert!(report.enabled && report.simulated);
+        
+        // Disabled indicators never light
+        let mut bank = IndicatorBank::new(&IndicatorConfig { enabled: false, ..config }, true).unwrap();
+        bank.update(&state);
+        assert_eq!(lit(&bank), [false, false, false, false]);
+    }
+    
+    #[test]
+    fn test_failed_indicator_write_is_retried() {
+        use crate::config::{IndicatorCondition, IndicatorOutputConfig};
+        use crate::indicators::{Indicator, IndicatorBank};
+        use sysfs_gpio::Pin;
+        
+        // A GPIO that was never exported: every write fails
+        let config = IndicatorOutputConfig {
+            name: "status".to_string(),
+            pin: 9999,
+            condition: IndicatorCondition::AnyChannelFault,
+            active_low: false,
+        };
+        let mut bank = IndicatorBank {
+            enabled: true,
+            simulated: false,
+            indicators: vec![Indicator { config, pin: Some(Pin::new(9999)), on: false }],
+            failing: false,
+        };
+        
+        let mut state = PdmState::new();
+        state.update_channel(3, 0.0, 0.0, ChannelStatus::Fault);
+        bank.update(&state);
+        assert!(!bank.report().indicators[0].on);
+        assert!(bank.failing);
+        
+        // Still off, so the write is attempted again on the next update
+        bank.update(&state);
+        assert!(!bank.report().indicators[0].on);
+    }
+}
@@ -1,12 +1,41 @@
-/**
+/*!
  * Backend Tests
  * 
  * Basic tests to verify the PDM backend functionality
  */
 
+pub mod alerts;
+pub mod api;
+pub mod audit;
+pub mod battery;
+pub mod can_output;
+pub mod client;
+pub mod config;
+pub mod current_sensors;
+pub mod demo;
+pub mod events;
+pub mod fleet;
+pub mod hardware;
+pub mod history;
+pub mod indicators;
+pub mod lifetime;
+pub mod log_stream;
+pub mod metrics;
+pub mod models;
+pub mod precision;
+pub mod scheduler;
+pub mod setup;
+pub mod signing;
+pub mod sim_failures;
+pub mod stream;
+pub mod telemetry;
+#[cfg(test)]
+mod test_support;
+pub mod validation;
+pub mod webhooks;
+
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::models::{PdmState, ChannelStatus};
     use crate::config::Config;
     
@@ -78,6 +107,7 @@ mod tests {
         
         assert_eq!(config.server_address, "127.0.0.1:3030");
         assert_eq!(config.api_version, "1.0.0");
+        assert_eq!(config.api_base_path, "/api");
         assert!(config.hardware.simulation_mode);
         assert_eq!(config.safety.max_total_current, 100.0);
     }
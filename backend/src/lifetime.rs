@@ -0,0 +1,237 @@
+/*!
+ * Lifetime Counters
+ *
+ * Operating totals that survive restarts: runtime, number of starts, energy
+ * delivered and ON-time per channel. They are advanced with the energy
+ * accounting on every status cycle, kept in memory, and flushed to a JSON
+ * file periodically and at shutdown. The file is replaced atomically, so a
+ * crash loses at most one flush interval and never leaves a torn file.
+ *
+ * Startup opens the persisted counters and gives them to the hardware
+ * manager; a manager created without them keeps in-memory counters.
+ */
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{interval, Duration};
+use tracing::{info, warn};
+
+use crate::config::LifetimeConfig;
+use crate::models::{ChannelStatus, PdmState};
+
+/// Cumulative operating totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    /// Total time the backend has been monitoring (s)
+    pub runtime_seconds: f64,
+    /// Times the backend has started
+    pub starts: u64,
+    /// Energy delivered to the outputs (Wh)
+    pub energy_wh: f64,
+    /// Time each channel has been ON (s)
+    pub channel_on_seconds: BTreeMap<u8, f64>,
+    /// When the counters were first started
+    pub first_start: Option<DateTime<Utc>>,
+}
+
+impl LifetimeStats {
+    /// Advance the totals by one status cycle of `seconds`
+    pub fn accumulate(&mut self, state: &PdmState, seconds: f64) {
+        self.runtime_seconds += seconds;
+        self.energy_wh += f64::from(state.total_power().max(0.0)) * seconds / 3600.0;
+        for channel in state.channels.values().filter(|channel| channel.status == ChannelStatus::On) {
+            *self.channel_on_seconds.entry(channel.ch).or_default() += seconds;
+        }
+    }
+}
+
+/// The lifetime counters and the file they persist to
+pub struct LifetimeCounters {
+    /// None when persistence is disabled
+    path: Option<PathBuf>,
+    stats: Mutex<LifetimeStats>,
+    session_started: Instant,
+    last_flush: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl LifetimeCounters {
+    /// Load the stored counters and count this start
+    ///
+    /// A missing file starts from zero. An unreadable one is moved aside to
+    /// `<path>.bad` so it is kept for inspection rather than overwritten.
+    pub fn open(config: &LifetimeConfig) -> Self {
+        let path = config.enabled.then(|| PathBuf::from(&config.path));
+        let mut stats = path.as_deref().map(load).unwrap_or_default();
+        stats.starts += 1;
+        stats.first_start.get_or_insert_with(Utc::now);
+
+        Self {
+            path,
+            stats: Mutex::new(stats),
+            session_started: Instant::now(),
+            last_flush: Mutex::new(None),
+        }
+    }
+
+    /// Counters for this run only, never read from or written to a file
+    pub fn in_memory() -> Self {
+        Self::open(&LifetimeConfig { enabled: false, ..Default::default() })
+    }
+
+    /// Advance the counters by one status cycle of `seconds`
+    pub fn accumulate(&self, state: &PdmState, seconds: f64) {
+        self.stats.lock().unwrap().accumulate(state, seconds);
+    }
+
+    /// Current totals
+    pub fn stats(&self) -> LifetimeStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Seconds since this process started
+    pub fn session_seconds(&self) -> u64 {
+        self.session_started.elapsed().as_secs()
+    }
+
+    /// File the counters persist to, if enabled
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// When the counters were last written
+    pub fn last_flush(&self) -> Option<DateTime<Utc>> {
+        *self.last_flush.lock().unwrap()
+    }
+
+    /// Write the counters to their file, replacing it atomically
+    ///
+    /// The new contents are synced before the rename and the directory after
+    /// it, so a power cut leaves either the old or the new counters.
+    pub async fn flush(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let contents = serde_json::to_vec_pretty(&self.stats())?;
+        let temporary = path.with_extension("tmp");
+        write_synced(&temporary, &contents).await
+            .with_context(|| format!("Failed to write {}", temporary.display()))?;
+        tokio::fs::rename(&temporary, path).await
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        sync_directory(path).await
+            .with_context(|| format!("Failed to sync the directory of {}", path.display()))?;
+        *self.last_flush.lock().unwrap() = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Flush now and then every `interval_ms`
+    pub async fn start_flushing(self: Arc<Self>, interval_ms: u64) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let stats = self.stats();
+        info!("Lifetime counters in {}: start {}, {:.1}h runtime", path.display(), stats.starts,
+              stats.runtime_seconds / 3600.0);
+
+        let mut flush_interval = interval(Duration::from_millis(interval_ms.max(1)));
+        loop {
+            flush_interval.tick().await;
+            if let Err(e) = self.flush().await {
+                warn!("Lifetime counters not saved: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Write `contents` to `path` and wait until they are on disk
+async fn write_synced(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut file = tokio::fs::File::create(path).await?;
+    file.write_all(contents).await?;
+    file.sync_all().await
+}
+
+/// Sync the directory holding `path`, making a rename into it durable
+#[cfg(unix)]
+async fn sync_directory(path: &Path) -> std::io::Result<()> {
+    let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    tokio::fs::File::open(directory).await?.sync_all().await
+}
+
+/// Directories cannot be opened for syncing here; the rename is left to the OS
+#[cfg(not(unix))]
+async fn sync_directory(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Read stored counters, starting over if there are none or they can't be read
+fn load(path: &Path) -> LifetimeStats {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return LifetimeStats::default(),
+        Err(e) => {
+            warn!("Cannot read lifetime counters {}: {} - starting from zero", path.display(), e);
+            return LifetimeStats::default();
+        }
+    };
+    serde_json::from_slice(&contents).unwrap_or_else(|e| {
+        let bad = path.with_extension("bad");
+        warn!("Lifetime counters {} are unreadable ({}); moved to {} and starting from zero",
+              path.display(), e, bad.display());
+        if let Err(e) = std::fs::rename(path, &bad) {
+            warn!("Could not move {} aside: {}", path.display(), e);
+        }
+        LifetimeStats::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[tokio::test]
+    async fn test_lifetime_counters_survive_restart() {
+        use crate::config::LifetimeConfig;
+        use crate::lifetime::LifetimeCounters;
+        
+        let path = std::env::temp_dir().join(format!("pdm_lifetime_test_{}.json", std::process::id()));
+        let config = LifetimeConfig { path: path.to_string_lossy().into_owned(), ..Default::default() };
+        let _ = std::fs::remove_file(&path);
+        
+        let mut state = PdmState::new();
+        state.update_channel(2, 12.0, 5.0, ChannelStatus::On);
+        state.input_voltage = 12.0;
+        state.total_current = 5.0;
+        
+        let counters = LifetimeCounters::open(&config);
+        counters.accumulate(&state, 1800.0);
+        counters.accumulate(&state, 1800.0);
+        counters.flush().await.unwrap();
+        assert!(counters.last_flush().is_some());
+        assert!(!path.with_extension("tmp").exists());
+        
+        let counters = LifetimeCounters::open(&config);
+        let stats = counters.stats();
+        assert_eq!(stats.starts, 2);
+        assert_eq!(stats.runtime_seconds, 3600.0);
+        assert!((stats.energy_wh - 60.0).abs() < 1e-6);
+        assert_eq!(stats.channel_on_seconds.get(&2), Some(&3600.0));
+        assert_eq!(stats.channel_on_seconds.get(&1), None);
+        
+        // A damaged file is kept aside rather than silently overwritten
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(LifetimeCounters::open(&config).stats().starts, 1);
+        let bad = path.with_extension("bad");
+        assert!(bad.exists());
+        let _ = std::fs::remove_file(&bad);
+        
+        let disabled = LifetimeCounters::open(&LifetimeConfig { enabled: false, ..config });
+        disabled.flush().await.unwrap();
+        assert!(disabled.path().is_none() && !path.exists());
+        
+        // Only counters handed to the manager persist
+        let hardware_manager = crate::hardware::HardwareManager::new(crate::config::Config::default()).unwrap();
+        assert!(hardware_manager.lifetime().path().is_none());
+    }
+}
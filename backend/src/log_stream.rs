@@ -0,0 +1,137 @@
+/*!
+ * Live Log Streaming
+ *
+ * A `tracing` layer that copies each log record into a bounded broadcast
+ * channel, next to the normal console output, so `GET /logs/stream` can tail
+ * the log from a browser on headless units. Sending never waits: with no
+ * client connected the record is dropped at once, and a client that falls
+ * more than `LOG_STREAM_CAPACITY` records behind skips the ones it missed.
+ *
+ * The channel is process-wide because the layer is installed before the
+ * config is loaded and the API exists.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::fmt::Write;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Records a streaming client may fall behind before it skips ahead
+pub const LOG_STREAM_CAPACITY: usize = 1024;
+
+/// One log line as streamed to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    #[serde(serialize_with = "level_name")]
+    pub level: Level,
+    /// Module the record came from
+    pub target: String,
+    /// The message followed by any other fields as `key=value`
+    pub message: String,
+}
+
+fn level_name<S: Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(level.as_str())
+}
+
+fn sender() -> &'static broadcast::Sender<LogRecord> {
+    static SENDER: OnceLock<broadcast::Sender<LogRecord>> = OnceLock::new();
+    SENDER.get_or_init(|| broadcast::channel(LOG_STREAM_CAPACITY).0)
+}
+
+/// Receive the log records written from now on
+pub fn subscribe() -> broadcast::Receiver<LogRecord> {
+    sender().subscribe()
+}
+
+/// Layer feeding the log stream
+pub fn layer() -> LogStreamLayer {
+    LogStreamLayer { sender: sender().clone() }
+}
+
+pub struct LogStreamLayer {
+    sender: broadcast::Sender<LogRecord>,
+}
+
+impl<S: Subscriber> Layer<S> for LogStreamLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        // Skip the formatting entirely while nobody is watching
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        let metadata = event.metadata();
+        let _ = self.sender.send(LogRecord {
+            timestamp: Utc::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: message.0,
+        });
+    }
+}
+
+/// Formats an event's fields the way the console output does
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::test_support::test_app;
+    
+    #[tokio::test]
+    async fn test_log_stream() {
+        use crate::log_stream;
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+        use tracing_subscriber::prelude::*;
+        
+        // Records reach subscribers with their fields; nobody listening never blocks
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(log_stream::layer()), || {
+            tracing::info!("nobody is listening yet");
+            let mut receiver = log_stream::subscribe();
+            tracing::warn!(channel = 3, "Channel tripped");
+            let record = receiver.try_recv().unwrap();
+            assert_eq!(record.level, tracing::Level::WARN);
+            assert_eq!(record.message, "Channel tripped channel=3");
+            assert!(receiver.try_recv().is_err());
+        });
+        
+        let request = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        
+        // Not served unless opted in
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        let (router, _, _) = test_app(config.clone());
+        let response = router.oneshot(request("/api/logs/stream")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        
+        config.features.opt_in = vec![crate::config::ApiFeature::Logs];
+        let (router, _, _) = test_app(config);
+        let response = router.clone().oneshot(request("/api/logs/stream?level=loud")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = router.oneshot(request("/api/logs/stream?level=debug")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+    }
+}
@@ -1,45 +1,94 @@
 
 // Import error handling type from anyhow crate
-use anyhow::Result;
+use anyhow::{bail, Result};
 // Import logging macros from tracing crate
-use tracing::{info, error};
+use tracing::{info, error, warn};
 // Import thread-safe reference counting pointer
 use std::sync::Arc;
 // Import async read/write lock for shared state
 use tokio::sync::RwLock;
 
-// Declare submodules for API, hardware, models, and config
-mod api;
-mod hardware;
-mod models;
-mod config;
-
+// Import config module from the backend library
+use pdm_backend::config;
 // Import PdmState struct from models module
-use models::PdmState;
+use pdm_backend::models::PdmState;
 // Import HardwareManager struct from hardware module
-use hardware::HardwareManager;
+use pdm_backend::hardware::HardwareManager;
 // Import create_router function from api module
-use api::create_router;
+use pdm_backend::api::create_router;
+// Import FleetMonitor struct for aggregator mode
+use pdm_backend::fleet::FleetMonitor;
+// Import CanBroadcaster for CAN telemetry output
+use pdm_backend::can_output::CanBroadcaster;
+// Import WebhookDispatcher for state-change webhooks
+use pdm_backend::webhooks::WebhookDispatcher;
+// Import TelemetryRecorder for the on-disk telemetry log
+use pdm_backend::telemetry::TelemetryRecorder;
+// Import Scheduler for time-of-day channel rules
+use pdm_backend::scheduler::Scheduler;
+// Import the simulator demo mode
+use pdm_backend::demo;
+// Import config signing helpers for the signing subcommands
+use pdm_backend::signing;
+// Import output rounding settings
+use pdm_backend::precision;
+// Import the persisted lifetime counters
+use pdm_backend::lifetime::LifetimeCounters;
+// Import the live log stream layer
+use pdm_backend::log_stream;
+// Import subscriber composition for the logging layers
+use tracing::Level;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::prelude::*;
 
 // Main async entry point for the backend server
 #[tokio::main] // Macro to use Tokio runtime for async main
 async fn main() -> Result<()> { // Main function, returns Result for error handling
-    // Initialize logging system
-    tracing_subscriber::fmt::init();
+    // Initialize logging: the console, plus a copy for GET /api/logs/stream
+    // that carries every level of the backend's own records
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
+        .with(log_stream::layer().with_filter(
+            Targets::new().with_target("pdm_backend", Level::TRACE).with_default(Level::INFO)
+        ))
+        .init();
+    
+    // Config signing subcommands do their job and exit
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--generate-signing-key") => return generate_signing_key(args.get(1)),
+        Some("--sign-config") => return sign_config(args.get(1)),
+        _ => {}
+    }
     
     // Log server startup
     info!("PDM Backend Server starting...");
     
     // Load configuration from file or environment
     let config = config::Config::load()?;
-    // Log loaded configuration
-    info!("Configuration loaded: listening on {}", config.server_address);
+    // Round readings in API output to the configured decimals
+    precision::apply(&config.precision);
+    // Log a summary of the operating configuration
+    info!("Configuration loaded:");
+    for line in config.startup_summary() {
+        info!("  {}", line);
+    }
     
     // Create shared, thread-safe PdmState
-    let pdm_state = Arc::new(RwLock::new(PdmState::new()));
+    let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config)?));
+    
+    // Load the lifetime counters and count this start
+    let lifetime = Arc::new(LifetimeCounters::open(&config.lifetime));
     
     // Create shared, thread-safe HardwareManager
-    let hardware_manager = Arc::new(HardwareManager::new(config.clone())?);
+    // (real mode without a serial port or CAN interface refuses or falls back to simulation)
+    let hardware_manager = Arc::new(HardwareManager::open(config.clone())?.with_lifetime(Arc::clone(&lifetime)));
+    
+    // Give the board time to boot and confirm it answers before commanding it
+    hardware_manager.bring_up().await;
+    
+    // Check configured limits against the hardware's ratings before running
+    hardware_manager.startup_self_check(&pdm_state).await?;
     
     // Start hardware monitoring in a background task
     let hardware_task = {
@@ -55,8 +104,93 @@ async fn main() -> Result<()> { // Main function, returns Result for error handl
         })
     };
     
+    // Start state-change webhooks if enabled
+    if config.webhooks.enabled {
+        let dispatcher = Arc::new(WebhookDispatcher::new(config.webhooks.clone())?);
+        let events = Arc::clone(hardware_manager.events()); // Clone Arc for task
+        tokio::spawn(async move {
+            if let Err(e) = dispatcher.start(events).await {
+                // Log error if webhook dispatch fails
+                error!("Webhook dispatcher failed: {}", e);
+            }
+        });
+    }
+    
+    // Start CAN telemetry broadcast if enabled
+    if config.can_output.enabled {
+        let broadcaster = CanBroadcaster::new(config.can_output.clone(), config.hardware.can_interface.clone())?;
+        let pdm_state = Arc::clone(&pdm_state); // Clone Arc for task
+        tokio::spawn(async move {
+            if let Err(e) = broadcaster.start(pdm_state).await {
+                // Log error if CAN output fails
+                error!("CAN telemetry output failed: {}", e);
+            }
+        });
+    }
+    
+    // Start the on-disk telemetry log if enabled
+    if config.telemetry.enabled {
+        let recorder = TelemetryRecorder::new(config.telemetry.clone())?;
+        let pdm_state = Arc::clone(&pdm_state); // Clone Arc for task
+        tokio::spawn(async move {
+            if let Err(e) = recorder.start(pdm_state).await {
+                // Log error if telemetry recording fails
+                error!("Telemetry recording failed: {}", e);
+            }
+        });
+    }
+    
+    // Persist the lifetime counters periodically so a crash loses little
+    {
+        let lifetime = Arc::clone(&lifetime); // Clone Arc for task
+        let flush_interval_ms = config.lifetime.flush_interval_ms;
+        tokio::spawn(async move {
+            if let Err(e) = lifetime.start_flushing(flush_interval_ms).await {
+                // Log error if the counters can't be persisted
+                error!("Lifetime counter flushing failed: {}", e);
+            }
+        });
+    }
+    
+    // Start the self-driving simulator demo if configured
+    if config.simulation.demo_mode {
+        if hardware_manager.is_simulation() {
+            let simulation = &config.simulation;
+            demo::start(&hardware_manager, &pdm_state, simulation.demo_pattern, simulation.demo_step_ms)?;
+        } else {
+            warn!("simulation.demo_mode is ignored with real hardware");
+        }
+    }
+    
+    // Start time-of-day schedules if any are configured
+    if !config.schedules.is_empty() {
+        let scheduler = Scheduler::new(&config.schedules, config.hardware.channel_count)?;
+        let pdm_state = Arc::clone(&pdm_state); // Clone Arc for task
+        let hardware_manager = Arc::clone(&hardware_manager); // Clone Arc for task
+        tokio::spawn(async move {
+            if let Err(e) = scheduler.start(hardware_manager, pdm_state).await {
+                // Log error if the scheduler fails
+                error!("Scheduler failed: {}", e);
+            }
+        });
+    }
+    
+    // Create fleet monitor for aggregator mode
+    let fleet = Arc::new(FleetMonitor::new(config.aggregator.clone())?);
+    
+    // Start polling downstream units if aggregator mode is enabled
+    if fleet.is_enabled() {
+        let fleet = Arc::clone(&fleet); // Clone Arc for task
+        tokio::spawn(async move {
+            if let Err(e) = fleet.start_polling().await {
+                // Log error if fleet polling fails
+                error!("Fleet polling failed: {}", e);
+            }
+        });
+    }
+    
     // Create API router with shared state
-    let app = create_router(pdm_state, hardware_manager);
+    let app = create_router(pdm_state, hardware_manager, fleet);
     
     // Bind TCP listener to server address
     let listener = tokio::net::TcpListener::bind(&config.server_address).await?;
@@ -67,7 +201,9 @@ async fn main() -> Result<()> { // Main function, returns Result for error handl
     
     // Start HTTP server in a background task
     let server_task = tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
+        // Serve with connection info so handlers can see the client address
+        let service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        if let Err(e) = axum::serve(listener, service).await {
             // Log error if server fails
             error!("Server error: {}", e);
         }
@@ -89,7 +225,49 @@ async fn main() -> Result<()> { // Main function, returns Result for error handl
         }
     }
     
+    // Save this run's share of the lifetime counters
+    if let Err(e) = lifetime.flush().await {
+        // Log error if the final flush fails
+        error!("Lifetime counters not saved at shutdown: {}", e);
+    }
+    
     // Log server shutdown
     info!("PDM Backend Server shutting down");
     Ok(()) // Return success
 }
+
+// Write a new secret signing key and install its public key for verification
+fn generate_signing_key(path: Option<&String>) -> Result<()> {
+    let Some(path) = path else {
+        bail!("Usage: pdm-backend --generate-signing-key <secret-key-file>");
+    };
+    // Never clobber an existing key - configs signed with it would stop verifying
+    if std::path::Path::new(path).exists() {
+        bail!("{} already exists", path);
+    }
+    let (secret, public) = signing::generate_key();
+    // Readable by the owner only
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, secret.as_bytes())?;
+    std::fs::write(signing::PUBLIC_KEY_FILE, &public)?;
+    println!("Secret key written to {} (keep it off the device)", path);
+    println!("Public key written to {}: {}", signing::PUBLIC_KEY_FILE, public);
+    Ok(())
+}
+
+// Sign the config file with a secret key, writing the detached signature
+fn sign_config(path: Option<&String>) -> Result<()> {
+    let Some(path) = path else {
+        bail!("Usage: pdm-backend --sign-config <secret-key-file>");
+    };
+    let secret = std::fs::read_to_string(path)?;
+    let contents = std::fs::read(config::CONFIG_FILE)?;
+    let signature_path = signing::signature_path(std::path::Path::new(config::CONFIG_FILE));
+    std::fs::write(&signature_path, signing::sign(&contents, &secret)?)?;
+    println!("Signed {} -> {} (public key {})",
+             config::CONFIG_FILE, signature_path.display(), signing::public_key(&secret)?);
+    Ok(())
+}
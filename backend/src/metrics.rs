@@ -0,0 +1,193 @@
+/*!
+ * API Request Metrics
+ *
+ * Counts requests per endpoint (method + route pattern) with their
+ * status-code classes and a fixed-bucket latency histogram. Recording is a
+ * handful of relaxed atomic increments; the map lock is only taken for
+ * writing the first time an endpoint is seen. Percentiles are estimated
+ * from the histogram as the upper bound of the bucket they fall in.
+ *
+ * `RequestLimit` bounds how many requests are handled at once, turning the
+ * excess away instead of letting it queue.
+ */
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets (µs); a final bucket catches the rest
+const LATENCY_BUCKETS_US: [u64; 14] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 2_500_000,
+];
+
+/// Counters for one endpoint
+#[derive(Debug, Default)]
+struct EndpointMetrics {
+    count: AtomicU64,
+    /// Responses by status class (1xx..5xx)
+    status_classes: [AtomicU64; 5],
+    latency_sum_us: AtomicU64,
+    latency_max_us: AtomicU64,
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+}
+
+impl EndpointMetrics {
+    fn record(&self, status: u16, latency: Duration) {
+        let latency_us = latency.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = LATENCY_BUCKETS_US.iter()
+            .position(|&bound| latency_us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if let Some(class) = (status / 100).checked_sub(1).and_then(|i| self.status_classes.get(i as usize)) {
+            class.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency_sum_us.fetch_add(latency_us, Ordering::Relaxed);
+        self.latency_max_us.fetch_max(latency_us, Ordering::Relaxed);
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> EndpointSnapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        let buckets: Vec<u64> = self.latency_buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+        let max_us = self.latency_max_us.load(Ordering::Relaxed);
+
+        let percentile = |p: f64| -> f64 {
+            let target = ((count as f64) * p).ceil().max(1.0) as u64;
+            let mut seen = 0;
+            for (i, &n) in buckets.iter().enumerate() {
+                seen += n;
+                if seen >= target {
+                    // Never report more than the slowest request actually seen
+                    let bound = LATENCY_BUCKETS_US.get(i).copied().unwrap_or(max_us);
+                    return bound.min(max_us) as f64 / 1000.0;
+                }
+            }
+            max_us as f64 / 1000.0
+        };
+
+        EndpointSnapshot {
+            count,
+            status: self.status_classes.iter().enumerate()
+                .map(|(i, n)| (format!("{}xx", i + 1), n.load(Ordering::Relaxed)))
+                .filter(|(_, n)| *n > 0)
+                .collect(),
+            latency_ms: LatencySnapshot {
+                mean: if count > 0 {
+                    self.latency_sum_us.load(Ordering::Relaxed) as f64 / count as f64 / 1000.0
+                } else {
+                    0.0
+                },
+                p50: percentile(0.5),
+                p90: percentile(0.9),
+                p99: percentile(0.99),
+                max: max_us as f64 / 1000.0,
+            },
+        }
+    }
+}
+
+/// Reported figures for one endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointSnapshot {
+    pub count: u64,
+    /// Response counts by status class ("2xx", "4xx", ...)
+    pub status: BTreeMap<String, u64>,
+    pub latency_ms: LatencySnapshot,
+}
+
+/// Latency summary (ms); percentiles are histogram bucket bounds
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySnapshot {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Request metrics for every endpoint that has been called
+#[derive(Debug, Default)]
+pub struct ApiMetrics {
+    endpoints: RwLock<HashMap<String, Arc<EndpointMetrics>>>,
+}
+
+impl ApiMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed request
+    pub fn record(&self, endpoint: &str, status: u16, latency: Duration) {
+        let existing = self.endpoints.read().unwrap().get(endpoint).cloned();
+        let metrics = match existing {
+            Some(metrics) => metrics,
+            None => Arc::clone(self.endpoints.write().unwrap().entry(endpoint.to_string()).or_default()),
+        };
+        metrics.record(status, latency);
+    }
+
+    /// Current figures keyed by endpoint ("GET /api/status")
+    pub fn snapshot(&self) -> BTreeMap<String, EndpointSnapshot> {
+        self.endpoints.read().unwrap().iter()
+            .map(|(endpoint, metrics)| (endpoint.clone(), metrics.snapshot()))
+            .collect()
+    }
+}
+
+/// Cap on requests being handled at the same time
+#[derive(Debug, Default)]
+pub struct RequestLimit {
+    /// Most requests in flight at once (0 = unlimited)
+    max: usize,
+    in_flight: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Held while a request is in flight
+pub struct RequestPermit<'a>(&'a RequestLimit);
+
+impl Drop for RequestPermit<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Request concurrency for the diagnostics endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcurrencySnapshot {
+    /// Configured limit (None = unlimited)
+    pub max_in_flight: Option<usize>,
+    pub in_flight: usize,
+    /// Requests turned away since startup
+    pub rejected: u64,
+}
+
+impl RequestLimit {
+    pub fn new(max: usize) -> Self {
+        Self { max, ..Self::default() }
+    }
+
+    /// Admit a request, or None when the limit is reached
+    pub fn try_acquire(&self) -> Option<RequestPermit<'_>> {
+        let previous = self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let permit = RequestPermit(self);
+        if self.max > 0 && previous >= self.max {
+            drop(permit);
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(permit)
+    }
+
+    pub fn snapshot(&self) -> ConcurrencySnapshot {
+        ConcurrencySnapshot {
+            max_in_flight: (self.max > 0).then_some(self.max),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*!
  * Data Models for PDM Backend
  * 
  * This module defines all the data structures used throughout the backend:
@@ -8,9 +8,32 @@
  * - Hardware communication protocols
  */
 
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::config::{
+    AnomalyConfig, CheckAction, Config, CurrentRange, DeratingConfig, DemoPattern, EnergyConfig, OvercurrentMode, ParallelGroupConfig,
+    Precondition, SafetyConfig, SwitchRateConfig, UnitIdentity,
+};
+use crate::precision;
+
+/// Number of status transitions kept per channel
+const TRANSITION_LOG_CAPACITY: usize = 50;
+/// Share of an expected current range a flagged channel must come back
+/// inside by before the flag clears, so a current hovering at an edge is
+/// not reported again on every crossing
+const EXPECTED_RANGE_HYSTERESIS: f32 = 0.05;
+/// ON/OFF switches remembered per channel for the switching rate
+pub(crate) const SWITCH_LOG_CAPACITY: usize = 256;
+
+/// Names of the standard 8-channel loadout
+const DEFAULT_CHANNEL_NAMES: [&str; 8] = [
+    "FUEL PUMP", "IGNITION", "COOLING FAN", "HEADLIGHTS",
+    "ECU MAIN", "SPARE 1", "SPARE 2", "SPARE 3"
+];
 
 /// Represents the status of a single PDM channel
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,20 +42,400 @@ pub struct Channel {
     pub ch: u8,
     /// Human-readable channel name
     pub name: String,
+    /// Display category for UI grouping, from the channel loadout
+    #[serde(default)]
+    pub category: Option<String>,
     /// Current voltage reading (V)
+    #[serde(serialize_with = "precision::voltage")]
     pub voltage: f32,
     /// Current amperage reading (A)
+    #[serde(serialize_with = "precision::current")]
     pub current: f32,
+    /// Output FET temperature (°C), on hardware with per-channel sensing
+    #[serde(default, serialize_with = "precision::temperature_option")]
+    pub temperature: Option<f32>,
     /// Channel status (ON/OFF)
     pub status: ChannelStatus,
     /// Maximum current limit for this channel (A)
     pub current_limit: f32,
+    /// Limit enforced right now, after temperature derating (A)
+    #[serde(default)]
+    pub effective_current_limit: f32,
+    /// Temperature derating curve for the current limit
+    #[serde(default)]
+    pub derating: Option<DeratingConfig>,
+    /// Maximum current the hardware output is rated for (A), if reported
+    #[serde(default)]
+    pub hardware_rating: Option<f32>,
+    /// Highest current limit that may be configured (A), from the channel loadout
+    #[serde(default)]
+    pub max_current_limit: Option<f32>,
+    /// Current is above the soft warning level but below the hard limit
+    #[serde(default)]
+    pub warning: bool,
+    /// Seconds until the current reaches `effective_current_limit` at its
+    /// recent rate of rise (None unless ON and rising)
+    #[serde(default)]
+    pub time_to_limit_seconds: Option<f32>,
+    /// Moving average of current while ON (A)
+    #[serde(default, serialize_with = "precision::current")]
+    pub average_current: f32,
+    /// Weight of each new reading in `display_current` (from the loadout)
+    #[serde(skip)]
+    pub display_smoothing: Option<f32>,
+    /// `current` smoothed for display, on channels with display smoothing (A);
+    /// `current` itself stays the raw reading that protection acts on
+    #[serde(default, serialize_with = "precision::current_option")]
+    pub display_current: Option<f32>,
+    /// Current deviates sharply from its recent average
+    #[serde(default)]
+    pub anomaly: bool,
+    /// Current of a healthy load while ON (A), from the loadout
+    #[serde(default)]
+    pub expected_current: Option<CurrentRange>,
+    /// ON outside the inrush window with the current outside `expected_current`
+    #[serde(default)]
+    pub out_of_range: bool,
     /// Fault status
     pub fault: Option<ChannelFault>,
+    /// Whether the fault came from the supply or from the channel itself
+    #[serde(default)]
+    pub fault_source: Option<FaultSource>,
+    /// Voltage and current (V, A) read at the moment of the most recent fault
+    #[serde(default)]
+    pub fault_snapshot: Option<(f32, f32)>,
+    /// Automatic re-enable attempts made since the last operator command
+    #[serde(default)]
+    pub retry_attempts: u32,
+    /// When the next automatic re-enable is due
+    #[serde(skip)]
+    pub retry_at: Option<Instant>,
+    /// When the channel entered its current fault
+    #[serde(skip)]
+    pub fault_entered_at: Option<Instant>,
+    /// Status the channel had before its current fault
+    #[serde(default)]
+    pub prior_status: Option<ChannelStatus>,
+    /// Deadline for a transient supply fault to clear and auto-recover
+    #[serde(skip)]
+    pub recover_until: Option<Instant>,
+    /// Breaker channels can be cleared in software; fuse channels need a fuse replacement
+    #[serde(default = "default_true")]
+    pub resettable: bool,
+    /// A fuse channel tripped on overcurrent and is waiting for a replacement fuse
+    #[serde(default)]
+    pub fuse_blown: bool,
+    /// A command for this channel has been sent and not yet acknowledged
+    #[serde(default)]
+    pub pending: bool,
+    /// Number of switch commands applied to this channel through the API
+    #[serde(default)]
+    pub command_seq: u64,
+    /// Locked in its state by a technician; automatic control leaves it alone
+    #[serde(default)]
+    pub overridden: bool,
+    /// Channels that always share this channel's on/off state
+    #[serde(default)]
+    pub mirrored_with: Vec<u8>,
+    /// Critical channels are left alone by a selective reset-all
+    #[serde(default)]
+    pub critical: bool,
+    /// Switched on by the power-on sequence
+    #[serde(default)]
+    pub startup: bool,
+    /// Channels powered on before this one
+    #[serde(default)]
+    pub depends_on: Vec<u8>,
+    /// Conditions checked before the API switches this channel on
+    #[serde(default)]
+    pub preconditions: Vec<Precondition>,
+    /// Expected power draw used for power budgeting (W)
+    #[serde(default)]
+    pub power_budget_watts: Option<f32>,
+    /// Time after switch-on during which overcurrent is ignored (ms)
+    #[serde(default)]
+    pub inrush_ignore_ms: u64,
+    /// Least current a healthy load draws while ON (A), from the loadout
+    #[serde(default)]
+    pub min_current: Option<f32>,
+    /// When an ON channel dropped below `min_current`
+    #[serde(skip)]
+    pub low_current_since: Option<Instant>,
+    /// Inductive load: turn-off ramps the PWM duty down over this long (ms, 0 = hard switch)
+    #[serde(default)]
+    pub ramp_down_ms: u64,
+    /// PWM duty (%) while a turn-off ramp is in progress
+    #[serde(default)]
+    pub ramp_duty: Option<u8>,
+    /// Shortest ON time before an operator switch-off is accepted (ms, 0 = none)
+    #[serde(default)]
+    pub min_on_ms: u64,
+    /// Shortest OFF time before an operator switch-on is accepted (ms, 0 = none)
+    #[serde(default)]
+    pub min_off_ms: u64,
+    /// Trip on overcurrent or hold the current at the limit
+    #[serde(default)]
+    pub overcurrent_mode: OvercurrentMode,
+    /// PWM duty (%) the current limiter has reduced the output to
+    #[serde(default)]
+    pub limit_duty: Option<u8>,
+    /// The output is being held at its current limit
+    #[serde(default)]
+    pub current_limiting: bool,
+    /// The limiter could not set its duty, so overcurrent trips the channel as in Trip mode
+    #[serde(default)]
+    pub limiter_failed: bool,
+    /// Current-limit learning in progress
+    #[serde(default)]
+    pub learning: Option<LearnSession>,
+    /// Bench test mode: overcurrent does not trip the channel
+    #[serde(default)]
+    pub test_mode: bool,
+    /// When test mode expires
+    #[serde(skip)]
+    pub test_mode_until: Option<Instant>,
+    /// Switched off by thermal load shedding, to be restored once the PDM cools
+    #[serde(default)]
+    pub shed: bool,
+    /// Switched off by battery load shedding, to be restored once the charge recovers
+    #[serde(default)]
+    pub battery_shed: bool,
+    /// When the channel was shed
+    #[serde(skip)]
+    pub shed_at: Option<Instant>,
+    /// When the channel was last switched on
+    #[serde(skip)]
+    pub switched_on_at: Option<Instant>,
+    /// When the channel last left ON, for whatever reason
+    #[serde(skip)]
+    pub switched_off_at: Option<Instant>,
+    /// When the status last changed
+    #[serde(default)]
+    pub last_transition: Option<DateTime<Utc>>,
+    /// Recent status transitions, oldest first
+    #[serde(skip)]
+    pub transitions: VecDeque<ChannelTransition>,
+    /// When the channel recently switched ON or OFF, oldest first
+    #[serde(skip)]
+    pub switch_times: VecDeque<Instant>,
+    /// Switched more often than `safety.switch_rate` allows
+    #[serde(default)]
+    pub switch_rate_exceeded: bool,
     /// Last update timestamp
     pub last_update: DateTime<Utc>,
 }
 
+impl Channel {
+    /// Create a channel that starts OFF with no fault
+    pub fn new(ch: u8, name: impl Into<String>, current_limit: f32) -> Self {
+        Self {
+            ch,
+            name: name.into(),
+            category: None,
+            voltage: 0.0,
+            current: 0.0,
+            temperature: None,
+            status: ChannelStatus::Off,
+            current_limit,
+            effective_current_limit: current_limit,
+            derating: None,
+            hardware_rating: None,
+            max_current_limit: None,
+            warning: false,
+            time_to_limit_seconds: None,
+            average_current: 0.0,
+            display_smoothing: None,
+            display_current: None,
+            anomaly: false,
+            expected_current: None,
+            out_of_range: false,
+            fault: None,
+            fault_source: None,
+            fault_snapshot: None,
+            retry_attempts: 0,
+            retry_at: None,
+            fault_entered_at: None,
+            prior_status: None,
+            recover_until: None,
+            resettable: true,
+            fuse_blown: false,
+            pending: false,
+            command_seq: 0,
+            overridden: false,
+            mirrored_with: Vec::new(),
+            critical: false,
+            startup: false,
+            depends_on: Vec::new(),
+            preconditions: Vec::new(),
+            power_budget_watts: None,
+            inrush_ignore_ms: 0,
+            min_current: None,
+            low_current_since: None,
+            ramp_down_ms: 0,
+            ramp_duty: None,
+            min_on_ms: 0,
+            min_off_ms: 0,
+            overcurrent_mode: OvercurrentMode::Trip,
+            limit_duty: None,
+            current_limiting: false,
+            limiter_failed: false,
+            learning: None,
+            test_mode: false,
+            shed: false,
+            battery_shed: false,
+            shed_at: None,
+            test_mode_until: None,
+            switched_on_at: None,
+            switched_off_at: None,
+            last_transition: None,
+            transitions: VecDeque::new(),
+            switch_times: VecDeque::new(),
+            switch_rate_exceeded: false,
+            last_update: Utc::now(),
+        }
+    }
+    
+    /// Change the channel status, recording a transition if it actually changed
+    pub fn set_status(&mut self, status: ChannelStatus, trigger: TransitionTrigger) {
+        if self.status != status {
+            let timestamp = Utc::now();
+            if self.transitions.len() == TRANSITION_LOG_CAPACITY {
+                self.transitions.pop_front();
+            }
+            self.transitions.push_back(ChannelTransition {
+                timestamp,
+                from: self.status.clone(),
+                to: status.clone(),
+                trigger,
+            });
+            self.last_transition = Some(timestamp);
+            if self.status == ChannelStatus::On {
+                self.switched_off_at = Some(Instant::now());
+            }
+            if self.status == ChannelStatus::On || status == ChannelStatus::On {
+                if self.switch_times.len() == SWITCH_LOG_CAPACITY {
+                    self.switch_times.pop_front();
+                }
+                self.switch_times.push_back(Instant::now());
+            }
+        }
+        if status != ChannelStatus::On {
+            // The limiter starts over at full duty on the next switch-on
+            self.limit_duty = None;
+            self.current_limiting = false;
+            self.limiter_failed = false;
+            self.low_current_since = None;
+        }
+        self.status = status;
+    }
+    
+    /// PWM duty (%) that holds the current at the effective limit
+    ///
+    /// The load's full-duty current is estimated from the reading at the
+    /// present duty; 100 means no limiting is needed.
+    pub fn limiting_duty(&self) -> u8 {
+        let duty = f32::from(self.limit_duty.unwrap_or(100)).max(1.0) / 100.0;
+        let full_current = self.current / duty;
+        if full_current <= self.effective_current_limit {
+            return 100;
+        }
+        (100.0 * self.effective_current_limit / full_current).round().clamp(0.0, 100.0) as u8
+    }
+    
+    /// The most recent `limit` transitions, oldest first
+    pub fn recent_transitions(&self, limit: usize) -> Vec<ChannelTransition> {
+        let start = self.transitions.len().saturating_sub(limit);
+        self.transitions.iter().skip(start).cloned().collect()
+    }
+    
+    /// Estimated power draw (W): the channel's budget if set, else its
+    /// measured current while ON, or the most it may draw once switched on
+    /// (the top of `expected_current`, else the current limit)
+    pub fn estimated_power(&self, input_voltage: f32) -> f32 {
+        if let Some(budget) = self.power_budget_watts {
+            return budget;
+        }
+        let current = if self.status == ChannelStatus::On {
+            self.current
+        } else {
+            self.expected_current.map_or(self.current_limit, |range| range.max)
+        };
+        current * input_voltage
+    }
+    
+    /// Whether the channel is still inside its inrush window after switch-on
+    pub fn in_inrush_window(&self, now: Instant) -> bool {
+        self.switched_on_at.is_some_and(|at| {
+            now.duration_since(at) < std::time::Duration::from_millis(self.inrush_ignore_ms)
+        })
+    }
+    
+    /// Time left before switching the channel `on`/off honours its minimum
+    /// on/off time (None if the switch may happen now or changes nothing)
+    pub fn dwell_remaining(&self, on: bool, now: Instant) -> Option<Duration> {
+        let is_on = self.status == ChannelStatus::On;
+        let (since, min_ms) = match on {
+            true if !is_on => (self.switched_off_at?, self.min_off_ms),
+            false if is_on => (self.switched_on_at?, self.min_on_ms),
+            _ => return None,
+        };
+        Duration::from_millis(min_ms)
+            .checked_sub(now.saturating_duration_since(since))
+            .filter(|remaining| !remaining.is_zero())
+    }
+    
+    /// Highest current limit that may be set: the loadout maximum or the
+    /// hardware rating, whichever is lower (None if neither is known)
+    pub fn settable_limit_max(&self) -> Option<f32> {
+        match (self.max_current_limit, self.hardware_rating) {
+            (Some(max), Some(rating)) => Some(max.min(rating)),
+            (max, rating) => max.or(rating),
+        }
+    }
+    
+    /// Check a requested current limit
+    ///
+    /// A non-finite, zero or negative limit, or one above what the channel may
+    /// carry, would effectively disable overcurrent protection.
+    pub fn validate_current_limit(&self, limit: f32) -> Result<(), String> {
+        let max = self.settable_limit_max();
+        if limit.is_finite() && limit > 0.0 && max.is_none_or(|max| limit <= max) {
+            return Ok(());
+        }
+        let range = match max {
+            Some(max) => format!("greater than 0 and at most {}A", max),
+            None => "a finite number greater than 0".to_string(),
+        };
+        Err(format!("Current limit {} for channel {} must be {}", limit, self.ch, range))
+    }
+    
+    /// Current limit after derating at `temperature`
+    pub fn derated_limit(&self, temperature: f32, max_temperature: f32) -> f32 {
+        let Some(derating) = self.derating else {
+            return self.current_limit;
+        };
+        
+        let span = max_temperature - derating.start_temperature;
+        let progress = if span > 0.0 {
+            ((temperature - derating.start_temperature) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.current_limit * (1.0 - progress * (1.0 - derating.min_fraction))
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Default name for a channel that isn't named in the loadout
+fn default_channel_name(ch: u8) -> String {
+    DEFAULT_CHANNEL_NAMES.get((ch as usize).wrapping_sub(1))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("CHANNEL {}", ch))
+}
+
 /// Channel status enumeration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ChannelStatus {
@@ -44,8 +447,40 @@ pub enum ChannelStatus {
     Fault,
 }
 
-/// Channel fault types
+/// What caused a channel status change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TransitionTrigger {
+    /// Operator command through the API
+    Api,
+    /// Scheduled action
+    Schedule,
+    /// Fault handling (trip or automatic retry)
+    Safety,
+    /// Emergency shutdown
+    Emergency,
+    /// Ignition switched off
+    Ignition,
+    /// Safety interlock opened
+    Interlock,
+    /// Energy budget used up
+    EnergyBudget,
+    /// Thermal load shedding or its restore
+    LoadShedding,
+    /// Simulator demo mode
+    Demo,
+}
+
+/// A single channel status change
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTransition {
+    pub timestamp: DateTime<Utc>,
+    pub from: ChannelStatus,
+    pub to: ChannelStatus,
+    pub trigger: TransitionTrigger,
+}
+
+/// Channel fault types
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ChannelFault {
     Overcurrent,
     Overvoltage,
@@ -53,6 +488,103 @@ pub enum ChannelFault {
     ShortCircuit,
     OpenLoad,
     Overtemperature,
+    /// Current flowing back into the channel (regenerating load, wiring fault)
+    ReverseCurrent,
+    /// A paralleled channel's current drifted away from the rest of its group
+    CurrentImbalance,
+    /// Emergency shutdown could not confirm the channel is OFF
+    ShutdownUnconfirmed,
+}
+
+/// Where a channel fault came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FaultSource {
+    /// The input supply was out of range (system-wide under/overvoltage)
+    System,
+    /// The channel's own load, wiring or output
+    Channel,
+}
+
+/// Peak-current observation used to suggest a channel's current limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnSession {
+    pub started: DateTime<Utc>,
+    /// Observation window (ms); sampling stops once it has elapsed
+    pub window_ms: u64,
+    /// Suggested limit = peak × margin
+    pub margin: f32,
+    /// Apply the suggested limit when learning is stopped
+    pub auto_apply: bool,
+    /// Highest current seen while running normally (A)
+    #[serde(serialize_with = "precision::current")]
+    pub peak_current: f32,
+    /// Monitoring samples taken while ON outside the inrush window
+    pub samples: u32,
+    /// The channel faulted during the window, so its peak cannot be trusted
+    pub faulted: bool,
+    #[serde(skip)]
+    pub started_at: Option<Instant>,
+}
+
+impl LearnSession {
+    pub fn new(window_ms: u64, margin: f32, auto_apply: bool) -> Self {
+        Self {
+            started: Utc::now(),
+            window_ms,
+            margin,
+            auto_apply,
+            peak_current: 0.0,
+            samples: 0,
+            faulted: false,
+            started_at: Some(Instant::now()),
+        }
+    }
+    
+    /// Whether the observation window has elapsed
+    pub fn window_elapsed(&self, now: Instant) -> bool {
+        self.started_at
+            .is_some_and(|at| now.saturating_duration_since(at) >= std::time::Duration::from_millis(self.window_ms))
+    }
+    
+    /// Peak × margin, rounded up to 0.1A
+    pub fn suggested_limit(&self) -> f32 {
+        (self.peak_current * self.margin * 10.0).ceil() / 10.0
+    }
+}
+
+/// API request to start learning a channel's current limit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LearnRequest {
+    /// Apply the suggestion when learning is stopped
+    #[serde(default)]
+    pub auto_apply: bool,
+    /// Overrides `learn.window_ms`
+    #[serde(default)]
+    pub window_ms: Option<u64>,
+    /// Overrides `learn.margin`
+    #[serde(default)]
+    pub margin: Option<f32>,
+}
+
+/// API request to start the simulator demo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DemoRequest {
+    /// Overrides `simulation.demo_pattern`
+    #[serde(default)]
+    pub pattern: Option<DemoPattern>,
+    /// Overrides `simulation.demo_step_ms`
+    #[serde(default)]
+    pub step_ms: Option<u64>,
+}
+
+/// API request to suspend a channel's overcurrent tripping for bench testing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestModeRequest {
+    /// How long test mode lasts, up to `test_mode.max_duration_ms`
+    pub duration_ms: u64,
+    /// Must be true: acknowledges that the channel is unprotected
+    #[serde(default)]
+    pub confirm: bool,
 }
 
 /// Overall PDM system state
@@ -61,19 +593,272 @@ pub struct PdmState {
     /// All 8 channels
     pub channels: HashMap<u8, Channel>,
     /// Input voltage from main power supply
+    #[serde(serialize_with = "precision::voltage")]
     pub input_voltage: f32,
+    /// Peak-to-peak input voltage ripple over the recent window (V)
+    #[serde(default, serialize_with = "precision::voltage")]
+    pub input_ripple_pp: f32,
+    /// Standard deviation of the input voltage over the recent window (V)
+    #[serde(default, serialize_with = "precision::voltage")]
+    pub input_ripple_std: f32,
     /// Total current consumption across all channels
+    #[serde(serialize_with = "precision::current")]
     pub total_current: f32,
+    /// Supply-side current into the PDM, including its own draw and losses (A)
+    #[serde(default, serialize_with = "precision::current")]
+    pub input_current: f32,
+    /// Output power / input power (None while nothing is drawn)
+    #[serde(default)]
+    pub efficiency: Option<f32>,
     /// PDM internal temperature (°C)
+    #[serde(serialize_with = "precision::temperature")]
     pub temperature: f32,
     /// System status
     pub system_status: SystemStatus,
+    /// Emergency status is latched until an operator resets the system
+    #[serde(default)]
+    pub emergency_latched: bool,
+    /// Software master switch; while false no channel may be switched on
+    #[serde(default = "default_true")]
+    pub master_enabled: bool,
+    /// Firmware version reported by the hardware
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    /// Whether the firmware meets `hardware.min_firmware_version` (None = not checked)
+    #[serde(default)]
+    pub firmware_compatible: Option<bool>,
+    /// When the controlling client last sent a heartbeat (None = not armed)
+    #[serde(skip)]
+    pub last_heartbeat: Option<Instant>,
+    /// Time left before a missed heartbeat triggers shutdown (ms), while armed
+    #[serde(default)]
+    pub heartbeat_remaining_ms: Option<u64>,
+    /// Ignition input state (None = ignition input not enabled)
+    #[serde(default)]
+    pub ignition_on: Option<bool>,
+    /// When the ignition went off, until the switch-off sequence has run
+    #[serde(skip)]
+    pub ignition_off_at: Option<Instant>,
+    /// Safety interlock state (None = interlock input not enabled)
+    #[serde(default)]
+    pub interlock_closed: Option<bool>,
+    /// Combined readings of each configured parallel group
+    #[serde(default)]
+    pub parallel_groups: Vec<ParallelGroupStatus>,
+    /// Energy drawn against the configured budget
+    #[serde(default)]
+    pub energy: EnergyStatus,
+    /// Estimated battery state of charge (%), when `battery` is enabled
+    #[serde(default)]
+    pub battery_soc_percent: Option<f32>,
+    /// Hardware reads whose last attempt failed; their readings are out of date
+    #[serde(default)]
+    pub stale_readings: BTreeSet<ReadKind>,
+    /// Seconds until the total current reaches `safety.max_total_current` at
+    /// its recent rate of rise (None unless rising)
+    #[serde(default)]
+    pub time_to_limit_seconds: Option<f32>,
     /// Last system update timestamp
     pub last_update: DateTime<Utc>,
 }
 
-/// System-wide status
+/// A periodic read of the hardware state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadKind {
+    /// Input voltage and total current
+    System,
+    /// Per-channel readings
+    Channels,
+    /// PDM temperature
+    Temperature,
+}
+
+/// Why channels may not be switched on now (see `PdmState::check_switch_on`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitchOnBlock {
+    MasterDisabled,
+    /// An emergency shutdown is latched until the outputs are reset
+    EmergencyLatched,
+    InterlockOpen,
+    /// The energy budget is used up and a channel is not critical
+    EnergyDepleted,
+    FuseBlown(u8),
+    /// Turn-on preconditions that do not hold: channel, precondition and reason
+    Preconditions(Vec<(u8, Precondition, String)>),
+    /// Would exceed `safety.max_total_power` (only with `power_budget_check = Refuse`)
+    PowerBudget { projected_power: f32, max_total_power: f32 },
+    /// Would exceed `safety.max_active_channels`
+    ActiveChannels { active_channels: usize, max_active_channels: usize },
+}
+
+impl std::fmt::Display for SwitchOnBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwitchOnBlock::MasterDisabled => write!(f, "the master switch is disabled"),
+            SwitchOnBlock::EmergencyLatched => write!(f, "an emergency shutdown is latched"),
+            SwitchOnBlock::InterlockOpen => write!(f, "the safety interlock is open"),
+            SwitchOnBlock::EnergyDepleted => write!(f, "the energy budget is used up"),
+            SwitchOnBlock::FuseBlown(ch) => write!(f, "channel {} fuse is blown", ch),
+            SwitchOnBlock::Preconditions(unmet) => {
+                let reasons: Vec<String> = unmet.iter().map(|(ch, _, reason)| format!("channel {}: {}", ch, reason)).collect();
+                write!(f, "turn-on preconditions are not met ({})", reasons.join("; "))
+            }
+            SwitchOnBlock::PowerBudget { projected_power, max_total_power } => {
+                write!(f, "{:.0}W would exceed the {:.0}W power budget", projected_power, max_total_power)
+            }
+            SwitchOnBlock::ActiveChannels { active_channels, max_active_channels } => {
+                write!(f, "{} channels are already ON (max {})", active_channels, max_active_channels)
+            }
+        }
+    }
+}
+
+/// Energy drawn since the counter was last reset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyStatus {
+    /// Wh drawn since `since`
+    pub consumed_wh: f64,
+    /// Configured `energy.max_total_energy_wh`
+    pub budget_wh: Option<f32>,
+    pub remaining_wh: Option<f64>,
+    /// Consumption has reached `energy.warn_fraction` of the budget
+    pub low: bool,
+    /// The budget is used up: non-critical channels were switched off and
+    /// stay off until the counter is reset
+    pub depleted: bool,
+    pub since: DateTime<Utc>,
+}
+
+impl Default for EnergyStatus {
+    fn default() -> Self {
+        Self {
+            consumed_wh: 0.0,
+            budget_wh: None,
+            remaining_wh: None,
+            low: false,
+            depleted: false,
+            since: Utc::now(),
+        }
+    }
+}
+
+impl EnergyStatus {
+    /// Add `power` drawn for `seconds`
+    pub fn accumulate(&mut self, power: f32, seconds: f64) {
+        self.consumed_wh += f64::from(power.max(0.0)) * seconds / 3600.0;
+    }
+    
+    /// Compare the consumption with the budget, returning whether it just
+    /// became low and whether it just became depleted
+    pub fn check_budget(&mut self, config: &EnergyConfig) -> (bool, bool) {
+        let (was_low, was_depleted) = (self.low, self.depleted);
+        self.budget_wh = config.max_total_energy_wh;
+        self.remaining_wh = self.budget_wh.map(|budget| (f64::from(budget) - self.consumed_wh).max(0.0));
+        let used = self.budget_wh.map_or(0.0, |budget| self.consumed_wh / f64::from(budget));
+        self.low = self.budget_wh.is_some() && used >= f64::from(config.warn_fraction);
+        self.depleted = self.budget_wh.is_some() && used >= 1.0;
+        (self.low && !was_low, self.depleted && !was_depleted)
+    }
+    
+    /// Start counting from zero
+    pub fn reset(&mut self) {
+        *self = Self { budget_wh: self.budget_wh, remaining_wh: self.budget_wh.map(f64::from), ..Self::default() };
+    }
+}
+
+/// A parallel group reported as one virtual channel
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelGroupStatus {
+    pub name: String,
+    pub channels: Vec<u8>,
+    /// ON only while every member is on; FAULT if any member is faulted
+    pub status: ChannelStatus,
+    /// Mean member voltage (V)
+    #[serde(serialize_with = "precision::voltage")]
+    pub voltage: f32,
+    /// Summed member current (A)
+    #[serde(serialize_with = "precision::current")]
+    pub current: f32,
+    /// Largest member deviation from the mean current, as a fraction of the
+    /// mean (only while all members are on and above `min_current`)
+    pub imbalance: Option<f32>,
+}
+
+impl ParallelGroupStatus {
+    /// Combine the readings of a group's members
+    pub fn measure(group: &ParallelGroupConfig, channels: &HashMap<u8, Channel>) -> Self {
+        let members: Vec<&Channel> = group.channels.iter().filter_map(|ch| channels.get(ch)).collect();
+        let status = if members.iter().any(|channel| channel.status == ChannelStatus::Fault) {
+            ChannelStatus::Fault
+        } else if !members.is_empty() && members.iter().all(|channel| channel.status == ChannelStatus::On) {
+            ChannelStatus::On
+        } else {
+            ChannelStatus::Off
+        };
+        let voltage = members.iter().map(|channel| channel.voltage).sum::<f32>() / members.len().max(1) as f32;
+        let current = members.iter().map(|channel| channel.current).sum();
+        
+        Self {
+            name: group.name.clone(),
+            channels: group.channels.clone(),
+            imbalance: current_imbalance(group, channels).map(|(imbalance, _)| imbalance),
+            status,
+            voltage,
+            current,
+        }
+    }
+}
+
+/// Time from `last` to `now` for accumulating energy or ON-time
+///
+/// Measured on the monotonic clock, so wall-clock changes (an NTP sync at
+/// boot) can't affect it; a `now` before `last` counts as no time and a gap
+/// longer than `max` counts as `max`.
+pub fn time_step(last: Instant, now: Instant, max: Duration) -> Duration {
+    now.saturating_duration_since(last).min(max)
+}
+
+/// Imbalance of a parallel group and the member furthest from the mean
+///
+/// Only judged while every member is on and the group carries at least
+/// `min_current`; below that the relative spread is mostly noise.
+pub fn current_imbalance(group: &ParallelGroupConfig, channels: &HashMap<u8, Channel>) -> Option<(f32, u8)> {
+    let members: Vec<&Channel> = group.channels.iter().filter_map(|ch| channels.get(ch)).collect();
+    if members.len() < 2 || members.iter().any(|channel| channel.status != ChannelStatus::On) {
+        return None;
+    }
+    let total: f32 = members.iter().map(|channel| channel.current).sum();
+    if total < group.min_current {
+        return None;
+    }
+    
+    let mean = total / members.len() as f32;
+    members.iter()
+        .map(|channel| ((channel.current - mean).abs() / mean, channel.ch))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Manual override: lock a channel ON or OFF
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OverrideRequest {
+    pub on: bool,
+}
+
+/// Simulated ignition input
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IgnitionRequest {
+    pub on: bool,
+}
+
+/// Simulated safety interlock input
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct InterlockRequest {
+    pub closed: bool,
+}
+
+/// System-wide status
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SystemStatus {
     Normal,
     Warning,
@@ -81,15 +866,39 @@ pub enum SystemStatus {
     Emergency,
 }
 
+impl SystemStatus {
+    /// Numeric severity, higher is worse
+    pub fn severity(&self) -> u8 {
+        match self {
+            SystemStatus::Normal => 0,
+            SystemStatus::Warning => 1,
+            SystemStatus::Fault => 2,
+            SystemStatus::Emergency => 3,
+        }
+    }
+}
+
 /// API request to control a channel
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelControlRequest {
     pub channel: u8,
     pub action: ChannelAction,
+    /// Channel switched off in the same request to make room under `max_active_channels`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swap_off: Option<u8>,
+    /// Refuse the command unless the channel's `command_seq` is still this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_seq: Option<u64>,
+}
+
+/// Optional sequence check for commands without a body (`?expected_seq=N`)
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ExpectedSeqQuery {
+    pub expected_seq: Option<u64>,
 }
 
 /// Channel control actions
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ChannelAction {
     TurnOn,
     TurnOff,
@@ -98,19 +907,109 @@ pub enum ChannelAction {
 }
 
 /// API request for emergency shutdown
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmergencyShutdownRequest {
     pub reason: String,
+    /// From `GET /emergency-shutdown/token`, when `safety.emergency_confirmation` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Optional body for the reset-all endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResetAllRequest {
+    /// Must be true for the reset to go ahead
+    #[serde(default)]
+    pub confirm: bool,
+    /// Channels to leave untouched
+    #[serde(default)]
+    pub exclude: Vec<u8>,
+}
+
+/// Outcome of an emergency shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Every channel was confirmed OFF by the hardware
+    pub confirmed_all_off: bool,
+    /// The broadcast shutdown command was acknowledged
+    pub broadcast_acknowledged: bool,
+    /// Channels whose OFF state could not be confirmed
+    pub unconfirmed_channels: Vec<u8>,
+}
+
+/// Simulated reading that overrides the random generator for a channel
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SimReading {
+    pub voltage: f32,
+    pub current: f32,
 }
 
 /// API response for system status
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStatusResponse {
+    /// Unit that produced the response
+    #[serde(default)]
+    pub unit: UnitIdentity,
     pub pdm_state: PdmState,
+    /// Channels currently ON
+    #[serde(default)]
+    pub active_channels: usize,
+    /// Configured `safety.max_active_channels`
+    #[serde(default)]
+    pub max_active_channels: Option<usize>,
     pub uptime_seconds: u64,
     pub api_version: String,
 }
 
+/// Status of one downstream unit in aggregator mode
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetUnitStatus {
+    /// Base URL of the unit's backend
+    pub url: String,
+    /// Whether the last poll succeeded
+    pub online: bool,
+    /// Last status reported by the unit (None if never reached)
+    pub status: Option<SystemStatusResponse>,
+    /// Error from the last failed poll
+    pub error: Option<String>,
+    /// Time of the last successful poll
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// API response for the combined fleet view
+#[derive(Debug, Serialize)]
+pub struct FleetStatusResponse {
+    pub units: Vec<FleetUnitStatus>,
+    pub total_units: usize,
+    pub online_units: usize,
+    /// Sum of total current across online units (A)
+    #[serde(serialize_with = "precision::current")]
+    pub total_current: f32,
+    /// Sum of power across online units (W)
+    #[serde(serialize_with = "precision::power")]
+    pub total_power: f32,
+    /// Worst system status among online units
+    pub system_status: SystemStatus,
+}
+
+/// Outcome of commissioning a single channel
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelCommissionResult {
+    pub channel: u8,
+    pub success: bool,
+    pub message: String,
+}
+
+/// API response for hardware commissioning
+#[derive(Debug, Clone, Serialize)]
+pub struct CommissionReport {
+    /// Every channel was acknowledged and the configuration was saved
+    pub success: bool,
+    /// The save-to-non-volatile-memory command was acknowledged
+    pub saved: bool,
+    pub channels: Vec<ChannelCommissionResult>,
+}
+
 /// Hardware communication message
 #[derive(Debug)]
 pub enum HardwareMessage {
@@ -122,8 +1021,23 @@ pub enum HardwareMessage {
         channel: u8,
         limit_amps: f32,
     },
+    /// PWM duty of a channel output (0-100%)
+    SetDuty {
+        channel: u8,
+        duty_percent: u8,
+    },
     EmergencyShutdown,
     RequestStatus,
+    /// Write a channel's configuration to the hardware
+    StoreChannelConfig {
+        channel: u8,
+        name: String,
+        current_limit: f32,
+    },
+    /// Persist the written configuration to non-volatile memory
+    SaveConfig,
+    /// Liveness check; a ready board acknowledges it
+    Ping,
 }
 
 /// Hardware response message
@@ -147,36 +1061,432 @@ pub enum HardwareResponse {
     },
 }
 
+impl Default for PdmState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PdmState {
     /// Create a new PDM state with default values
     pub fn new() -> Self {
-        let mut channels = HashMap::new();
-        
         // Initialize all 8 channels with default values
-        let channel_names = [
-            "FUEL PUMP", "IGNITION", "COOLING FAN", "HEADLIGHTS",
-            "ECU MAIN", "SPARE 1", "SPARE 2", "SPARE 3"
-        ];
-        
-        for i in 1..=8 {
-            channels.insert(i, Channel {
-                ch: i,
-                name: channel_names[(i - 1) as usize].to_string(),
-                voltage: 0.0,
-                current: 0.0,
-                status: ChannelStatus::Off,
-                current_limit: 15.0, // Default 15A limit
-                fault: None,
-                last_update: Utc::now(),
-            });
+        Self::with_channels((1..=8).map(|i| Channel::new(i, default_channel_name(i), 15.0)))
+    }
+    
+    /// Build the PDM state from the configured channel loadout
+    ///
+    /// Fails if a channel is defined twice or lies outside `1..=channel_count`,
+    /// rather than silently dropping entries.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let channel_count = config.hardware.channel_count;
+        let mut seen = HashSet::new();
+        
+        for entry in &config.channels {
+            if entry.ch == 0 || entry.ch > channel_count {
+                bail!("Channel {} in the channel loadout is outside the configured range 1-{}",
+                      entry.ch, channel_count);
+            }
+            if !seen.insert(entry.ch) {
+                bail!("Channel {} is defined more than once in the channel loadout", entry.ch);
+            }
+        }
+        
+        let mut sequenced = HashSet::new();
+        for &ch in &config.sequence.power_off_order {
+            if ch == 0 || ch > channel_count {
+                bail!("Channel {} in sequence.power_off_order is outside the configured range 1-{}",
+                      ch, channel_count);
+            }
+            if !sequenced.insert(ch) {
+                bail!("Channel {} appears more than once in sequence.power_off_order", ch);
+            }
+        }
+        
+        let mut grouped = HashSet::new();
+        for group in &config.mirror_groups {
+            if group.len() < 2 {
+                bail!("Mirror group {:?} needs at least two channels", group);
+            }
+            for &ch in group {
+                if ch == 0 || ch > channel_count {
+                    bail!("Channel {} in mirror group {:?} is outside the configured range 1-{}",
+                          ch, group, channel_count);
+                }
+                if !grouped.insert(ch) {
+                    bail!("Channel {} appears in more than one mirror group", ch);
+                }
+            }
+        }
+        
+        let mut zone_names = HashSet::new();
+        for zone in &config.zones {
+            if zone.name.trim().is_empty() || zone.name.contains('/') {
+                bail!("Zone name {:?} must be non-empty and must not contain '/'", zone.name);
+            }
+            if !zone_names.insert(zone.name.as_str()) {
+                bail!("Zone '{}' is defined more than once", zone.name);
+            }
+            if zone.channels.is_empty() {
+                bail!("Zone '{}' has no channels", zone.name);
+            }
+            if let Some(ch) = zone.channels.iter().find(|&&ch| ch == 0 || ch > channel_count) {
+                bail!("Channel {} in zone '{}' is outside the configured range 1-{}", ch, zone.name, channel_count);
+            }
+        }
+        
+        for group in &config.parallel_groups {
+            if group.channels.len() < 2 {
+                bail!("Parallel group '{}' needs at least two channels", group.name);
+            }
+            if !(group.imbalance_tolerance.is_finite() && group.imbalance_tolerance > 0.0) {
+                bail!("Parallel group '{}' imbalance_tolerance must be positive", group.name);
+            }
+            for &ch in &group.channels {
+                if ch == 0 || ch > channel_count {
+                    bail!("Channel {} in parallel group '{}' is outside the configured range 1-{}",
+                          ch, group.name, channel_count);
+                }
+                if !grouped.insert(ch) {
+                    bail!("Channel {} in parallel group '{}' already belongs to another group", ch, group.name);
+                }
+            }
         }
         
+        for entry in &config.channels {
+            if let Some(ch) = entry.depends_on.iter().find(|&&ch| ch == 0 || ch > channel_count || ch == entry.ch) {
+                bail!("Channel {} depends_on {} which is itself or outside the configured range 1-{}",
+                      entry.ch, ch, channel_count);
+            }
+            for precondition in &entry.preconditions {
+                match *precondition {
+                    Precondition::ChannelOn { channel } if channel == 0 || channel > channel_count || channel == entry.ch => {
+                        bail!("Channel {} precondition ChannelOn {} is itself or outside the configured range 1-{}",
+                              entry.ch, channel, channel_count);
+                    }
+                    Precondition::InputVoltage { min, max } if !(min.is_finite() && max.is_finite() && min < max) => {
+                        bail!("Channel {} precondition InputVoltage needs min < max (got {}-{}V)", entry.ch, min, max);
+                    }
+                    Precondition::TemperatureBelow { max } if !max.is_finite() => {
+                        bail!("Channel {} precondition TemperatureBelow needs a temperature", entry.ch);
+                    }
+                    Precondition::IgnitionOn if !config.ignition.enabled => {
+                        bail!("Channel {} precondition IgnitionOn needs the ignition input enabled", entry.ch);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        let sensors = &config.hardware.current_sensors;
+        if sensors.enabled {
+            let mut seen = HashSet::new();
+            let mut addresses = HashSet::new();
+            for sensor in &sensors.sensors {
+                if sensor.channel == 0 || sensor.channel > channel_count {
+                    bail!("Current sensor channel {} is outside the configured range 1-{}", sensor.channel, channel_count);
+                }
+                if !seen.insert(sensor.channel) {
+                    bail!("Channel {} has more than one current sensor", sensor.channel);
+                }
+                if sensor.address > 0x7f {
+                    bail!("Current sensor address 0x{:x} for channel {} is not a 7-bit I2C address",
+                          sensor.address, sensor.channel);
+                }
+                if !addresses.insert(sensor.address) {
+                    bail!("Two current sensors share I2C address 0x{:02x}", sensor.address);
+                }
+                if !(sensor.shunt_ohms.is_finite() && sensor.shunt_ohms > 0.0) {
+                    bail!("Current sensor shunt_ohms for channel {} must be above 0", sensor.channel);
+                }
+            }
+        }
+        
+        let switch_rate = &config.safety.switch_rate;
+        // The log must hold one switch more than the limit to see it exceeded
+        if switch_rate.enabled && switch_rate.max_switches >= SWITCH_LOG_CAPACITY {
+            bail!("safety.switch_rate.max_switches {} must be below {}",
+                  switch_rate.max_switches, SWITCH_LOG_CAPACITY);
+        }
+        
+        let shedding = &config.safety.thermal_shedding;
+        if shedding.enabled {
+            if shedding.recovery_temperature >= shedding.shed_temperature {
+                bail!("safety.thermal_shedding.recovery_temperature {}°C must be below shed_temperature {}°C",
+                      shedding.recovery_temperature, shedding.shed_temperature);
+            }
+            if let Some(ch) = shedding.channels.iter().find(|&&ch| ch == 0 || ch > channel_count) {
+                bail!("Channel {} in safety.thermal_shedding.channels is outside the configured range 1-{}",
+                      ch, channel_count);
+            }
+        }
+        
+        let battery = &config.battery;
+        if battery.enabled {
+            if !(battery.capacity_ah.is_finite() && battery.capacity_ah > 0.0) {
+                bail!("battery.capacity_ah must be above 0");
+            }
+            if battery.soc_curve.len() < 2 {
+                bail!("battery.soc_curve needs at least two points");
+            }
+            if battery.soc_curve.iter().any(|point| !(0.0..=100.0).contains(&point.soc)) {
+                bail!("battery.soc_curve state of charge must be 0-100%");
+            }
+            if battery.soc_curve.windows(2).any(|pair| pair[1].voltage <= pair[0].voltage || pair[1].soc < pair[0].soc) {
+                bail!("battery.soc_curve must rise in voltage, with state of charge never falling");
+            }
+            if !(0.0..=1.0).contains(&battery.voltage_correction) {
+                bail!("battery.voltage_correction must be 0-1");
+            }
+            if !(battery.recovery_margin.is_finite() && battery.recovery_margin >= 0.0) {
+                bail!("battery.recovery_margin must not be negative");
+            }
+            for stage in &battery.shedding {
+                if !(stage.below_soc > 0.0 && stage.below_soc <= 100.0) {
+                    bail!("battery.shedding below_soc {}% must be above 0 and at most 100", stage.below_soc);
+                }
+                if let Some(ch) = stage.channels.iter().find(|&&ch| ch == 0 || ch > channel_count) {
+                    bail!("Channel {} in battery.shedding is outside the configured range 1-{}", ch, channel_count);
+                }
+            }
+        }
+        
+        let hardware = &config.hardware;
+        if hardware.max_time_step_ms == 0 {
+            bail!("hardware.max_time_step_ms must be positive");
+        }
+        if let Some(interval) = hardware.temperature_interval_ms.filter(|&ms| ms > crate::config::MAX_TEMPERATURE_INTERVAL_MS) {
+            bail!("hardware.temperature_interval_ms {} exceeds the {}ms maximum",
+                  interval, crate::config::MAX_TEMPERATURE_INTERVAL_MS);
+        }
+        // A cap below the update intervals would drop part of every normal step
+        for (field, interval) in [
+            ("status_update_interval_ms", hardware.status_update_interval_ms),
+            ("monitoring_interval_ms", hardware.monitoring_interval_ms),
+        ] {
+            if hardware.max_time_step_ms < interval {
+                bail!("hardware.max_time_step_ms {} must not be below hardware.{} {}",
+                      hardware.max_time_step_ms, field, interval);
+            }
+        }
+        let nominal_voltage = config.simulation.nominal_voltage;
+        if !(nominal_voltage.is_finite() && nominal_voltage > 0.0) {
+            bail!("simulation.nominal_voltage {} must be a positive number of volts", nominal_voltage);
+        }
+        let failures = &config.simulation.failures;
+        if failures.enabled {
+            if !(failures.mtbf_secs.is_finite() && failures.mtbf_secs > 0.0) {
+                bail!("simulation.failures.mtbf_secs must be positive");
+            }
+            if failures.modes.is_empty() {
+                bail!("simulation.failures needs at least one failure mode");
+            }
+            for mode in &failures.modes {
+                if !crate::sim_failures::FAILURE_FAULTS.contains(&mode.fault) {
+                    bail!("Simulated failures cannot produce {:?}; use one of {:?}",
+                          mode.fault, crate::sim_failures::FAILURE_FAULTS);
+                }
+                if !(mode.weight.is_finite() && mode.weight > 0.0) {
+                    bail!("Simulated failure mode {:?} weight must be positive", mode.fault);
+                }
+            }
+            if let Some(ch) = failures.channels.iter().find(|&&ch| ch == 0 || ch > channel_count) {
+                bail!("Channel {} in simulation.failures.channels is outside the configured range 1-{}", ch, channel_count);
+            }
+        }
+        
+        let energy = &config.energy;
+        if let Some(budget) = energy.max_total_energy_wh.filter(|budget| !(budget.is_finite() && *budget > 0.0)) {
+            bail!("energy.max_total_energy_wh {} must be positive", budget);
+        }
+        if !(energy.warn_fraction > 0.0 && energy.warn_fraction <= 1.0) {
+            bail!("energy.warn_fraction {} must be in (0, 1]", energy.warn_fraction);
+        }
+        
+        for derating in config.channels.iter().filter_map(|entry| entry.derating) {
+            if !(derating.min_fraction > 0.0 && derating.min_fraction <= 1.0) {
+                bail!("Derating min_fraction {} must be in (0, 1]", derating.min_fraction);
+            }
+            if derating.start_temperature >= config.safety.max_channel_temperature {
+                bail!("Derating start_temperature {}°C must be below safety.max_channel_temperature {}°C",
+                      derating.start_temperature, config.safety.max_channel_temperature);
+            }
+        }
+        
+        for entry in &config.channels {
+            for (field, value) in [("current_limit", entry.current_limit), ("max_current_limit", entry.max_current_limit)] {
+                if let Some(value) = value.filter(|value| !(value.is_finite() && *value > 0.0)) {
+                    bail!("Channel {} {} {} must be a positive number of amps", entry.ch, field, value);
+                }
+            }
+            if entry.category.as_deref().is_some_and(|category| category.trim().is_empty()) {
+                bail!("Channel {} category must not be empty", entry.ch);
+            }
+            if let Some(smoothing) = entry.display_smoothing.filter(|smoothing| !(*smoothing > 0.0 && *smoothing <= 1.0)) {
+                bail!("Channel {} display_smoothing {} must be above 0 and at most 1", entry.ch, smoothing);
+            }
+            if let Some(range) = entry.expected_current {
+                let limit = entry.current_limit.unwrap_or(config.safety.default_channel_current_limit);
+                if !(range.min.is_finite() && range.max.is_finite() && 0.0 <= range.min && range.min < range.max) {
+                    bail!("Channel {} expected_current needs 0 <= min < max (got {}-{}A)", entry.ch, range.min, range.max);
+                }
+                if range.max >= limit {
+                    bail!("Channel {} expected_current max {}A must be below its {}A current limit", entry.ch, range.max, limit);
+                }
+            }
+            if let Some(min) = entry.min_current {
+                let limit = entry.current_limit.unwrap_or(config.safety.default_channel_current_limit);
+                if !(min.is_finite() && min > 0.0 && min < limit) {
+                    bail!("Channel {} min_current {}A must be above 0 and below its {}A current limit", entry.ch, min, limit);
+                }
+            }
+            if let Some(max) = entry.max_current_limit {
+                let limit = entry.current_limit.unwrap_or(config.safety.default_channel_current_limit);
+                if limit > max {
+                    bail!("Channel {} current_limit {}A exceeds its max_current_limit {}A", entry.ch, limit, max);
+                }
+            }
+        }
+        
+        let default_limit = config.safety.default_channel_current_limit;
+        let mut state = Self::with_channels(
+            (1..=channel_count).map(|i| Channel::new(i, default_channel_name(i), default_limit))
+        );
+        
+        for entry in &config.channels {
+            if let Some(channel) = state.channels.get_mut(&entry.ch) {
+                if let Some(name) = &entry.name {
+                    channel.name = name.clone();
+                }
+                channel.category = entry.category.clone();
+                if let Some(limit) = entry.current_limit {
+                    channel.current_limit = limit;
+                    channel.effective_current_limit = limit;
+                }
+                channel.max_current_limit = entry.max_current_limit;
+                channel.derating = entry.derating;
+                channel.display_smoothing = entry.display_smoothing;
+                channel.expected_current = entry.expected_current;
+                channel.min_current = entry.min_current;
+                channel.critical = entry.critical;
+                channel.startup = entry.startup;
+                channel.depends_on = entry.depends_on.clone();
+                channel.preconditions = entry.preconditions.clone();
+                if let Some(resettable) = entry.resettable {
+                    channel.resettable = resettable;
+                }
+                if let Some(power_budget_watts) = entry.power_budget_watts {
+                    channel.power_budget_watts = Some(power_budget_watts);
+                }
+                if let Some(inrush_ignore_ms) = entry.inrush_ignore_ms {
+                    channel.inrush_ignore_ms = inrush_ignore_ms;
+                }
+                channel.ramp_down_ms = entry.ramp_down_ms.unwrap_or(0);
+                channel.min_on_ms = entry.min_on_ms.unwrap_or(0);
+                channel.min_off_ms = entry.min_off_ms.unwrap_or(0);
+                channel.overcurrent_mode = entry.overcurrent_mode;
+            }
+        }
+        
+        // Paralleled channels switch and fault together just like mirrored ones
+        let groups = config.mirror_groups.iter()
+            .chain(config.parallel_groups.iter().map(|group| &group.channels));
+        for group in groups {
+            for &ch in group {
+                if let Some(channel) = state.channels.get_mut(&ch) {
+                    channel.mirrored_with = group.iter().copied().filter(|&peer| peer != ch).collect();
+                }
+            }
+        }
+        state.update_parallel_groups(&config.parallel_groups);
+        
+        // Reject dependency cycles up front rather than when sequencing
+        let all: Vec<u8> = (1..=channel_count).collect();
+        state.dependency_order(&all)?;
+        
+        Ok(state)
+    }
+    
+    /// Take over the loadout of `loadout`, a state freshly built from a new config
+    ///
+    /// Only what the config decides changes (names, limits, startup states,
+    /// dependencies, groups); status, readings and faults stay. Nothing is
+    /// changed if a new limit is above a channel's hardware rating.
+    pub fn apply_loadout(&mut self, loadout: PdmState) -> Result<()> {
+        for (ch, configured) in &loadout.channels {
+            let Some(channel) = self.channels.get(ch) else {
+                bail!("Channel {} does not exist on this unit", ch);
+            };
+            if let Some(rating) = channel.hardware_rating.filter(|rating| configured.current_limit > *rating) {
+                bail!("Channel {} current_limit {}A exceeds its {}A hardware rating", ch, configured.current_limit, rating);
+            }
+        }
+        
+        for (ch, configured) in loadout.channels {
+            let Some(channel) = self.channels.get_mut(&ch) else { continue };
+            if channel.current_limit != configured.current_limit {
+                channel.effective_current_limit = configured.current_limit;
+            }
+            if channel.display_smoothing.is_none() || configured.display_smoothing.is_none() {
+                channel.display_current = None;
+            }
+            channel.name = configured.name;
+            channel.category = configured.category;
+            channel.current_limit = configured.current_limit;
+            channel.max_current_limit = configured.max_current_limit;
+            channel.derating = configured.derating;
+            channel.display_smoothing = configured.display_smoothing;
+            if channel.expected_current != configured.expected_current {
+                channel.out_of_range = false;
+            }
+            channel.expected_current = configured.expected_current;
+            if channel.min_current != configured.min_current {
+                channel.low_current_since = None;
+            }
+            channel.min_current = configured.min_current;
+            channel.critical = configured.critical;
+            channel.startup = configured.startup;
+            channel.depends_on = configured.depends_on;
+            channel.preconditions = configured.preconditions;
+            channel.resettable = configured.resettable;
+            channel.power_budget_watts = configured.power_budget_watts;
+            channel.inrush_ignore_ms = configured.inrush_ignore_ms;
+            channel.ramp_down_ms = configured.ramp_down_ms;
+            channel.min_on_ms = configured.min_on_ms;
+            channel.min_off_ms = configured.min_off_ms;
+            channel.overcurrent_mode = configured.overcurrent_mode;
+            channel.mirrored_with = configured.mirrored_with;
+        }
+        self.parallel_groups = loadout.parallel_groups;
+        Ok(())
+    }
+    
+    /// Create a state with the given channels and default system readings
+    fn with_channels(channels: impl IntoIterator<Item = Channel>) -> Self {
         Self {
-            channels,
+            channels: channels.into_iter().map(|channel| (channel.ch, channel)).collect(),
             input_voltage: 12.0,
+            input_ripple_pp: 0.0,
+            input_ripple_std: 0.0,
             total_current: 0.0,
+            input_current: 0.0,
+            efficiency: None,
             temperature: 25.0,
             system_status: SystemStatus::Normal,
+            emergency_latched: false,
+            master_enabled: true,
+            firmware_version: None,
+            firmware_compatible: None,
+            last_heartbeat: None,
+            heartbeat_remaining_ms: None,
+            ignition_on: None,
+            ignition_off_at: None,
+            interlock_closed: None,
+            parallel_groups: Vec::new(),
+            energy: EnergyStatus::default(),
+            battery_soc_percent: None,
+            stale_readings: BTreeSet::new(),
+            time_to_limit_seconds: None,
             last_update: Utc::now(),
         }
     }
@@ -194,18 +1504,622 @@ impl PdmState {
     
     /// Emergency shutdown all channels
     pub fn emergency_shutdown(&mut self) {
-        for channel in self.channels.values_mut() {
-            channel.status = ChannelStatus::Off;
+        let channels: Vec<u8> = self.channels.keys().copied().collect();
+        self.shutdown_channels(&channels);
+    }
+    
+    /// Emergency shutdown of some channels (a zone)
+    pub fn shutdown_channels(&mut self, channels: &[u8]) {
+        self.force_off(channels, TransitionTrigger::Emergency);
+    }
+    
+    /// Hold every channel off for an open safety interlock, leaving nothing
+    /// scheduled to come back on by itself
+    pub fn interlock_shutdown(&mut self) {
+        let channels: Vec<u8> = self.channels.keys().copied().collect();
+        self.force_off(&channels, TransitionTrigger::Interlock);
+    }
+    
+    /// Check whether `channels` may be switched on now
+    ///
+    /// The one gate every switch-on goes through, whether from the API, a
+    /// schedule, a shedding restore or the demo, so none of them can bring an
+    /// output up past a limit the others respect. Channels already ON pass
+    /// through; minimum on/off times are left to the callers.
+    pub fn check_switch_on(&self, channels: &[u8], safety: &SafetyConfig) -> Result<(), SwitchOnBlock> {
+        let turning_on: Vec<u8> = channels.iter().copied()
+            .filter(|ch| self.channels.get(ch).is_some_and(|channel| channel.status != ChannelStatus::On))
+            .collect();
+        if turning_on.is_empty() {
+            return Ok(());
+        }
+        
+        if !self.master_enabled {
+            return Err(SwitchOnBlock::MasterDisabled);
+        }
+        if self.emergency_latched {
+            return Err(SwitchOnBlock::EmergencyLatched);
+        }
+        if self.interlock_open() {
+            return Err(SwitchOnBlock::InterlockOpen);
+        }
+        let is_critical = |ch: &u8| self.channels.get(ch).is_some_and(|channel| channel.critical);
+        if self.energy.depleted && !turning_on.iter().all(is_critical) {
+            return Err(SwitchOnBlock::EnergyDepleted);
+        }
+        if let Some(&blown) = turning_on.iter().find(|ch| self.channels[ch].fuse_blown) {
+            return Err(SwitchOnBlock::FuseBlown(blown));
+        }
+        let unmet: Vec<(u8, Precondition, String)> = turning_on.iter()
+            .flat_map(|&ch| self.unmet_preconditions(ch).into_iter().map(move |(precondition, reason)| (ch, precondition, reason)))
+            .collect();
+        if !unmet.is_empty() {
+            return Err(SwitchOnBlock::Preconditions(unmet));
+        }
+        
+        if let Some(max_total_power) = safety.max_total_power.filter(|_| safety.power_budget_check == CheckAction::Refuse) {
+            let projected_power = self.projected_power(&turning_on);
+            if projected_power > max_total_power {
+                return Err(SwitchOnBlock::PowerBudget { projected_power, max_total_power });
+            }
+        }
+        if let Some(max_active_channels) = safety.max_active_channels {
+            let active_channels = self.active_channel_count();
+            if active_channels + turning_on.len() > max_active_channels {
+                return Err(SwitchOnBlock::ActiveChannels { active_channels, max_active_channels });
+            }
+        }
+        Ok(())
+    }
+    
+    /// Preconditions of channel `ch` that do not hold now, each with the reason
+    pub fn unmet_preconditions(&self, ch: u8) -> Vec<(Precondition, String)> {
+        let Some(channel) = self.channels.get(&ch) else { return Vec::new() };
+        channel.preconditions.iter()
+            .filter_map(|precondition| {
+                let reason = match *precondition {
+                    Precondition::SystemNormal if self.system_status != SystemStatus::Normal => {
+                        format!("system status is {:?}", self.system_status)
+                    }
+                    Precondition::InputVoltage { min, max } if !(min..=max).contains(&self.input_voltage) => {
+                        format!("input voltage {:.2}V is outside {}-{}V", self.input_voltage, min, max)
+                    }
+                    Precondition::IgnitionOn if self.ignition_on != Some(true) => "ignition is off".to_string(),
+                    Precondition::ChannelOn { channel }
+                        if self.channels.get(&channel).is_none_or(|other| other.status != ChannelStatus::On) =>
+                    {
+                        format!("channel {} is not ON", channel)
+                    }
+                    Precondition::TemperatureBelow { max } if self.temperature >= max => {
+                        format!("temperature {:.1}°C is not below {}°C", self.temperature, max)
+                    }
+                    _ => return None,
+                };
+                Some((precondition.clone(), reason))
+            })
+            .collect()
+    }
+    
+    /// Whether the safety interlock is enabled and open
+    pub fn interlock_open(&self) -> bool {
+        self.interlock_closed == Some(false)
+    }
+    
+    fn force_off(&mut self, channels: &[u8], trigger: TransitionTrigger) {
+        for channel in self.channels.values_mut().filter(|channel| channels.contains(&channel.ch)) {
+            // An emergency overrides any manual lock, ends a bench test and
+            // cancels pending retries and shedding restores
+            channel.overridden = false;
+            channel.test_mode = false;
+            channel.test_mode_until = None;
+            channel.ramp_duty = None;
+            channel.retry_at = None;
+            channel.recover_until = None;
+            channel.shed = false;
+            channel.shed_at = None;
+            channel.battery_shed = false;
+            channel.set_status(ChannelStatus::Off, trigger);
             channel.voltage = 0.0;
             channel.current = 0.0;
             channel.last_update = Utc::now();
         }
-        self.total_current = 0.0;
+        self.total_current = self.channels.values()
+            .filter(|channel| channel.status == ChannelStatus::On)
+            .map(|channel| channel.current)
+            .sum();
+        self.last_update = Utc::now();
+    }
+    
+    /// Flag channels the hardware did not confirm OFF after an emergency shutdown
+    pub fn mark_shutdown_unconfirmed(&mut self, channels: &[u8]) {
+        for ch in channels {
+            if let Some(channel) = self.channels.get_mut(ch) {
+                channel.set_status(ChannelStatus::Fault, TransitionTrigger::Emergency);
+                channel.fault = Some(ChannelFault::ShutdownUnconfirmed);
+                channel.fault_source = Some(FaultSource::Channel);
+                channel.last_update = Utc::now();
+            }
+        }
         self.last_update = Utc::now();
     }
     
+    /// Recompute each channel's derated current limit
+    ///
+    /// Channels without their own temperature reading use the PDM temperature.
+    pub fn update_effective_limits(&mut self, max_temperature: f32) {
+        let system_temperature = self.temperature;
+        for channel in self.channels.values_mut() {
+            let temperature = channel.temperature.unwrap_or(system_temperature);
+            channel.effective_current_limit = channel.derated_limit(temperature, max_temperature);
+        }
+    }
+    
+    /// Flag channels whose current is approaching their limit
+    ///
+    /// `warn_fraction` is the fraction of the effective limit at which the warning starts.
+    pub fn update_current_warnings(&mut self, warn_fraction: f32) {
+        for channel in self.channels.values_mut() {
+            channel.warning = channel.status == ChannelStatus::On
+                && channel.current > channel.effective_current_limit * warn_fraction
+                && channel.current <= channel.effective_current_limit;
+        }
+    }
+    
+    /// Record running currents for channels that are learning their limit
+    ///
+    /// Inrush is not part of normal running and is skipped; a fault during
+    /// the window marks the session as untrustworthy.
+    pub fn update_learning(&mut self, now: Instant) {
+        for channel in self.channels.values_mut() {
+            let in_inrush = channel.in_inrush_window(now);
+            let Some(session) = channel.learning.as_mut() else { continue };
+            if session.window_elapsed(now) {
+                continue;
+            }
+            match channel.status {
+                ChannelStatus::On if !in_inrush => {
+                    session.peak_current = session.peak_current.max(channel.current);
+                    session.samples += 1;
+                }
+                ChannelStatus::Fault => session.faulted = true,
+                _ => {}
+            }
+        }
+    }
+    
+    /// Move each smoothed channel's `display_current` toward the latest reading
+    ///
+    /// A channel that is not ON shows its reading (0A) at once, so a trip is
+    /// never hidden behind the smoothing.
+    pub fn update_display_currents(&mut self) {
+        for channel in self.channels.values_mut() {
+            let Some(smoothing) = channel.display_smoothing else { continue };
+            channel.display_current = Some(match channel.display_current {
+                Some(shown) if channel.status == ChannelStatus::On => shown + smoothing * (channel.current - shown),
+                _ => channel.current,
+            });
+        }
+    }
+    
+    /// Count each channel's switches over the `switch_rate` window and flag
+    /// channels switched too often
+    ///
+    /// Returns the channels that went over the limit on this update.
+    pub fn update_switch_rates(&mut self, config: &SwitchRateConfig, now: Instant) -> Vec<u8> {
+        let window = Duration::from_millis(config.window_ms);
+        let mut exceeded = Vec::new();
+        for channel in self.channels.values_mut() {
+            while channel.switch_times.front().is_some_and(|&at| now.saturating_duration_since(at) > window) {
+                channel.switch_times.pop_front();
+            }
+            let over = config.enabled && channel.switch_times.len() > config.max_switches;
+            if over && !channel.switch_rate_exceeded {
+                exceeded.push(channel.ch);
+            }
+            channel.switch_rate_exceeded = over;
+        }
+        exceeded
+    }
+    
+    /// Flag ON channels whose current is outside their expected range
+    ///
+    /// The inrush window is not judged. A flagged channel clears only once it
+    /// is back inside the range by `EXPECTED_RANGE_HYSTERESIS`. Returns the
+    /// channels that left their range on this update.
+    pub fn update_expected_ranges(&mut self, now: Instant) -> Vec<u8> {
+        let mut left_range = Vec::new();
+        for channel in self.channels.values_mut() {
+            let out_of_range = match channel.expected_current {
+                Some(range) if channel.status == ChannelStatus::On && !channel.in_inrush_window(now) => {
+                    let margin = if channel.out_of_range {
+                        (range.max - range.min) * EXPECTED_RANGE_HYSTERESIS
+                    } else {
+                        0.0
+                    };
+                    channel.current < range.min + margin || channel.current > range.max - margin
+                }
+                _ => false,
+            };
+            if out_of_range && !channel.out_of_range {
+                left_range.push(channel.ch);
+            }
+            channel.out_of_range = out_of_range;
+        }
+        left_range
+    }
+    
+    /// Update each channel's moving-average current and flag sharp deviations
+    ///
+    /// Returns the channels that became anomalous on this update.
+    pub fn update_current_anomalies(&mut self, config: &AnomalyConfig) -> Vec<u8> {
+        let alpha = 2.0 / (config.window_samples.max(1) as f32 + 1.0);
+        let mut new_anomalies = Vec::new();
+        
+        for channel in self.channels.values_mut() {
+            if channel.status != ChannelStatus::On {
+                channel.average_current = 0.0;
+                channel.anomaly = false;
+                continue;
+            }
+            
+            // First sample after switch-on seeds the average
+            if channel.average_current <= 0.0 {
+                channel.average_current = channel.current;
+                channel.anomaly = false;
+                continue;
+            }
+            
+            let deviation = (channel.current - channel.average_current).abs() / channel.average_current;
+            let anomaly = config.enabled
+                && channel.average_current >= config.min_current
+                && deviation > config.deviation_fraction;
+            
+            if anomaly && !channel.anomaly {
+                new_anomalies.push(channel.ch);
+            }
+            channel.anomaly = anomaly;
+            channel.average_current += alpha * (channel.current - channel.average_current);
+        }
+        
+        new_anomalies
+    }
+    
+    /// Channels whose configured limit exceeds the hardware rating, as (channel, limit, rating)
+    pub fn limits_exceeding_rating(&self) -> Vec<(u8, f32, f32)> {
+        let mut violations: Vec<(u8, f32, f32)> = self.channels.values()
+            .filter_map(|ch| {
+                let rating = ch.hardware_rating?;
+                (ch.current_limit > rating).then_some((ch.ch, ch.current_limit, rating))
+            })
+            .collect();
+        violations.sort_by_key(|(ch, _, _)| *ch);
+        violations
+    }
+    
     /// Calculate total power consumption
     pub fn total_power(&self) -> f32 {
         self.input_voltage * self.total_current
     }
+    
+    /// Recompute the efficiency from the channel outputs and the supply-side input
+    pub fn update_efficiency(&mut self) {
+        let output_power: f32 = self.channels.values()
+            .filter(|channel| channel.status == ChannelStatus::On)
+            .map(|channel| channel.voltage * channel.current)
+            .sum();
+        let input_power = self.input_voltage * self.input_current;
+        
+        // Below a watt the ratio is mostly measurement noise
+        self.efficiency = (input_power > 1.0 && output_power > 0.0)
+            .then(|| (output_power / input_power).min(1.0));
+    }
+    
+    /// Channels in power-off order: `configured` first, then the rest from the highest number down
+    pub fn power_off_order(&self, configured: &[u8]) -> Vec<u8> {
+        let mut rest: Vec<u8> = self.channels.keys()
+            .copied()
+            .filter(|ch| !configured.contains(ch))
+            .collect();
+        rest.sort_unstable_by(|a, b| b.cmp(a));
+        
+        configured.iter()
+            .copied()
+            .filter(|ch| self.channels.contains_key(ch))
+            .chain(rest)
+            .collect()
+    }
+    
+    /// Order for the power-on sequence: the startup channels, each after the channels it depends on
+    ///
+    /// Dependencies of a startup channel are included even if they are not
+    /// startup channels themselves.
+    pub fn power_on_order(&self) -> Result<Vec<u8>> {
+        let mut startup: Vec<u8> = self.channels.values()
+            .filter(|channel| channel.startup)
+            .map(|channel| channel.ch)
+            .collect();
+        startup.sort_unstable();
+        self.dependency_order(&startup)
+    }
+    
+    /// `roots` and everything they depend on, each channel after its dependencies
+    ///
+    /// Ties are broken by channel number. Fails on a dependency cycle.
+    pub fn dependency_order(&self, roots: &[u8]) -> Result<Vec<u8>> {
+        fn visit(channels: &HashMap<u8, Channel>, ch: u8, path: &mut Vec<u8>, order: &mut Vec<u8>) -> Result<()> {
+            if order.contains(&ch) {
+                return Ok(());
+            }
+            if let Some(start) = path.iter().position(|&visiting| visiting == ch) {
+                let cycle: Vec<String> = path[start..].iter().chain([&ch]).map(|ch| ch.to_string()).collect();
+                bail!("Channel dependency cycle: {}", cycle.join(" -> "));
+            }
+            
+            path.push(ch);
+            let mut dependencies = channels.get(&ch).map(|channel| channel.depends_on.clone()).unwrap_or_default();
+            dependencies.sort_unstable();
+            for dependency in dependencies {
+                visit(channels, dependency, path, order)?;
+            }
+            path.pop();
+            order.push(ch);
+            Ok(())
+        }
+        
+        let mut order = Vec::new();
+        for &ch in roots {
+            visit(&self.channels, ch, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
+    }
+    
+    /// Refresh the combined readings of the parallel groups
+    pub fn update_parallel_groups(&mut self, groups: &[ParallelGroupConfig]) {
+        self.parallel_groups = groups.iter()
+            .map(|group| ParallelGroupStatus::measure(group, &self.channels))
+            .collect();
+    }
+    
+    /// Number of channels currently ON
+    pub fn active_channel_count(&self) -> usize {
+        self.channels.values().filter(|channel| channel.status == ChannelStatus::On).count()
+    }
+    
+    /// Estimated total power of the ON channels plus those in `enabling` (W)
+    pub fn projected_power(&self, enabling: &[u8]) -> f32 {
+        self.channels.values()
+            .filter(|channel| channel.status == ChannelStatus::On || enabling.contains(&channel.ch))
+            .map(|channel| channel.estimated_power(self.input_voltage))
+            .sum()
+    }
+}
+
+/// Peak-to-peak and standard deviation of a window of samples
+pub fn ripple_stats(samples: &[f32]) -> (f32, f32) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    
+    let max = samples.iter().copied().fold(f32::MIN, f32::max);
+    let min = samples.iter().copied().fold(f32::MAX, f32::min);
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / samples.len() as f32;
+    
+    (max - min, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use super::{ChannelStatus, PdmState};
+    
+    #[test]
+    fn test_pdm_state_from_config_loadout() {
+        use crate::config::ChannelConfig;
+        
+        let config = Config {
+            channels: vec![
+                ChannelConfig { ch: 3, name: Some("WATER PUMP".to_string()), current_limit: Some(10.0), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        
+        let state = PdmState::from_config(&config).unwrap();
+        assert_eq!(state.channels.len(), 8);
+        assert_eq!(state.channels.get(&3).unwrap().name, "WATER PUMP");
+        assert_eq!(state.channels.get(&3).unwrap().current_limit, 10.0);
+        assert_eq!(state.channels.get(&1).unwrap().name, "FUEL PUMP");
+    }
+    
+    #[test]
+    fn test_channel_transitions_record_trigger() {
+        use crate::models::TransitionTrigger;
+        
+        let mut state = PdmState::new();
+        let channel = state.channels.get_mut(&1).unwrap();
+        channel.set_status(ChannelStatus::On, TransitionTrigger::Api);
+        // Re-applying the same status is not a transition
+        channel.set_status(ChannelStatus::On, TransitionTrigger::Api);
+        state.emergency_shutdown();
+        
+        let channel = state.channels.get(&1).unwrap();
+        let transitions = channel.recent_transitions(10);
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].from, ChannelStatus::Off);
+        assert_eq!(transitions[0].trigger, TransitionTrigger::Api);
+        assert_eq!(transitions[1].to, ChannelStatus::Off);
+        assert_eq!(transitions[1].trigger, TransitionTrigger::Emergency);
+        assert_eq!(channel.last_transition, Some(transitions[1].timestamp));
+        
+        // Channels that never changed have no transitions
+        assert!(state.channels.get(&2).unwrap().last_transition.is_none());
+    }
+    
+    #[test]
+    fn test_current_warning_below_limit() {
+        let mut state = PdmState::new();
+        
+        // 13A on a 15A limit is above the 80% warning level
+        state.update_channel(1, 13.2, 13.0, ChannelStatus::On);
+        state.update_channel(2, 13.2, 4.0, ChannelStatus::On);
+        state.update_current_warnings(0.8);
+        
+        assert!(state.channels.get(&1).unwrap().warning);
+        assert!(!state.channels.get(&2).unwrap().warning);
+        assert_eq!(state.channels.get(&1).unwrap().status, ChannelStatus::On);
+    }
+    
+    #[test]
+    fn test_current_anomaly_detection() {
+        let mut state = PdmState::new();
+        let anomaly_config = crate::config::AnomalyConfig::default();
+        
+        // Steady 4A draw builds up the average
+        for _ in 0..20 {
+            state.update_channel(1, 13.5, 4.0, ChannelStatus::On);
+            assert!(state.update_current_anomalies(&anomaly_config).is_empty());
+        }
+        
+        // Pump running dry: current collapses
+        state.update_channel(1, 13.5, 1.0, ChannelStatus::On);
+        assert_eq!(state.update_current_anomalies(&anomaly_config), vec![1]);
+        assert!(state.channels.get(&1).unwrap().anomaly);
+        assert_eq!(state.channels.get(&1).unwrap().status, ChannelStatus::On);
+    }
+    
+    #[test]
+    fn test_system_status_downgrades_are_debounced() {
+        use crate::hardware::StatusDebounce;
+        use crate::models::SystemStatus;
+        
+        let mut debounce = StatusDebounce::default();
+        let mut reported = SystemStatus::Normal;
+        
+        // A value hovering on the warning threshold escalates once and then holds
+        let mut history = Vec::new();
+        for cycle in 0..10 {
+            let evaluated = if cycle % 2 == 0 { SystemStatus::Warning } else { SystemStatus::Normal };
+            reported = debounce.apply(reported, evaluated, 3);
+            history.push(reported.clone());
+        }
+        assert!(history.iter().all(|status| *status == SystemStatus::Warning), "{:?}", history);
+        
+        // Escalation is immediate
+        reported = debounce.apply(reported, SystemStatus::Fault, 3);
+        assert_eq!(reported, SystemStatus::Fault);
+        
+        // Recovery needs three cycles below Fault, and goes to the worst status seen meanwhile
+        reported = debounce.apply(reported, SystemStatus::Warning, 3);
+        assert_eq!(reported, SystemStatus::Fault);
+        reported = debounce.apply(reported, SystemStatus::Normal, 3);
+        assert_eq!(reported, SystemStatus::Fault);
+        reported = debounce.apply(reported, SystemStatus::Normal, 3);
+        assert_eq!(reported, SystemStatus::Warning);
+        for _ in 0..3 {
+            reported = debounce.apply(reported, SystemStatus::Normal, 3);
+        }
+        assert_eq!(reported, SystemStatus::Normal);
+        
+        // Without debounce every change is reported
+        assert_eq!(debounce.apply(SystemStatus::Fault, SystemStatus::Normal, 0), SystemStatus::Normal);
+    }
+    
+    #[test]
+    fn test_channel_category_reported_in_status() {
+        use crate::config::{ChannelConfig, Config};
+        use crate::models::PdmState;
+        
+        let lighting = |ch: u8| ChannelConfig { ch, category: Some("Lighting".to_string()), ..Default::default() };
+        let config = Config { channels: vec![lighting(1), lighting(2)], ..Default::default() };
+        let state = PdmState::from_config(&config).unwrap();
+        
+        let status = serde_json::to_value(&state).unwrap();
+        assert_eq!(status["channels"]["1"]["category"], "Lighting");
+        assert_eq!(status["channels"]["2"]["category"], "Lighting");
+        assert!(status["channels"]["3"]["category"].is_null());
+        
+        let blank = Config { channels: vec![ChannelConfig { ch: 1, category: Some(" ".to_string()), ..Default::default() }], ..Default::default() };
+        assert!(PdmState::from_config(&blank).is_err());
+    }
+    
+    #[test]
+    fn test_display_smoothing_leaves_raw_current() {
+        use crate::config::ChannelConfig;
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig { ch: 1, display_smoothing: Some(0.5), ..Default::default() }],
+            ..Default::default()
+        };
+        let mut state = PdmState::from_config(&config).unwrap();
+        assert_eq!(state.channels.get(&1).unwrap().display_smoothing, Some(0.5));
+        assert_eq!(state.channels.get(&2).unwrap().display_smoothing, None);
+        
+        state.update_channel(1, 13.5, 4.0, ChannelStatus::On);
+        state.update_display_currents();
+        state.update_channel(1, 13.5, 12.0, ChannelStatus::On);
+        state.update_display_currents();
+        let channel = state.channels.get(&1).unwrap();
+        assert_eq!(channel.current, 12.0);
+        assert_eq!(channel.display_current, Some(8.0));
+        
+        // Switching off shows 0A at once rather than decaying
+        state.update_channel(1, 13.5, 0.0, ChannelStatus::Off);
+        state.update_display_currents();
+        assert_eq!(state.channels.get(&1).unwrap().display_current, Some(0.0));
+        
+        config.channels[0].display_smoothing = Some(0.0);
+        assert!(PdmState::from_config(&config).is_err());
+    }
+    
+    #[test]
+    fn test_expected_current_range_flags_without_faulting() {
+        use crate::config::{ChannelConfig, CurrentRange};
+        use std::time::{Duration, Instant};
+        
+        let mut config = Config {
+            channels: vec![ChannelConfig {
+                ch: 1,
+                expected_current: Some(CurrentRange { min: 2.0, max: 6.0 }),
+                inrush_ignore_ms: Some(500),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut state = PdmState::from_config(&config).unwrap();
+        let now = Instant::now();
+        let set = |state: &mut PdmState, current: f32, on_for_ms: u64| {
+            let channel = state.channels.get_mut(&1).unwrap();
+            channel.status = ChannelStatus::On;
+            channel.current = current;
+            channel.switched_on_at = Some(now - Duration::from_millis(on_for_ms));
+            state.update_expected_ranges(now)
+        };
+        
+        assert!(set(&mut state, 4.0, 1000).is_empty());
+        assert!(!state.channels[&1].out_of_range);
+        // Too low: flagged once, and stays flagged
+        assert_eq!(set(&mut state, 0.3, 1000), vec![1]);
+        assert!(set(&mut state, 0.3, 1000).is_empty());
+        assert!(state.channels[&1].out_of_range);
+        assert_eq!(state.channels[&1].status, ChannelStatus::On);
+        // Hovering at the edge neither clears it nor reports it again
+        assert!(set(&mut state, 2.1, 1000).is_empty());
+        assert!(set(&mut state, 1.9, 1000).is_empty());
+        assert!(state.channels[&1].out_of_range);
+        assert!(set(&mut state, 4.0, 1000).is_empty());
+        assert!(!state.channels[&1].out_of_range);
+        // Too high, but not while inrush is being tolerated
+        assert!(set(&mut state, 9.0, 100).is_empty());
+        assert!(!state.channels[&1].out_of_range);
+        assert_eq!(set(&mut state, 9.0, 1000), vec![1]);
+        
+        state.channels.get_mut(&1).unwrap().status = ChannelStatus::Off;
+        state.update_expected_ranges(now);
+        assert!(!state.channels[&1].out_of_range);
+        
+        config.channels[0].expected_current = Some(CurrentRange { min: 2.0, max: 15.0 });
+        assert!(PdmState::from_config(&config).is_err());
+        config.channels[0].expected_current = Some(CurrentRange { min: 6.0, max: 2.0 });
+        assert!(PdmState::from_config(&config).is_err());
+    }
 }
@@ -0,0 +1,124 @@
+/*!
+ * Output Precision
+ *
+ * Readings are kept at full `f32` precision internally, but serializing them
+ * as-is shows float noise (`13.799998`). Fields annotated with these
+ * serializers are rounded to the configured number of decimals on output:
+ *
+ * ```ignore
+ * #[serde(serialize_with = "precision::voltage")]
+ * pub voltage: f32,
+ * ```
+ *
+ * The precision is process-wide; `apply` sets it at startup and on config
+ * reload.
+ */
+
+use serde::Serializer;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::config::PrecisionConfig;
+
+/// Decimals at or above this are left unrounded (f32 carries ~7 significant digits)
+pub const FULL_PRECISION: u8 = 7;
+
+static VOLTAGE: AtomicU8 = AtomicU8::new(2);
+static CURRENT: AtomicU8 = AtomicU8::new(2);
+static TEMPERATURE: AtomicU8 = AtomicU8::new(1);
+static POWER: AtomicU8 = AtomicU8::new(1);
+
+/// Use the configured precision for all subsequent output
+pub fn apply(config: &PrecisionConfig) {
+    VOLTAGE.store(config.voltage, Ordering::Relaxed);
+    CURRENT.store(config.current, Ordering::Relaxed);
+    TEMPERATURE.store(config.temperature, Ordering::Relaxed);
+    POWER.store(config.power, Ordering::Relaxed);
+}
+
+/// `value` rounded to `decimals` places
+pub fn round(value: f32, decimals: u8) -> f32 {
+    round_f64(value, decimals) as f32
+}
+
+/// Rounded in f64, so values that pass through `serde_json::Value` (an f64)
+/// print as e.g. `13.8` rather than the widened f32 `13.800000190734863`
+fn round_f64(value: f32, decimals: u8) -> f64 {
+    let value = f64::from(value);
+    if decimals >= FULL_PRECISION || !value.is_finite() {
+        return value;
+    }
+    let scale = 10f64.powi(i32::from(decimals));
+    (value * scale).round() / scale
+}
+
+fn rounded<S: Serializer>(value: f32, decimals: &AtomicU8, serializer: S) -> Result<S::Ok, S::Error> {
+    match decimals.load(Ordering::Relaxed) {
+        decimals if decimals >= FULL_PRECISION => serializer.serialize_f32(value),
+        decimals => serializer.serialize_f64(round_f64(value, decimals)),
+    }
+}
+
+fn rounded_option<S: Serializer>(value: &Option<f32>, decimals: &AtomicU8, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => rounded(*value, decimals, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn voltage<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded(*value, &VOLTAGE, serializer)
+}
+
+pub fn current<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded(*value, &CURRENT, serializer)
+}
+
+pub fn temperature<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded(*value, &TEMPERATURE, serializer)
+}
+
+pub fn power<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded(*value, &POWER, serializer)
+}
+
+pub fn voltage_option<S: Serializer>(value: &Option<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded_option(value, &VOLTAGE, serializer)
+}
+
+pub fn current_option<S: Serializer>(value: &Option<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded_option(value, &CURRENT, serializer)
+}
+
+pub fn temperature_option<S: Serializer>(value: &Option<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+    rounded_option(value, &TEMPERATURE, serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_readings_are_rounded_in_json_output() {
+        use crate::precision;
+        
+        assert_eq!(precision::round(13.799998, 2), 13.8);
+        assert_eq!(precision::round(-0.126, 2), -0.13);
+        assert_eq!(precision::round(13.799998, precision::FULL_PRECISION), 13.799998);
+        
+        let mut state = PdmState::new();
+        state.input_voltage = 13.799998;
+        state.temperature = 41.26;
+        let channel = state.channels.get_mut(&1).unwrap();
+        channel.current = 4.2012;
+        channel.temperature = Some(55.56);
+        
+        // Output is rounded to the default decimals; the state keeps full precision
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["input_voltage"].to_string(), "13.8");
+        assert_eq!(json["temperature"].to_string(), "41.3");
+        assert_eq!(json["channels"]["1"]["current"].to_string(), "4.2");
+        assert_eq!(json["channels"]["1"]["temperature"].to_string(), "55.6");
+        assert_eq!(json["channels"]["2"]["temperature"], serde_json::Value::Null);
+        assert_eq!(state.input_voltage, 13.799998);
+    }
+}
@@ -0,0 +1,230 @@
+/*!
+ * Time-of-Day Channel Schedules
+ *
+ * Evaluates the `schedules` rules against the local system clock. When a
+ * rule's window opens its channels are switched to the rule's action (e.g.
+ * OFF for quiet hours); when it closes they are put back. Critical and
+ * manually overridden channels are never touched, and a channel the
+ * operator switched during the window is left as the operator set it.
+ */
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveTime};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use tracing::{info, warn};
+
+use crate::config::{ScheduleAction, ScheduleConfig};
+use crate::hardware::HardwareManager;
+use crate::models::{ChannelStatus, PdmState, TransitionTrigger};
+
+/// How often the rules are checked against the clock
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A parsed schedule rule and what it has switched
+#[derive(Debug)]
+struct Rule {
+    config: ScheduleConfig,
+    start: NaiveTime,
+    end: NaiveTime,
+    /// Whether the window was open at the last evaluation
+    active: bool,
+    /// Channels switched when the window opened, to restore when it closes
+    switched: Vec<u8>,
+}
+
+/// Applies the configured schedules
+#[derive(Debug)]
+pub struct Scheduler {
+    rules: Vec<Rule>,
+}
+
+impl Scheduler {
+    /// Parse and check the schedule rules
+    pub fn new(schedules: &[ScheduleConfig], channel_count: u8) -> Result<Self> {
+        let rules = schedules.iter()
+            .map(|config| {
+                for &ch in &config.channels {
+                    if ch == 0 || ch > channel_count {
+                        return Err(anyhow!("Channel {} in schedule '{}' is outside the configured range 1-{}",
+                                           ch, config.name, channel_count));
+                    }
+                }
+                let parse = |time: &str| NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| anyhow!("Schedule '{}' time '{}' is not HH:MM", config.name, time));
+                let (start, end) = (parse(&config.start)?, parse(&config.end)?);
+                if start == end {
+                    return Err(anyhow!("Schedule '{}' window is empty (start equals end)", config.name));
+                }
+                Ok(Rule { config: config.clone(), start, end, active: false, switched: Vec::new() })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Start the scheduling loop
+    pub async fn start(mut self, hardware_manager: Arc<HardwareManager>, pdm_state: Arc<RwLock<PdmState>>) -> Result<()> {
+        info!("Scheduler running {} rules", self.rules.len());
+        let mut check_interval = interval(CHECK_INTERVAL);
+        loop {
+            check_interval.tick().await;
+            self.evaluate(Local::now().time(), &hardware_manager, &pdm_state).await;
+        }
+    }
+
+    /// Open or close each rule's window for the given time of day
+    pub async fn evaluate(&mut self, now: NaiveTime, hardware_manager: &HardwareManager, pdm_state: &Arc<RwLock<PdmState>>) {
+        for rule in &mut self.rules {
+            let inside = in_window(rule.start, rule.end, now);
+            if inside == rule.active {
+                continue;
+            }
+            rule.active = inside;
+
+            let on = rule.config.action == ScheduleAction::On;
+            if inside {
+                info!("Schedule '{}' started ({}-{})", rule.config.name, rule.config.start, rule.config.end);
+                let candidates = eligible_channels(&rule.config.channels, pdm_state, !on).await;
+                rule.switched = switch_channels(hardware_manager, pdm_state, &candidates, on).await;
+            } else {
+                info!("Schedule '{}' ended, restoring channels {:?}", rule.config.name, rule.switched);
+                let switched = std::mem::take(&mut rule.switched);
+                // Only channels still as the schedule left them are restored
+                let candidates = eligible_channels(&switched, pdm_state, on).await;
+                switch_channels(hardware_manager, pdm_state, &candidates, !on).await;
+            }
+        }
+    }
+}
+
+/// Whether `now` falls in [start, end), wrapping over midnight
+fn in_window(start: NaiveTime, end: NaiveTime, now: NaiveTime) -> bool {
+    if start < end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Channels (and their mirrored peers) that are currently ON (`on`) or OFF
+/// and may be switched by a schedule
+async fn eligible_channels(channels: &[u8], pdm_state: &Arc<RwLock<PdmState>>, on: bool) -> Vec<u8> {
+    let state = pdm_state.read().await;
+    let wanted = if on { ChannelStatus::On } else { ChannelStatus::Off };
+    let mut eligible = BTreeSet::new();
+    for channel in channels.iter().filter_map(|ch| state.channels.get(ch)) {
+        for ch in std::iter::once(channel.ch).chain(channel.mirrored_with.iter().copied()) {
+            let Some(member) = state.channels.get(&ch) else { continue };
+            if member.status == wanted && !member.critical && !member.overridden {
+                eligible.insert(ch);
+            }
+        }
+    }
+    eligible.into_iter().collect()
+}
+
+/// Switch channels ON or OFF, returning the ones that were switched
+async fn switch_channels(hardware_manager: &HardwareManager, pdm_state: &Arc<RwLock<PdmState>>, channels: &[u8], on: bool) -> Vec<u8> {
+    if on {
+        // A rule's channels come up together or not at all
+        let state = pdm_state.read().await;
+        if let Err(block) = state.check_switch_on(channels, &hardware_manager.config().safety) {
+            warn!("Schedule cannot switch channels {:?} ON: {}", channels, block);
+            return Vec::new();
+        }
+    } else {
+        hardware_manager.ramp_down(pdm_state, channels).await;
+    }
+
+    let mut switched = Vec::new();
+    for &ch in channels {
+        if let Err(e) = hardware_manager.control_channel(ch, on).await {
+            warn!("Schedule failed to switch channel {} {}: {}", ch, if on { "ON" } else { "OFF" }, e);
+            continue;
+        }
+
+        let mut state = pdm_state.write().await;
+        if let Some(channel) = state.channels.get_mut(&ch) {
+            let status = if on { ChannelStatus::On } else { ChannelStatus::Off };
+            channel.set_status(status, TransitionTrigger::Schedule);
+            channel.ramp_duty = None;
+            channel.switched_on_at = on.then(std::time::Instant::now);
+            channel.last_update = chrono::Utc::now();
+            info!("Channel {} switched {} by schedule", ch, if on { "ON" } else { "OFF" });
+            switched.push(ch);
+        }
+    }
+    switched
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[tokio::test]
+    async fn test_quiet_hours_schedule_switches_and_restores() {
+        use crate::config::{ChannelConfig, ScheduleAction, ScheduleConfig};
+        use crate::models::TransitionTrigger;
+        use crate::scheduler::Scheduler;
+        use chrono::NaiveTime;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+        
+        let quiet_hours = ScheduleConfig {
+            name: "Quiet hours".to_string(),
+            channels: vec![2, 3, 5],
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            action: ScheduleAction::Off,
+        };
+        let pump_run = ScheduleConfig {
+            name: "Pump run".to_string(),
+            channels: vec![6],
+            start: "08:00".to_string(),
+            end: "09:00".to_string(),
+            action: ScheduleAction::On,
+        };
+        let mut config = Config {
+            channels: vec![ChannelConfig { ch: 3, critical: true, ..Default::default() }],
+            schedules: vec![quiet_hours.clone(), pump_run],
+            ..Default::default()
+        };
+        config.safety.max_active_channels = Some(3);
+        let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = crate::hardware::HardwareManager::new(config.clone()).unwrap();
+        let mut scheduler = Scheduler::new(&config.schedules, config.hardware.channel_count).unwrap();
+        for ch in [2, 3, 4] {
+            pdm_state.write().await.update_channel(ch, 13.5, 1.0, ChannelStatus::On);
+        }
+        let at = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        let statuses = || async {
+            let state = pdm_state.read().await;
+            [2, 3, 4, 5].map(|ch| state.channels[&ch].status.clone())
+        };
+        
+        scheduler.evaluate(at("21:59"), &hardware_manager, &pdm_state).await;
+        assert_eq!(statuses().await, [ChannelStatus::On, ChannelStatus::On, ChannelStatus::On, ChannelStatus::Off]);
+        
+        // Inside the window non-critical listed channels go off; critical ones are exempt
+        scheduler.evaluate(at("23:30"), &hardware_manager, &pdm_state).await;
+        assert_eq!(statuses().await, [ChannelStatus::Off, ChannelStatus::On, ChannelStatus::On, ChannelStatus::Off]);
+        let trigger = pdm_state.read().await.channels[&2].transitions.back().unwrap().trigger;
+        assert_eq!(trigger, TransitionTrigger::Schedule);
+        
+        // The window runs over midnight, then restores only what it switched
+        scheduler.evaluate(at("06:59"), &hardware_manager, &pdm_state).await;
+        assert_eq!(statuses().await[0], ChannelStatus::Off);
+        scheduler.evaluate(at("07:00"), &hardware_manager, &pdm_state).await;
+        assert_eq!(statuses().await, [ChannelStatus::On, ChannelStatus::On, ChannelStatus::On, ChannelStatus::Off]);
+        
+        // A schedule switching on is held to the same limits as the API
+        scheduler.evaluate(at("08:00"), &hardware_manager, &pdm_state).await;
+        assert_eq!(pdm_state.read().await.channels[&6].status, ChannelStatus::Off);
+        
+        let bad_time = ScheduleConfig { start: "25:00".to_string(), ..quiet_hours };
+        assert!(Scheduler::new(&[bad_time], 8).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+/*!
+ * Setup Checklist
+ *
+ * First-time setup leaves a lot at its defaults that is fine on the bench but
+ * wrong on a vehicle: the simulator still running, stock channel names and
+ * limits, the API open to the network. `GET /api/setup/status` runs these
+ * checks against the live config and state so a new unit can be walked
+ * through them before it ships.
+ */
+
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::models::PdmState;
+use crate::signing;
+
+/// How much a failed check matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    /// Worth doing, fine to ship without
+    Info,
+    /// Likely a mistake on a deployed unit
+    Warning,
+    /// Must be fixed before the unit ships
+    Critical,
+}
+
+/// One checklist item
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupCheck {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub severity: Severity,
+    pub passed: bool,
+    /// What is wrong and how to fix it (None when passed)
+    pub detail: Option<String>,
+}
+
+impl SetupCheck {
+    fn new(id: &'static str, title: &'static str, severity: Severity, problem: Option<String>) -> Self {
+        Self {
+            id,
+            title,
+            severity,
+            passed: problem.is_none(),
+            detail: problem,
+        }
+    }
+}
+
+/// Whether every Warning and Critical check passed
+pub fn is_complete(checks: &[SetupCheck]) -> bool {
+    checks.iter().all(|check| check.passed || check.severity == Severity::Info)
+}
+
+/// Run the setup checklist against the current config and state
+///
+/// `simulation` is the mode the hardware manager actually runs in, which
+/// differs from `hardware.simulation_mode` after a fallback to the simulator.
+pub fn check(config: &Config, state: &PdmState, simulation: bool) -> Vec<SetupCheck> {
+    let defaults = Config::default();
+    let mut channels: Vec<u8> = state.channels.keys().copied().collect();
+    channels.sort_unstable();
+    let configured = |ch: u8| config.channels.iter().find(|entry| entry.ch == ch);
+
+    let unnamed: Vec<u8> = channels.iter().copied()
+        .filter(|&ch| configured(ch).and_then(|entry| entry.name.as_ref()).is_none())
+        .collect();
+    let default_limits: Vec<u8> = channels.iter().copied()
+        .filter(|&ch| configured(ch).and_then(|entry| entry.current_limit).is_none())
+        .collect();
+    let over_rating: Vec<String> = channels.iter()
+        .filter_map(|ch| state.channels.get(ch))
+        .filter_map(|channel| {
+            let rating = channel.hardware_rating?;
+            (channel.current_limit > rating)
+                .then(|| format!("channel {} {:.1}A > {:.1}A", channel.ch, channel.current_limit, rating))
+        })
+        .collect();
+
+    let safety = &config.safety;
+    let stock_safety = &defaults.safety;
+    let safety_unchanged = safety.max_input_voltage == stock_safety.max_input_voltage
+        && safety.min_input_voltage == stock_safety.min_input_voltage
+        && safety.max_total_current == stock_safety.max_total_current
+        && safety.max_temperature == stock_safety.max_temperature
+        && safety.default_channel_current_limit == stock_safety.default_channel_current_limit;
+
+    let exposed = config.server_address.parse::<SocketAddr>()
+        .map_or(true, |address| !address.ip().is_loopback());
+
+    let key_path = std::env::var(signing::PUBLIC_KEY_ENV).unwrap_or_else(|_| signing::PUBLIC_KEY_FILE.to_string());
+
+    vec![
+        SetupCheck::new(
+            "simulation_off",
+            "Real hardware selected",
+            Severity::Critical,
+            simulation.then(|| if config.hardware.simulation_mode {
+                "The simulator is still running; set hardware.simulation_mode = false".to_string()
+            } else {
+                "No hardware link was found, so the simulator is running in its place; \
+                 check the serial port or CAN interface".to_string()
+            }),
+        ),
+        SetupCheck::new(
+            "api_not_exposed",
+            "API restricted to this machine",
+            Severity::Critical,
+            exposed.then(|| format!(
+                "The API listens on {} without authentication; bind server_address to 127.0.0.1 \
+                 or restrict access with a firewall",
+                config.server_address,
+            )),
+        ),
+        SetupCheck::new(
+            "limits_within_rating",
+            "Current limits within the hardware ratings",
+            Severity::Critical,
+            (!over_rating.is_empty()).then(|| format!("Limits above the rating: {}", over_rating.join(", "))),
+        ),
+        SetupCheck::new(
+            "firmware_compatible",
+            "Firmware meets the minimum version",
+            Severity::Critical,
+            (state.firmware_compatible == Some(false)).then(|| format!(
+                "Firmware {} is older than hardware.min_firmware_version {}",
+                state.firmware_version.as_deref().unwrap_or("unknown"),
+                config.hardware.min_firmware_version.as_deref().unwrap_or("unknown"),
+            )),
+        ),
+        SetupCheck::new(
+            "safety_limits_set",
+            "Safety limits configured",
+            Severity::Warning,
+            safety_unchanged.then(|| {
+                "The [safety] voltage, current and temperature limits are the defaults; set them for this vehicle"
+                    .to_string()
+            }),
+        ),
+        SetupCheck::new(
+            "channel_limits_set",
+            "Channel current limits configured",
+            Severity::Warning,
+            (!default_limits.is_empty()).then(|| format!(
+                "Channels {} use the default {:.1}A limit; set current_limit in [[channels]]",
+                list(&default_limits), safety.default_channel_current_limit,
+            )),
+        ),
+        SetupCheck::new(
+            "channels_named",
+            "Channels named",
+            Severity::Warning,
+            (!unnamed.is_empty()).then(|| format!(
+                "Channels {} have no name in [[channels]]",
+                list(&unnamed),
+            )),
+        ),
+        SetupCheck::new(
+            "unit_identified",
+            "Unit identity set",
+            Severity::Info,
+            (config.unit.id.is_none() || config.unit.name.is_none()).then(|| {
+                "[unit] id or name falls back to the hostname; set both to tell units apart".to_string()
+            }),
+        ),
+        SetupCheck::new(
+            "audit_enabled",
+            "Audit log enabled",
+            Severity::Info,
+            (!config.audit.enabled).then(|| "Control actions are not recorded; set audit.enabled = true".to_string()),
+        ),
+        SetupCheck::new(
+            "config_signed",
+            "Config signature checked",
+            Severity::Info,
+            (!Path::new(&key_path).exists()).then(|| format!(
+                "No signing key at {}; see --generate-signing-key to make config changes tamper-evident",
+                key_path,
+            )),
+        ),
+    ]
+}
+
+fn list(channels: &[u8]) -> String {
+    channels.iter().map(u8::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::PdmState;
+    
+    #[test]
+    fn test_setup_checklist() {
+        use crate::config::{ChannelConfig, Config};
+        use crate::setup::{self, Severity};
+        
+        let failed = |checks: &[setup::SetupCheck]| -> Vec<&str> {
+            checks.iter().filter(|check| !check.passed).map(|check| check.id).collect()
+        };
+        
+        // A stock config fails everything but the localhost binding
+        let config = Config::default();
+        let checks = setup::check(&config, &PdmState::from_config(&config).unwrap(), config.hardware.simulation_mode);
+        assert!(!setup::is_complete(&checks));
+        let open = failed(&checks);
+        for id in ["simulation_off", "safety_limits_set", "channel_limits_set", "channels_named"] {
+            assert!(open.contains(&id), "{} should fail", id);
+        }
+        assert!(!open.contains(&"api_not_exposed"));
+        
+        // Configured for a vehicle, only advisory items remain
+        let mut config = Config {
+            server_address: "0.0.0.0:3030".to_string(),
+            channels: (1..=8).map(|ch| ChannelConfig {
+                ch,
+                name: Some(format!("Load {}", ch)),
+                current_limit: Some(10.0),
+                ..Default::default()
+            }).collect(),
+            ..Default::default()
+        };
+        config.hardware.simulation_mode = false;
+        config.safety.max_total_current = 60.0;
+        let checks = setup::check(&config, &PdmState::from_config(&config).unwrap(), config.hardware.simulation_mode);
+        assert_eq!(failed(&checks), vec!["api_not_exposed", "unit_identified", "config_signed"]);
+        assert!(!setup::is_complete(&checks));
+        
+        config.server_address = "127.0.0.1:3030".to_string();
+        let checks = setup::check(&config, &PdmState::from_config(&config).unwrap(), config.hardware.simulation_mode);
+        assert!(checks.iter().all(|check| check.passed || check.severity == Severity::Info));
+        assert!(setup::is_complete(&checks));
+        
+        // Real mode that fell back to the simulator is still flagged
+        let checks = setup::check(&config, &PdmState::from_config(&config).unwrap(), true);
+        assert_eq!(failed(&checks), vec!["simulation_off", "unit_identified", "config_signed"]);
+    }
+}
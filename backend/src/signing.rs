@@ -0,0 +1,155 @@
+/*!
+ * Config File Signatures
+ *
+ * Tamper-evidence for safety-critical configs: `pdm_config.toml` may carry a
+ * detached Ed25519 signature in `pdm_config.toml.sig` (hex). Verification is
+ * enabled by installing the signer's public key (hex) in `pdm_config.pub`, or
+ * at the path in `PDM_CONFIG_PUBLIC_KEY`. Both the key and the policy live
+ * outside the config file, so editing the config can't switch the check off.
+ *
+ * A missing or mismatched signature refuses the load unless
+ * `PDM_CONFIG_SIGNATURE_CHECK=warn` is set. Keys and signatures are made with
+ * `pdm-backend --generate-signing-key <file>` and `--sign-config <file>`.
+ */
+
+use anyhow::{anyhow, bail, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+use crate::config::CheckAction;
+
+/// Default location of the verification key
+pub const PUBLIC_KEY_FILE: &str = "pdm_config.pub";
+/// Overrides the verification key location
+pub const PUBLIC_KEY_ENV: &str = "PDM_CONFIG_PUBLIC_KEY";
+/// `warn` or `refuse` (default) on a bad signature
+pub const CHECK_ENV: &str = "PDM_CONFIG_SIGNATURE_CHECK";
+
+/// Detached signature file for a config file (`<config>.sig`)
+pub fn signature_path(config_path: &Path) -> PathBuf {
+    let mut path = config_path.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// New random signing key, as (secret, public) hex
+pub fn generate_key() -> (String, String) {
+    let key = SigningKey::generate(&mut OsRng);
+    (hex::encode(key.to_bytes()), hex::encode(key.verifying_key().to_bytes()))
+}
+
+/// Hex public key for a hex secret key
+pub fn public_key(secret_hex: &str) -> Result<String> {
+    Ok(hex::encode(signing_key(secret_hex)?.verifying_key().to_bytes()))
+}
+
+/// Hex signature of `contents`
+pub fn sign(contents: &[u8], secret_hex: &str) -> Result<String> {
+    Ok(hex::encode(signing_key(secret_hex)?.sign(contents).to_bytes()))
+}
+
+/// Check a hex signature of `contents` against a hex public key
+pub fn verify(contents: &[u8], signature_hex: &str, public_hex: &str) -> Result<()> {
+    let public: [u8; 32] = decode(public_hex).context("Invalid public key")?;
+    let signature: [u8; 64] = decode(signature_hex).context("Invalid signature")?;
+    VerifyingKey::from_bytes(&public)
+        .context("Invalid public key")?
+        .verify(contents, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow!("Signature does not match the config contents"))
+}
+
+/// Check a config file's contents against its signature, using the installed key and policy
+pub fn check_config(config_path: &Path, contents: &str) -> Result<()> {
+    let key_path = std::env::var(PUBLIC_KEY_ENV).unwrap_or_else(|_| PUBLIC_KEY_FILE.to_string());
+    let action = match std::env::var(CHECK_ENV) {
+        Err(_) => CheckAction::Refuse,
+        Ok(value) => match value.to_ascii_lowercase().as_str() {
+            "warn" => CheckAction::Warn,
+            "refuse" => CheckAction::Refuse,
+            _ => bail!("{} must be \"warn\" or \"refuse\", not {:?}", CHECK_ENV, value),
+        },
+    };
+    check_config_with(config_path, contents, Path::new(&key_path), action)
+}
+
+/// Check a config file's contents against its signature
+///
+/// Does nothing without a key at `key_path`.
+pub fn check_config_with(config_path: &Path, contents: &str, key_path: &Path, action: CheckAction) -> Result<()> {
+    if !key_path.exists() {
+        debug!("No config signing key at {} - signature not checked", key_path.display());
+        return Ok(());
+    }
+
+    let signature_path = signature_path(config_path);
+    let result = fs::read_to_string(key_path)
+        .with_context(|| format!("Failed to read {}", key_path.display()))
+        .and_then(|public| {
+            let signature = fs::read_to_string(&signature_path)
+                .with_context(|| format!("Missing signature {}", signature_path.display()))?;
+            verify(contents.as_bytes(), &signature, &public)
+        });
+
+    match (result, action) {
+        (Ok(()), _) => {
+            info!("Config signature verified ({})", signature_path.display());
+            Ok(())
+        }
+        (Err(e), CheckAction::Warn) => {
+            warn!("Config {} failed signature check: {:#} - loading anyway", config_path.display(), e);
+            Ok(())
+        }
+        (Err(e), CheckAction::Refuse) => {
+            Err(e.context(format!("Config {} failed signature check", config_path.display())))
+        }
+    }
+}
+
+fn signing_key(secret_hex: &str) -> Result<SigningKey> {
+    Ok(SigningKey::from_bytes(&decode(secret_hex).context("Invalid signing key")?))
+}
+
+/// Fixed-length bytes from hex, ignoring surrounding whitespace
+fn decode<const N: usize>(value: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(value.trim())?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| anyhow!("Expected {} bytes, got {}", N, bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    
+    #[test]
+    fn test_config_signature_check() {
+        use crate::config::CheckAction;
+        use crate::signing;
+        
+        let directory = std::env::temp_dir().join(format!("pdm_signing_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let config_path = directory.join("pdm_config.toml");
+        let key_path = directory.join("pdm_config.pub");
+        let contents = toml::to_string_pretty(&Config::default()).unwrap();
+        
+        // Without an installed key nothing is checked
+        assert!(signing::check_config_with(&config_path, &contents, &key_path, CheckAction::Refuse).is_ok());
+        
+        let (secret, public) = signing::generate_key();
+        std::fs::write(&key_path, &public).unwrap();
+        assert!(signing::check_config_with(&config_path, &contents, &key_path, CheckAction::Refuse).is_err());
+        
+        std::fs::write(signing::signature_path(&config_path), signing::sign(contents.as_bytes(), &secret).unwrap()).unwrap();
+        assert!(signing::check_config_with(&config_path, &contents, &key_path, CheckAction::Refuse).is_ok());
+        
+        // Any edit breaks the signature; Warn still loads
+        let tampered = contents.replace("max_total_current = 100.0", "max_total_current = 500.0");
+        assert_ne!(tampered, contents);
+        let error = signing::check_config_with(&config_path, &tampered, &key_path, CheckAction::Refuse).unwrap_err();
+        assert!(format!("{:#}", error).contains("does not match"));
+        assert!(signing::check_config_with(&config_path, &tampered, &key_path, CheckAction::Warn).is_ok());
+        
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
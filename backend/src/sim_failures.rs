@@ -0,0 +1,218 @@
+/*!
+ * Simulated Channel Failures
+ *
+ * For reliability demos the simulator can make channels fail on their own.
+ * Each channel gets a lifetime of ON-time drawn from an exponential
+ * distribution with the configured mean time between failures; once it is
+ * used up the channel develops a fault picked from the weighted failure
+ * modes. The failure is pinned as a simulated reading (like
+ * `POST /api/sim/channel/:id/reading`), so the normal fault detection,
+ * fault actions and alerts take it from there, and it persists until the
+ * reading override is cleared.
+ *
+ * Lifetimes and modes come from an RNG seeded with `seed`, so a run with the
+ * same seed and the same ON-times fails the same channels the same way.
+ */
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use crate::config::{SafetyConfig, SimFailureConfig};
+use crate::models::{time_step, Channel, ChannelFault, ChannelStatus, PdmState, SimReading};
+
+/// Faults a simulated failure can produce
+pub const FAILURE_FAULTS: [ChannelFault; 4] = [
+    ChannelFault::Overcurrent,
+    ChannelFault::Undervoltage,
+    ChannelFault::Overvoltage,
+    ChannelFault::ReverseCurrent,
+];
+
+/// A channel that has just failed
+#[derive(Debug, Clone, Copy)]
+pub struct SimFailure {
+    pub ch: u8,
+    pub fault: ChannelFault,
+    /// Reading to pin so the fault is detected
+    pub reading: SimReading,
+}
+
+/// Draws channel failures from the seeded RNG
+pub struct FailureInjector {
+    rng: StdRng,
+    /// ON-time left before each channel fails (s)
+    remaining: BTreeMap<u8, f64>,
+    last_tick: Option<Instant>,
+}
+
+impl FailureInjector {
+    pub fn new(config: &SimFailureConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            rng,
+            remaining: BTreeMap::new(),
+            last_tick: None,
+        }
+    }
+
+    /// Count down the ON channels' lifetimes, returning the channels that failed
+    ///
+    /// Channels with a pinned reading in `pinned` are skipped. At most
+    /// `max_step` of ON-time is counted per tick.
+    pub fn tick<T>(
+        &mut self,
+        config: &SimFailureConfig,
+        safety: &SafetyConfig,
+        state: &PdmState,
+        pinned: &HashMap<u8, T>,
+        now: Instant,
+        max_step: Duration,
+    ) -> Vec<SimFailure> {
+        let elapsed = self.last_tick.map_or(0.0, |last| time_step(last, now, max_step).as_secs_f64());
+        if self.last_tick.is_none_or(|last| now > last) {
+            self.last_tick = Some(now);
+        }
+
+        let mut channels: Vec<&Channel> = state.channels.values()
+            .filter(|channel| config.channels.is_empty() || config.channels.contains(&channel.ch))
+            .collect();
+        // Draw in channel order so a seed always gives the same lifetimes
+        channels.sort_unstable_by_key(|channel| channel.ch);
+
+        let mut failures = Vec::new();
+        for channel in channels {
+            if !self.remaining.contains_key(&channel.ch) {
+                let lifetime = self.lifetime(config.mtbf_secs);
+                self.remaining.insert(channel.ch, lifetime);
+            }
+            if channel.status != ChannelStatus::On || pinned.contains_key(&channel.ch) {
+                continue;
+            }
+
+            let remaining = self.remaining.get_mut(&channel.ch).expect("lifetime drawn above");
+            *remaining -= elapsed;
+            if *remaining > 0.0 {
+                continue;
+            }
+
+            let fault = self.failure_mode(config);
+            let next = self.lifetime(config.mtbf_secs);
+            self.remaining.insert(channel.ch, next);
+            failures.push(SimFailure {
+                ch: channel.ch,
+                fault,
+                reading: failure_reading(fault, channel, state.input_voltage, safety),
+            });
+        }
+        failures
+    }
+
+    /// Exponentially distributed time to failure (s)
+    fn lifetime(&mut self, mtbf_secs: f64) -> f64 {
+        let u: f64 = self.rng.gen();
+        -mtbf_secs * (1.0 - u).ln()
+    }
+
+    /// Weighted pick among the configured failure modes
+    fn failure_mode(&mut self, config: &SimFailureConfig) -> ChannelFault {
+        let total: f32 = config.modes.iter().map(|mode| mode.weight).sum();
+        let mut pick = self.rng.gen::<f32>() * total;
+        for mode in &config.modes {
+            if pick < mode.weight {
+                return mode.fault;
+            }
+            pick -= mode.weight;
+        }
+        config.modes.last().map_or(ChannelFault::Overcurrent, |mode| mode.fault)
+    }
+}
+
+/// Reading that makes the fault detection report `fault`
+fn failure_reading(fault: ChannelFault, channel: &Channel, input_voltage: f32, safety: &SafetyConfig) -> SimReading {
+    let (voltage, current) = match fault {
+        // Seized motor or chafed wire
+        ChannelFault::Overcurrent => (input_voltage, channel.effective_current_limit * 2.0),
+        // Corroded connector dropping the output voltage
+        ChannelFault::Undervoltage => ((safety.min_input_voltage * 0.8).max(0.0), channel.current * 0.5),
+        ChannelFault::Overvoltage => (safety.max_input_voltage + 2.0, channel.current),
+        // Load backfeeding the output
+        _ => (input_voltage, -(safety.max_reverse_current * 2.0 + 0.5)),
+    };
+    SimReading { voltage, current }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::{ChannelStatus, PdmState};
+    
+    #[tokio::test]
+    async fn test_simulated_failures_are_seeded_and_detected() {
+        use crate::config::{FailureModeConfig, SimFailureConfig};
+        use crate::hardware::HardwareManager;
+        use crate::models::ChannelFault;
+        use crate::sim_failures::FailureInjector;
+        use std::collections::HashMap;
+        use std::time::{Duration, Instant};
+        
+        let failures = SimFailureConfig {
+            enabled: true,
+            mtbf_secs: 10.0,
+            seed: Some(7),
+            channels: vec![1, 2, 3],
+            modes: vec![
+                FailureModeConfig { fault: ChannelFault::Overcurrent, weight: 1.0 },
+                FailureModeConfig { fault: ChannelFault::ReverseCurrent, weight: 1.0 },
+            ],
+        };
+        let safety = Config::default().safety;
+        let mut state = PdmState::new();
+        for channel in state.channels.values_mut() {
+            channel.status = ChannelStatus::On;
+        }
+        let pinned: HashMap<u8, ()> = HashMap::new();
+        
+        // The same seed fails the same channels the same way after the same ON-time
+        let run = || {
+            let mut injector = FailureInjector::new(&failures);
+            let start = Instant::now();
+            (0..=120).flat_map(|second| {
+                injector.tick(&failures, &safety, &state, &pinned, start + Duration::from_secs(second), Duration::from_secs(5))
+                    .into_iter()
+                    .map(move |failure| (second, failure.ch, failure.fault))
+            }).collect::<Vec<_>>()
+        };
+        let first = run();
+        assert!(!first.is_empty());
+        assert_eq!(first, run());
+        assert!(first.iter().all(|&(_, ch, _)| (1..=3).contains(&ch)));
+        
+        // Unsupported modes are rejected with the config
+        let mut config = Config::default();
+        config.simulation.failures = SimFailureConfig {
+            modes: vec![FailureModeConfig { fault: ChannelFault::OpenLoad, weight: 1.0 }],
+            ..failures.clone()
+        };
+        assert!(PdmState::from_config(&config).is_err());
+        
+        // A failure is pinned as a reading and tripped by the normal fault detection
+        config.simulation.failures = SimFailureConfig { mtbf_secs: 1e-9, channels: vec![2], ..failures };
+        config.simulation.fet_resistance_ohms = 0.0;
+        let pdm_state = std::sync::Arc::new(tokio::sync::RwLock::new(PdmState::from_config(&config).unwrap()));
+        let hardware_manager = HardwareManager::new(config.clone()).unwrap();
+        pdm_state.write().await.channels.get_mut(&2).unwrap().status = ChannelStatus::On;
+        for _ in 0..2 {
+            hardware_manager.simulate_channel_readings(&config, &pdm_state).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        hardware_manager.process_channel_faults(&config, &pdm_state).await.unwrap();
+        let state = pdm_state.read().await;
+        assert_eq!(state.channels[&2].status, ChannelStatus::Fault);
+        assert!(matches!(state.channels[&2].fault, Some(ChannelFault::Overcurrent | ChannelFault::ReverseCurrent)));
+    }
+}
@@ -0,0 +1,329 @@
+/*!
+ * Incremental State Streaming
+ *
+ * Live state streams send a full snapshot when a client connects and then
+ * only what changed since the last message:
+ *
+ * - `{"type": "snapshot", "seq": 1, "state": {...}}` - the complete `PdmState`
+ * - `{"type": "delta", "seq": 2, "system": {...}, "channels": {"3": {...}}}` -
+ *   only the fields that changed, keyed by channel number. A field that was
+ *   cleared (e.g. a fault) is sent as `null`.
+ *
+ * - `{"type": "replay", "samples": [...]}` - history points covering the
+ *   `replay_secs` a reconnecting client asked for, sent once before the
+ *   snapshot so charts fill the gap
+ *
+ * Readings only count as changed once they move beyond a deadband from the
+ * last value sent, so sensor noise doesn't generate traffic. A snapshot
+ * (keyframe) is repeated periodically so clients can't drift.
+ */
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::StreamConfig;
+use crate::history::HistorySample;
+use crate::models::{ChannelFault, ChannelStatus, PdmState, SystemStatus};
+use crate::precision;
+
+/// A message on a state stream
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    /// Recent history, oldest first
+    Replay { samples: Vec<HistorySample> },
+    /// Full state
+    Snapshot { seq: u64, state: Box<PdmState> },
+    /// Changes since the previous message
+    Delta {
+        seq: u64,
+        #[serde(skip_serializing_if = "SystemDelta::is_empty")]
+        system: SystemDelta,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        channels: BTreeMap<u8, ChannelDelta>,
+    },
+}
+
+impl StreamMessage {
+    /// SSE event name for the message
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            StreamMessage::Replay { .. } => "replay",
+            StreamMessage::Snapshot { .. } => "snapshot",
+            StreamMessage::Delta { .. } => "delta",
+        }
+    }
+}
+
+/// Changed system-level fields
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemDelta {
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::voltage_option")]
+    pub input_voltage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::current_option")]
+    pub total_current: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::temperature_option")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_status: Option<SystemStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emergency_latched: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub master_enabled: Option<bool>,
+}
+
+impl SystemDelta {
+    fn is_empty(&self) -> bool {
+        self.input_voltage.is_none()
+            && self.total_current.is_none()
+            && self.temperature.is_none()
+            && self.system_status.is_none()
+            && self.emergency_latched.is_none()
+            && self.master_enabled.is_none()
+    }
+}
+
+/// Changed fields of one channel
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelDelta {
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::voltage_option")]
+    pub voltage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::current_option")]
+    pub current: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::current_option")]
+    pub display_current: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "precision::temperature_option")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ChannelStatus>,
+    /// `Some(None)` (sent as `null`) means the fault was cleared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fault: Option<Option<ChannelFault>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_of_range: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuse_blown: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden: Option<bool>,
+}
+
+impl ChannelDelta {
+    fn is_empty(&self) -> bool {
+        self.voltage.is_none()
+            && self.current.is_none()
+            && self.display_current.is_none()
+            && self.temperature.is_none()
+            && self.status.is_none()
+            && self.fault.is_none()
+            && self.warning.is_none()
+            && self.anomaly.is_none()
+            && self.out_of_range.is_none()
+            && self.pending.is_none()
+            && self.fuse_blown.is_none()
+            && self.overridden.is_none()
+    }
+}
+
+/// Produces the snapshot/delta sequence for one stream client
+pub struct StateDiffer {
+    config: StreamConfig,
+    keyframe_ticks: u64,
+    /// What the client has been sent so far
+    baseline: Option<PdmState>,
+    ticks_since_keyframe: u64,
+    seq: u64,
+}
+
+impl StateDiffer {
+    /// Create a differ; the first call to `next` yields a snapshot
+    pub fn new(config: &StreamConfig) -> Self {
+        Self {
+            config: config.clone(),
+            keyframe_ticks: (config.keyframe_interval_ms / config.interval_ms.max(1)).max(1),
+            baseline: None,
+            ticks_since_keyframe: 0,
+            seq: 0,
+        }
+    }
+
+    /// The message to send for the current state, if anything changed
+    pub fn next(&mut self, state: &PdmState) -> Option<StreamMessage> {
+        self.ticks_since_keyframe += 1;
+
+        let keyframe_due = match &self.baseline {
+            None => true,
+            // A changed channel set can't be expressed as a delta
+            Some(baseline) => {
+                self.ticks_since_keyframe >= self.keyframe_ticks
+                    || baseline.channels.len() != state.channels.len()
+                    || !state.channels.keys().all(|ch| baseline.channels.contains_key(ch))
+            }
+        };
+
+        if keyframe_due {
+            self.baseline = Some(state.clone());
+            self.ticks_since_keyframe = 0;
+            self.seq += 1;
+            return Some(StreamMessage::Snapshot { seq: self.seq, state: Box::new(state.clone()) });
+        }
+
+        let config = &self.config;
+        let baseline = self.baseline.as_mut()?;
+
+        let system = SystemDelta {
+            input_voltage: moved(&mut baseline.input_voltage, state.input_voltage, config.voltage_deadband),
+            total_current: moved(&mut baseline.total_current, state.total_current, config.current_deadband),
+            temperature: moved(&mut baseline.temperature, state.temperature, config.temperature_deadband),
+            system_status: changed(&mut baseline.system_status, &state.system_status),
+            emergency_latched: changed(&mut baseline.emergency_latched, &state.emergency_latched),
+            master_enabled: changed(&mut baseline.master_enabled, &state.master_enabled),
+        };
+
+        let mut channels = BTreeMap::new();
+        for (ch, channel) in &state.channels {
+            let Some(sent) = baseline.channels.get_mut(ch) else { continue };
+            let delta = ChannelDelta {
+                voltage: moved(&mut sent.voltage, channel.voltage, config.voltage_deadband),
+                current: moved(&mut sent.current, channel.current, config.current_deadband),
+                display_current: channel.display_current.and_then(|value| match &mut sent.display_current {
+                    Some(sent) => moved(sent, value, config.current_deadband),
+                    None => {
+                        sent.display_current = Some(value);
+                        Some(value)
+                    }
+                }),
+                temperature: channel.temperature.and_then(|value| match &mut sent.temperature {
+                    Some(sent) => moved(sent, value, config.temperature_deadband),
+                    // First reading since the snapshot
+                    None => {
+                        sent.temperature = Some(value);
+                        Some(value)
+                    }
+                }),
+                status: changed(&mut sent.status, &channel.status),
+                fault: changed(&mut sent.fault, &channel.fault),
+                warning: changed(&mut sent.warning, &channel.warning),
+                anomaly: changed(&mut sent.anomaly, &channel.anomaly),
+                out_of_range: changed(&mut sent.out_of_range, &channel.out_of_range),
+                pending: changed(&mut sent.pending, &channel.pending),
+                fuse_blown: changed(&mut sent.fuse_blown, &channel.fuse_blown),
+                overridden: changed(&mut sent.overridden, &channel.overridden),
+            };
+            if !delta.is_empty() {
+                channels.insert(*ch, delta);
+            }
+        }
+
+        if system.is_empty() && channels.is_empty() {
+            return None;
+        }
+
+        self.seq += 1;
+        Some(StreamMessage::Delta { seq: self.seq, system, channels })
+    }
+}
+
+/// New reading if it moved beyond the deadband from the last one sent
+fn moved(sent: &mut f32, value: f32, deadband: f32) -> Option<f32> {
+    if (value - *sent).abs() > deadband {
+        *sent = value;
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// New value if it differs from the last one sent
+fn changed<T: PartialEq + Clone>(sent: &mut T, value: &T) -> Option<T> {
+    if sent != value {
+        *sent = value.clone();
+        Some(value.clone())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::{ChannelStatus, PdmState};
+    use crate::test_support::test_app;
+    
+    #[test]
+    fn test_state_stream_sends_snapshot_then_deltas() {
+        use crate::config::StreamConfig;
+        use crate::stream::{StateDiffer, StreamMessage};
+        
+        let config = StreamConfig { interval_ms: 100, keyframe_interval_ms: 1000, ..Default::default() };
+        let mut differ = StateDiffer::new(&config);
+        let mut state = PdmState::new();
+        
+        // First message is a full snapshot
+        assert!(matches!(differ.next(&state), Some(StreamMessage::Snapshot { seq: 1, .. })));
+        
+        // Noise within the deadband sends nothing
+        state.input_voltage += 0.01;
+        assert!(differ.next(&state).is_none());
+        
+        // Only the changed fields are sent
+        state.update_channel(3, 13.5, 4.2, ChannelStatus::On);
+        let Some(StreamMessage::Delta { seq, system, channels }) = differ.next(&state) else {
+            panic!("expected a delta");
+        };
+        assert_eq!(seq, 2);
+        assert!(system.input_voltage.is_none());
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[&3].status, Some(ChannelStatus::On));
+        assert_eq!(channels[&3].current, Some(4.2));
+        assert!(channels[&3].fault.is_none());
+        
+        // A keyframe is re-sent once the interval has passed
+        let messages: Vec<_> = (0..10).filter_map(|_| differ.next(&state)).collect();
+        assert!(matches!(messages.as_slice(), [StreamMessage::Snapshot { .. }]));
+    }
+    
+    #[tokio::test]
+    async fn test_status_stream_replays_recent_history_on_connect() {
+        use crate::config::HistoryConfig;
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tokio_stream::StreamExt;
+        use tower::ServiceExt;
+        
+        let mut config = Config {
+            history: HistoryConfig { capacity: 10, sample_interval_ms: 0, ..Default::default() },
+            ..Default::default()
+        };
+        config.audit.enabled = false;
+        let (app, pdm_state, hardware_manager) = test_app(config);
+        {
+            let state = pdm_state.read().await;
+            let mut history = hardware_manager.history().lock().unwrap();
+            for _ in 0..3 {
+                history.record(&state, std::time::Instant::now());
+            }
+        }
+        
+        let response = app
+            .oneshot(Request::get("/api/status/stream?replay_secs=60").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body().into_data_stream();
+        
+        // History first, then live data starting with a snapshot
+        let frame = String::from_utf8(body.next().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(frame.starts_with("event: replay\n"), "{}", frame);
+        let data: serde_json::Value = serde_json::from_str(frame.lines().nth(1).unwrap().trim_start_matches("data: ")).unwrap();
+        assert_eq!(data["samples"].as_array().unwrap().len(), 3);
+        
+        let frame = String::from_utf8(body.next().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(frame.starts_with("event: snapshot\n"), "{}", frame);
+    }
+}
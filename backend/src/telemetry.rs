@@ -0,0 +1,267 @@
+/*!
+ * On-Disk Telemetry Recorder
+ *
+ * Flight-recorder style logging for post-drive analysis. A background task
+ * appends one JSON line per sample to the current file; a new file is
+ * started every `rotation_secs` (hourly by default) and only the newest
+ * `retain_files` files are kept. Files are named after the UTC start of
+ * their period, so they sort chronologically by name.
+ */
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use tracing::{info, warn};
+
+use crate::config::TelemetryConfig;
+use crate::models::{ChannelFault, ChannelStatus, PdmState, SystemStatus};
+
+const FILE_PREFIX: &str = "telemetry-";
+const FILE_EXTENSION: &str = ".jsonl";
+
+/// One line of the telemetry log
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryRecord {
+    pub timestamp: DateTime<Utc>,
+    pub input_voltage: f32,
+    pub total_current: f32,
+    pub temperature: f32,
+    pub system_status: SystemStatus,
+    pub channels: Vec<ChannelRecord>,
+}
+
+/// Readings of one channel in a telemetry record
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelRecord {
+    pub ch: u8,
+    pub status: ChannelStatus,
+    pub voltage: f32,
+    pub current: f32,
+    pub fault: Option<ChannelFault>,
+}
+
+impl TelemetryRecord {
+    pub fn capture(state: &PdmState, timestamp: DateTime<Utc>) -> Self {
+        let mut channels: Vec<ChannelRecord> = state.channels.values()
+            .map(|channel| ChannelRecord {
+                ch: channel.ch,
+                status: channel.status.clone(),
+                voltage: channel.voltage,
+                current: channel.current,
+                fault: channel.fault,
+            })
+            .collect();
+        channels.sort_by_key(|channel| channel.ch);
+
+        Self {
+            timestamp,
+            input_voltage: state.input_voltage,
+            total_current: state.total_current,
+            temperature: state.temperature,
+            system_status: state.system_status.clone(),
+            channels,
+        }
+    }
+}
+
+/// A stored telemetry file
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryFile {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Start of the period the file covers
+    pub started: DateTime<Utc>,
+}
+
+/// Appends telemetry samples to rotating files
+pub struct TelemetryRecorder {
+    config: TelemetryConfig,
+    /// File currently being appended to, with its name
+    current: Option<(String, File)>,
+}
+
+impl TelemetryRecorder {
+    pub fn new(config: TelemetryConfig) -> Result<Self> {
+        if config.rotation_secs == 0 {
+            return Err(anyhow!("telemetry.rotation_secs must be greater than zero"));
+        }
+        if config.retain_files == 0 {
+            return Err(anyhow!("telemetry.retain_files must be at least 1"));
+        }
+        Ok(Self { config, current: None })
+    }
+
+    /// Start the sampling loop
+    pub async fn start(mut self, pdm_state: Arc<RwLock<PdmState>>) -> Result<()> {
+        fs::create_dir_all(&self.config.directory).await
+            .with_context(|| format!("Failed to create telemetry directory {}", self.config.directory))?;
+        info!("Recording telemetry to {} every {}ms (new file every {}s, keeping {})",
+              self.config.directory, self.config.interval_ms, self.config.rotation_secs, self.config.retain_files);
+
+        let mut sample_interval = interval(Duration::from_millis(self.config.interval_ms.max(1)));
+        let mut failing = false;
+
+        loop {
+            sample_interval.tick().await;
+            let record = TelemetryRecord::capture(&*pdm_state.read().await, Utc::now());
+
+            match self.append(&record).await {
+                Ok(()) => failing = false,
+                Err(e) => {
+                    // Log once per outage (e.g. a full disk) rather than every sample
+                    if !failing {
+                        warn!("Telemetry write failed: {:#}", e);
+                    }
+                    failing = true;
+                }
+            }
+        }
+    }
+
+    /// Append one record, rotating to a new file when its period starts
+    pub async fn append(&mut self, record: &TelemetryRecord) -> Result<()> {
+        let name = file_name(record.timestamp, self.config.rotation_secs);
+        if !matches!(&self.current, Some((current, _)) if *current == name) {
+            let path = Path::new(&self.config.directory).join(&name);
+            let file = OpenOptions::new().create(true).append(true).open(&path).await
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            self.current = Some((name, file));
+            self.prune().await?;
+        }
+
+        let (_, file) = self.current.as_mut().expect("telemetry file opened above");
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    /// Delete the oldest files beyond the retention count
+    async fn prune(&self) -> Result<()> {
+        let files = list_files(&self.config.directory).await?;
+        let excess = files.len().saturating_sub(self.config.retain_files);
+        for file in &files[..excess] {
+            let path = Path::new(&self.config.directory).join(&file.name);
+            fs::remove_file(&path).await
+                .with_context(|| format!("Failed to delete {}", path.display()))?;
+            info!("Deleted old telemetry file {}", file.name);
+        }
+        Ok(())
+    }
+}
+
+/// File for the rotation period containing `timestamp`
+fn file_name(timestamp: DateTime<Utc>, rotation_secs: u64) -> String {
+    let rotation_secs = rotation_secs.max(1) as i64;
+    let start = timestamp.timestamp().div_euclid(rotation_secs) * rotation_secs;
+    let start = Utc.timestamp_opt(start, 0).single().unwrap_or(timestamp);
+    format!("{}{}{}", FILE_PREFIX, start.format("%Y%m%dT%H%M%SZ"), FILE_EXTENSION)
+}
+
+/// Period start encoded in a telemetry file name
+fn parse_file_name(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_EXTENSION)?;
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%SZ").ok().map(|t| t.and_utc())
+}
+
+/// Telemetry files in `directory`, oldest first
+pub async fn list_files(directory: &str) -> Result<Vec<TelemetryFile>> {
+    let mut files = Vec::new();
+    let mut entries = match fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        // Nothing recorded yet
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", directory)),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(started) = parse_file_name(&name) else { continue };
+        files.push(TelemetryFile {
+            name,
+            size_bytes: entry.metadata().await?.len(),
+            started,
+        });
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+/// Path of a telemetry file, refusing names that are not telemetry files
+pub fn file_path(directory: &str, name: &str) -> Option<PathBuf> {
+    parse_file_name(name).map(|_| Path::new(directory).join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::models::PdmState;
+    use crate::test_support::{json_body, test_app};
+    
+    #[tokio::test]
+    async fn test_telemetry_log_rotates_and_is_served() {
+        use crate::config::TelemetryConfig;
+        use crate::telemetry::{TelemetryRecord, TelemetryRecorder};
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use chrono::{TimeZone, Utc};
+        use tower::ServiceExt;
+        
+        let directory = std::env::temp_dir().join(format!("pdm_telemetry_test_{}", std::process::id()));
+        let directory = directory.to_string_lossy().into_owned();
+        let telemetry = TelemetryConfig {
+            directory: directory.clone(),
+            rotation_secs: 3600,
+            retain_files: 2,
+            ..Default::default()
+        };
+        tokio::fs::create_dir_all(&directory).await.unwrap();
+        
+        // Two samples in each of three hours; only the newest two files are kept
+        let state = PdmState::new();
+        let mut recorder = TelemetryRecorder::new(telemetry.clone()).unwrap();
+        for (hour, minute) in [(10, 0), (10, 30), (11, 5), (11, 59), (12, 0), (12, 1)] {
+            let timestamp = Utc.with_ymd_and_hms(2026, 5, 1, hour, minute, 0).unwrap();
+            recorder.append(&TelemetryRecord::capture(&state, timestamp)).await.unwrap();
+        }
+        let files = crate::telemetry::list_files(&directory).await.unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, vec!["telemetry-20260501T110000Z.jsonl", "telemetry-20260501T120000Z.jsonl"]);
+        
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        config.telemetry = telemetry;
+        let (app, _, _) = test_app(config);
+        let get = |path: &str| Request::get(path).body(Body::empty()).unwrap();
+        
+        let response = app.clone().oneshot(get("/api/telemetry/files")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let listing = json_body(response).await;
+        assert_eq!(listing.as_array().unwrap().len(), 2);
+        assert_eq!(listing[1]["started"], "2026-05-01T12:00:00Z");
+        
+        let response = app.clone().oneshot(get("/api/telemetry/files/telemetry-20260501T110000Z.jsonl")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let lines: Vec<serde_json::Value> = body.split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["channels"].as_array().unwrap().len(), state.channels.len());
+        
+        for name in ["telemetry-20260501T100000Z.jsonl", "pdm_config.toml", "..%2Fpdm_config.toml"] {
+            let response = app.clone().oneshot(get(&format!("/api/telemetry/files/{}", name))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", name);
+        }
+        
+        tokio::fs::remove_dir_all(&directory).await.unwrap();
+    }
+}
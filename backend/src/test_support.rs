@@ -0,0 +1,33 @@
+/*!
+ * Test Support
+ *
+ * Fixtures shared by the unit tests of the backend modules
+ */
+
+use axum::response::Response;
+use axum::Router;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::config::Config;
+use crate::fleet::FleetMonitor;
+use crate::hardware::HardwareManager;
+use crate::models::PdmState;
+
+/// Build the API router over a fresh state for `config`
+///
+/// Returns the router with the state and hardware manager behind it.
+pub fn test_app(config: Config) -> (Router, Arc<RwLock<PdmState>>, Arc<HardwareManager>) {
+    let pdm_state = Arc::new(RwLock::new(PdmState::from_config(&config).unwrap()));
+    let fleet = Arc::new(FleetMonitor::new(config.aggregator.clone()).unwrap());
+    let hardware_manager = Arc::new(HardwareManager::new(config).unwrap());
+    let app = crate::api::create_router(pdm_state.clone(), hardware_manager.clone(), fleet);
+    (app, pdm_state, hardware_manager)
+}
+
+/// Read a response body as JSON
+pub async fn json_body(response: Response) -> Value {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
@@ -0,0 +1,278 @@
+/*!
+ * Hardware Validation Test Vectors
+ *
+ * A test vector is a list of channel commands, each followed by a settle
+ * time and the readings expected afterwards. Running one against a unit
+ * (see `POST /api/validate`) produces a per-step report with the measured
+ * values and their deltas from the expected ones, so a production test
+ * fixture can pass or fail new hardware against a known-good reference.
+ *
+ * Vectors are sent inline or stored as `<name>.json` in
+ * `validation.vector_dir`.
+ */
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::models::{Channel, ChannelStatus};
+
+/// Longest settle time a single step may ask for (ms)
+pub const MAX_SETTLE_MS: u64 = 60_000;
+
+/// Body of `POST /api/validate`: an inline vector or the name of a stored one
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ValidateRequest {
+    Stored { vector: String },
+    Inline(TestVector),
+}
+
+/// A named sequence of validation steps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVector {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tolerances for steps that do not set their own
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    pub steps: Vec<TestStep>,
+}
+
+impl TestVector {
+    /// Refuse steps that would stall the run, e.g. a mistyped settle time
+    pub fn validate(&self) -> Result<()> {
+        if let Some((index, step)) = self.steps.iter().enumerate().find(|(_, step)| step.settle_ms > MAX_SETTLE_MS) {
+            return Err(anyhow!(
+                "Step {} settle_ms {} exceeds the {}ms maximum",
+                index, step.settle_ms, MAX_SETTLE_MS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One command and the readings expected after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestStep {
+    pub channel: u8,
+    pub command: StepCommand,
+    /// Wait before sampling the readings (ms)
+    #[serde(default)]
+    pub settle_ms: u64,
+    #[serde(default)]
+    pub expect: ExpectedReading,
+    #[serde(default)]
+    pub tolerance: Option<Tolerance>,
+}
+
+/// Command applied to the channel at the start of a step
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepCommand {
+    On,
+    Off,
+    /// Leave the channel as it is and only check the readings
+    Hold,
+}
+
+/// Expected readings; unset fields are not checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpectedReading {
+    pub status: Option<ChannelStatus>,
+    pub voltage: Option<f32>,
+    pub current: Option<f32>,
+}
+
+/// Allowed absolute deviation from the expected readings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tolerance {
+    /// Volts
+    pub voltage: f32,
+    /// Amps
+    pub current: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            voltage: 0.5,
+            current: 0.5,
+        }
+    }
+}
+
+/// Outcome of one step
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub step: usize,
+    pub channel: u8,
+    pub command: StepCommand,
+    pub passed: bool,
+    /// Measured readings (absent if the command itself failed)
+    pub status: Option<ChannelStatus>,
+    pub voltage: Option<f32>,
+    pub current: Option<f32>,
+    /// Measured minus expected
+    pub voltage_delta: Option<f32>,
+    pub current_delta: Option<f32>,
+    /// Why the step failed
+    pub failures: Vec<String>,
+}
+
+impl StepResult {
+    /// A step whose command could not be applied
+    pub fn command_failed(step: usize, test_step: &TestStep, error: String) -> Self {
+        Self {
+            step,
+            channel: test_step.channel,
+            command: test_step.command,
+            passed: false,
+            status: None,
+            voltage: None,
+            current: None,
+            voltage_delta: None,
+            current_delta: None,
+            failures: vec![error],
+        }
+    }
+}
+
+/// Pass/fail report for a whole vector
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub name: Option<String>,
+    pub passed: bool,
+    pub steps: Vec<StepResult>,
+}
+
+impl ValidationReport {
+    pub fn new(name: Option<String>, steps: Vec<StepResult>) -> Self {
+        Self {
+            name,
+            passed: steps.iter().all(|step| step.passed),
+            steps,
+        }
+    }
+}
+
+/// Compare a channel's readings with a step's expectations
+pub fn check_step(step: usize, test_step: &TestStep, channel: &Channel, default_tolerance: Tolerance) -> StepResult {
+    let tolerance = test_step.tolerance.unwrap_or(default_tolerance);
+    let expect = &test_step.expect;
+    let mut failures = Vec::new();
+
+    if let Some(status) = &expect.status {
+        if *status != channel.status {
+            failures.push(format!("status {:?}, expected {:?}", channel.status, status));
+        }
+    }
+    let voltage_delta = expect.voltage.map(|expected| channel.voltage - expected);
+    if let Some(delta) = voltage_delta.filter(|delta| delta.abs() > tolerance.voltage) {
+        failures.push(format!("voltage off by {:.3}V (tolerance {:.3}V)", delta, tolerance.voltage));
+    }
+    let current_delta = expect.current.map(|expected| channel.current - expected);
+    if let Some(delta) = current_delta.filter(|delta| delta.abs() > tolerance.current) {
+        failures.push(format!("current off by {:.3}A (tolerance {:.3}A)", delta, tolerance.current));
+    }
+
+    StepResult {
+        step,
+        channel: test_step.channel,
+        command: test_step.command,
+        passed: failures.is_empty(),
+        status: Some(channel.status.clone()),
+        voltage: Some(channel.voltage),
+        current: Some(channel.current),
+        voltage_delta,
+        current_delta,
+        failures,
+    }
+}
+
+/// Load `<name>.json` from the vector directory
+///
+/// Names are restricted to letters, digits, '-' and '_' so a request cannot
+/// reach outside the directory.
+pub fn load_vector(dir: &str, name: &str) -> Result<TestVector> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!("Invalid test vector name '{}'", name));
+    }
+
+    let path = Path::new(dir).join(format!("{}.json", name));
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read test vector {}", path.display()))?;
+    let mut vector: TestVector = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid test vector {}", path.display()))?;
+    vector.name.get_or_insert_with(|| name.to_string());
+    Ok(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::test_support::{json_body, test_app};
+    
+    #[tokio::test]
+    async fn test_validation_vector_reports_step_deltas() {
+        use crate::models::SimReading;
+        use axum::body::Body;
+        use axum::http::{header, Request, StatusCode};
+        use tower::ServiceExt;
+        
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        config.simulation.fet_resistance_ohms = 0.0;
+        config.hardware.monitoring_interval_ms = 10;
+        let (app, pdm_state, hardware_manager) = test_app(config);
+        hardware_manager.set_sim_override(2, SimReading { voltage: 12.0, current: 3.0 });
+        tokio::spawn({
+            let hardware_manager = hardware_manager.clone();
+            let pdm_state = pdm_state.clone();
+            async move { hardware_manager.start_monitoring(pdm_state).await }
+        });
+        
+        let validate = |body: serde_json::Value| {
+            Request::post("/api/validate")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let vector = serde_json::json!({
+            "name": "ch2-load",
+            "steps": [
+                {"channel": 2, "command": "on", "settle_ms": 100,
+                 "expect": {"status": "ON", "voltage": 12.1, "current": 3.0}},
+                {"channel": 2, "command": "hold",
+                 "expect": {"current": 5.0}, "tolerance": {"current": 0.5}},
+                {"channel": 2, "command": "off", "expect": {"status": "OFF"}},
+            ],
+        });
+        let response = app.clone().oneshot(validate(vector)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let report = json_body(response).await;
+        
+        assert_eq!(report["name"], "ch2-load");
+        assert_eq!(report["passed"], false);
+        let steps = report["steps"].as_array().unwrap();
+        assert_eq!(steps[0]["passed"], true);
+        assert!((steps[0]["voltage_delta"].as_f64().unwrap() + 0.1).abs() < 1e-3);
+        assert_eq!(steps[1]["passed"], false);
+        assert!((steps[1]["current_delta"].as_f64().unwrap() + 2.0).abs() < 1e-3);
+        assert_eq!(steps[1]["failures"].as_array().unwrap().len(), 1);
+        assert_eq!(steps[2]["passed"], true);
+        
+        // Stored vector names cannot escape the vector directory
+        let response = app.clone().oneshot(validate(serde_json::json!({"vector": "../config"}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        
+        // A settle time past the maximum is refused before anything switches
+        let stalled = serde_json::json!({"steps": [{"channel": 2, "command": "on", "settle_ms": 3_600_000}]});
+        let response = app.oneshot(validate(stalled)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(json_body(response).await["error"].as_str().unwrap().contains("settle_ms"));
+    }
+}
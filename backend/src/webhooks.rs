@@ -0,0 +1,162 @@
+/*!
+ * State-Change Webhooks
+ *
+ * Subscribes to the system event log and POSTs selected events (faults,
+ * emergency shutdowns, status changes) as JSON to a configured URL, for
+ * alerting integrations. Deliveries run in their own tasks with
+ * exponential backoff, so a slow or unreachable endpoint never holds up
+ * monitoring. When a secret is configured each payload is signed with
+ * HMAC-SHA256 in the `X-PDM-Signature` header.
+ */
+
+use anyhow::{bail, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::config::{WebhookConfig, WebhookEvent};
+use crate::events::{Event, EventKind, EventLog};
+
+/// Header carrying the payload signature
+pub const SIGNATURE_HEADER: &str = "X-PDM-Signature";
+
+/// Delivers matching events to the configured webhook
+pub struct WebhookDispatcher {
+    config: WebhookConfig,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    /// Create a dispatcher for the configured webhook
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let Some(url) = config.url.clone() else {
+            bail!("Webhooks are enabled but webhooks.url is not set");
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()?;
+
+        Ok(Self { config, url, client })
+    }
+
+    /// Forward events until the event log is dropped
+    pub async fn start(self: Arc<Self>, events: Arc<EventLog>) -> Result<()> {
+        info!("Sending {:?} webhooks to {}", self.config.events, self.url);
+        let mut receiver = events.subscribe();
+
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if !self.wants(&event) {
+                        continue;
+                    }
+                    let dispatcher = Arc::clone(&self);
+                    tokio::spawn(async move { dispatcher.deliver(&event).await });
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Webhook dispatcher fell behind, {} events not sent", skipped);
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    /// Whether an event is one the webhook is subscribed to
+    pub fn wants(&self, event: &Event) -> bool {
+        webhook_event_for(event.kind).is_some_and(|kind| self.config.events.contains(&kind))
+    }
+
+    /// POST one event, retrying with exponential backoff
+    async fn deliver(&self, event: &Event) {
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+
+        let mut backoff = Duration::from_millis(self.config.initial_backoff_ms);
+        for attempt in 0..=self.config.max_retries {
+            let mut request = self.client.post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            if let Some(secret) = &self.config.secret {
+                request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+            }
+
+            match request.send().await.and_then(|response| response.error_for_status()) {
+                Ok(_) => {
+                    debug!("Webhook delivered: {:?}", event.kind);
+                    return;
+                }
+                Err(e) if attempt < self.config.max_retries => {
+                    debug!("Webhook attempt {} failed: {} - retrying in {}ms", attempt + 1, e, backoff.as_millis());
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => {
+                    warn!("Webhook for {:?} failed after {} attempts: {}", event.kind, attempt + 1, e);
+                }
+            }
+        }
+    }
+}
+
+/// Webhook category an event kind belongs to, if any
+fn webhook_event_for(kind: EventKind) -> Option<WebhookEvent> {
+    match kind {
+        EventKind::ChannelFault => Some(WebhookEvent::Fault),
+        EventKind::EmergencyShutdown | EventKind::HeartbeatLost | EventKind::InterlockOpened => {
+            Some(WebhookEvent::EmergencyShutdown)
+        }
+        EventKind::SystemStatusChanged | EventKind::EnergyBudget | EventKind::LoadShedding
+        | EventKind::InterlockClosed => {
+            Some(WebhookEvent::StatusChange)
+        }
+        EventKind::Alert | EventKind::AlertCleared | EventKind::SwitchRate => Some(WebhookEvent::Alert),
+        EventKind::HardwareDisconnected | EventKind::HardwareReconnected | EventKind::MaintenanceConcern => None,
+    }
+}
+
+/// Hex HMAC-SHA256 of a payload
+pub fn sign(secret: &str, payload: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    
+    #[tokio::test]
+    async fn test_webhook_signature_and_event_selection() {
+        use crate::config::WebhookEvent;
+        use crate::events::{EventKind, EventLog};
+        use crate::webhooks::{sign, WebhookDispatcher};
+        
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        
+        let mut config = Config::default();
+        config.webhooks.url = Some("http://127.0.0.1:9/hook".to_string());
+        config.webhooks.events = vec![WebhookEvent::Fault];
+        let dispatcher = WebhookDispatcher::new(config.webhooks).unwrap();
+        
+        let log = EventLog::new();
+        log.emit(EventKind::ChannelFault, Some(1), "Channel 1 Overcurrent");
+        log.emit(EventKind::SystemStatusChanged, None, "System status Normal -> Warning");
+        let events = log.recent(2);
+        assert!(dispatcher.wants(&events[0]));
+        assert!(!dispatcher.wants(&events[1]));
+    }
+}